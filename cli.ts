@@ -24,7 +24,7 @@ function getRbacStatePDA() {
 }
 
 function getRolePDA(roleName: string) {
-    return PublicKey.findProgramAddressSync([Buffer.from("role"), Buffer.from(roleName)], PROGRAM_ID)[0];
+    return PublicKey.findProgramAddressSync([Buffer.from("role:"), Buffer.from(roleName)], PROGRAM_ID)[0];
 }
 
 function getUserRolePDA(user: PublicKey, roleName: string) {
@@ -42,7 +42,7 @@ async function main() {
     try {
         if (command === "initialize") {
             console.log(`\n⏳ Initializing RBAC State on Devnet...`);
-            const tx = await program.methods.initialize()
+            const tx = await program.methods.initialize({ maxRoles: 0, changeCooldownSecs: new anchor.BN(0), guardian: null })
                 .accountsPartial({
                     rbacState: getRbacStatePDA(),
                     admin: wallet.publicKey,
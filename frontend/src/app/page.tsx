@@ -9,6 +9,7 @@ import {
   getRbacStatePDA,
   getRolePDA,
   getUserRolePDA,
+  getUserStatusPDA,
   PERMISSIONS,
 } from "../utils/anchor";
 import {
@@ -530,6 +531,7 @@ function CheckPermissionCard() {
         .accounts({
           role: getRolePDA(roleName),
           userRole: getUserRolePDA(targetPubkey, roleName),
+          userStatus: getUserStatusPDA(targetPubkey),
         })
         .view();
 
@@ -33,7 +33,7 @@ export const getRbacStatePDA = () => {
 
 export const getRolePDA = (roleName: string) => {
     return PublicKey.findProgramAddressSync(
-        [Buffer.from("role"), Buffer.from(roleName)],
+        [Buffer.from("role:"), Buffer.from(roleName)],
         PROGRAM_ID
     )[0];
 };
@@ -44,3 +44,10 @@ export const getUserRolePDA = (userWallet: PublicKey, roleName: string) => {
         PROGRAM_ID
     )[0];
 };
+
+export const getUserStatusPDA = (userWallet: PublicKey) => {
+    return PublicKey.findProgramAddressSync(
+        [Buffer.from("user_status"), userWallet.toBuffer()],
+        PROGRAM_ID
+    )[0];
+};
@@ -0,0 +1,38 @@
+//! Helpers for programs that gate their own instructions on this one, enabled
+//! with the `cpi` feature. Anchor's generated `crate::cpi` module holds the raw
+//! per-instruction wrappers and their account structs; these derive the PDAs a
+//! caller has to pass and unwrap the returned verdicts.
+
+use anchor_lang::prelude::*;
+
+use crate::cpi;
+use crate::cpi::accounts::{CheckPermission, HasPermission};
+
+pub use crate::seeds::{rbac_state_address, role_address, user_role_address, user_status_address};
+
+/// Whether the checked user holds `permission`, per `check_permission`.
+pub fn check_permission<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, CheckPermission<'info>>,
+    permission: u32,
+) -> Result<bool> {
+    Ok(cpi::check_permission(ctx, permission)?.get())
+}
+
+/// Fail the whole transaction unless the checked user holds `permission`, with
+/// the denial error `assert_has_permission` reports.
+pub fn require_permission<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, CheckPermission<'info>>,
+    permission: u32,
+) -> Result<()> {
+    cpi::assert_has_permission(ctx, permission)
+}
+
+/// Whether `user` holds `permission` through the passed assignment, per the
+/// state-free `has_permission` view.
+pub fn has_permission<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, HasPermission<'info>>,
+    user: Pubkey,
+    permission: u32,
+) -> Result<bool> {
+    Ok(cpi::has_permission(ctx, user, permission)?.get())
+}
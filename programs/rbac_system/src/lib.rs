@@ -1,469 +1,8949 @@
+// create_role's optional gates take it (and Anchor's generated CPI/client
+// wrappers) past clippy's default argument limit
+#![allow(clippy::too_many_arguments)]
+
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::system_program::{create_account, transfer, CreateAccount, Transfer};
+use anchor_lang::Discriminator;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::{self, TokenAccount};
 
 declare_id!("826VeESV6R1DQnt5dELnGHx7j3xewoCRYX3nN4gJ9p2T");
 
+pub mod seeds;
+#[cfg(feature = "cpi")]
+pub mod guard;
+#[cfg(test)]
+mod tests;
+
 #[program]
 pub mod rbac_system {
     use super::*;
 
     /// Initialize the RBAC system with an admin
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    pub fn initialize(ctx: Context<Initialize>, config: InitializeConfig) -> Result<()> {
+        require!(config.change_cooldown_secs >= 0, RbacError::InvalidCooldown);
         let rbac_state = &mut ctx.accounts.rbac_state;
         rbac_state.admin = ctx.accounts.admin.key();
+        rbac_state.admins = vec![ctx.accounts.admin.key()];
         rbac_state.bump = ctx.bumps.rbac_state;
-        rbac_state.role_count = 0;
+        rbac_state.role_count = 1;
         rbac_state.assignment_count = 0;
-        
-        // Let user manually create admin role. The initial creator is already stored as admin.
+        rbac_state.max_roles = config.max_roles;
+        rbac_state.change_cooldown_secs = config.change_cooldown_secs;
+        rbac_state.guardian = config.guardian;
+        rbac_state.max_delegation_depth = DEFAULT_MAX_DELEGATION_DEPTH;
+        rbac_state.version = RbacState::current_version();
+        ctx.accounts.role_registry.bump = ctx.bumps.role_registry;
+        ctx.accounts.role_registry.names = vec![ADMIN_ROLE_NAME.to_string()];
+        ctx.accounts.admin_history.bump = ctx.bumps.admin_history;
+        ctx.accounts.audit_log.bump = ctx.bumps.audit_log;
+
+        // The built-in admin role exists from the start so it can be assigned right away
+        let current_time = Clock::get()?.unix_timestamp;
+        let admin_role = &mut ctx.accounts.admin_role;
+        admin_role.name = ADMIN_ROLE_NAME.to_string();
+        admin_role.permissions = PERM_ADMIN;
+        admin_role.created_at = current_time;
+        admin_role.bump = ctx.bumps.admin_role;
+        admin_role.active = true;
+        admin_role.version = Role::current_version();
+
         emit!(RbacInitialized {
             admin: ctx.accounts.admin.key(),
-            timestamp: Clock::get()?.unix_timestamp,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleCreated {
+            name: ADMIN_ROLE_NAME.to_string(),
+            permissions: PERM_ADMIN,
+            breakglass: false,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
         });
         
         Ok(())
     }
 
-    /// Create a new role with specific bitmask permissions
+    /// Decommission the deployment: close `RbacState` and the other singletons
+    /// `initialize` created, refunding their rent to the admin. Every role (the
+    /// built-in `admin` one included) and assignment must be gone first so no
+    /// account is left pointing at a missing state; managers, groups and other
+    /// side accounts should be closed too.
+    /// Root admin only, and not once the configuration is sealed.
+    pub fn close_rbac_state(ctx: Context<CloseRbacState>) -> Result<()> {
+        let rbac_state = &ctx.accounts.rbac_state;
+        rbac_state.require_unsealed()?;
+        require!(
+            rbac_state.admin == ctx.accounts.admin.key(),
+            RbacError::NotAuthorized
+        );
+        rbac_state.require_empty()?;
+
+        emit!(RbacClosed {
+            admin: ctx.accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Create a new role with specific bitmask permissions. `role_name` must be in
+    /// canonical form (see `validate_role_name`) since it is used verbatim as PDA seed.
     pub fn create_role(
         ctx: Context<CreateRole>,
         role_name: String,
         permissions: u32,
+        description: String,
+        required_mint: Option<Pubkey>,
+        min_amount: u64,
+        permission_group: Option<String>,
+        max_assignments: Option<u32>,
+        valid_from: Option<i64>,
+        valid_until: Option<i64>,
     ) -> Result<()> {
+        validate_role_name(&role_name)?;
+        if let Some(group_name) = &permission_group {
+            validate_role_name(group_name)?;
+        }
         require!(
-            role_name.len() <= 32,
-            RbacError::RoleNameTooLong
-        );
-        require!(
-            ctx.accounts.rbac_state.admin == ctx.accounts.admin.key(),
-            RbacError::NotAuthorized
+            description.len() <= MAX_DESCRIPTION_LEN,
+            RbacError::DescriptionTooLong
         );
+        validate_role_window(valid_from, valid_until)?;
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        require_wildcard_authority(&ctx.accounts.rbac_state, &ctx.accounts.admin.key(), &role_name, current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
         // Max 5 permission bits = 0b11111 = 31
         require!(
             permissions <= 31u32,
             RbacError::InvalidPermissions
         );
+        // The wildcard role is stored with every bit so privilege policies see it as such
+        let permissions = if role_name == WILDCARD_ROLE_NAME {
+            PERM_READ | PERM_CREATE | PERM_UPDATE | PERM_DELETE | PERM_ADMIN
+        } else {
+            permissions
+        };
+        validate_admin_role(&role_name, permissions)?;
+        ctx.accounts.rbac_state.require_proposal_for(permissions)?;
+        require_can_grant(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            permissions,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_assignable(&role_name, permissions)?;
+        ctx.accounts.rbac_state.require_role_capacity()?;
+        ctx.accounts.role_registry.names.push(role_name.clone());
         
         let role = &mut ctx.accounts.role;
         role.name = role_name.clone();
         role.permissions = permissions;
-        role.created_at = Clock::get()?.unix_timestamp;
+        role.created_at = current_time;
         role.bump = ctx.bumps.role;
+        role.description = description;
+        role.active = true;
+        role.version = Role::current_version();
+        role.required_mint = required_mint;
+        role.min_amount = min_amount;
+        role.permission_group = permission_group;
+        role.max_assignments = max_assignments;
+        role.valid_from = valid_from;
+        role.valid_until = valid_until;
         
         let rbac_state = &mut ctx.accounts.rbac_state;
-        rbac_state.role_count += 1;
+        increment(&mut rbac_state.role_count)?;
         
-        emit!(RoleCreated {
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleCreated {
             name: role_name,
             permissions,
+            breakglass,
             timestamp: role.created_at,
+            slot: Clock::get()?.slot,
         });
         
         Ok(())
     }
 
-    /// Assign a role to a user. Supports multi-role per user via PDA structure and Time-bound expiry.
-    pub fn assign_role(
-        ctx: Context<AssignRole>,
-        user: Pubkey,
+    /// Delete a role that no longer has any assignments, refunding its rent to the signer.
+    pub fn delete_role(
+        ctx: Context<DeleteRole>,
         role_name: String,
-        expires_at: Option<i64>,
     ) -> Result<()> {
-        // Guard: Validate role_name length to prevent oversized PDA seed injection.
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(!ctx.accounts.rbac_state.requires_quorum(), RbacError::ProposalRequired);
+        require!(ctx.accounts.role.assignment_count == 0, RbacError::RoleInUse);
+
+        ctx.accounts.role_registry.names.retain(|name| *name != role_name);
+        decrement(&mut ctx.accounts.rbac_state.role_count)?;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleDeleted {
+            name: role_name,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Rename a role. Role names are PDA seeds, so this creates the `new_name` role
+    /// with the old one's permissions and metadata. A role without assignments is
+    /// closed at once; otherwise it stays behind disabled, pointing at `new_name`,
+    /// until `remap_user_role` has moved every assignment over, and is closed with
+    /// the last one. Callers must remap every holder: until then the old
+    /// assignments deny. Scoped assignments, claims, snapshots, managers and org
+    /// admin prefixes are keyed by name and are not carried over. Admin only, and
+    /// only while no quorum is required (see `requires_quorum`).
+    pub fn rename_role(
+        ctx: Context<RenameRole>,
+        old_name: String,
+        new_name: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        // A rename creates the new role and closes the old one
+        require!(!ctx.accounts.rbac_state.requires_quorum(), RbacError::ProposalRequired);
+        validate_role_name(&new_name)?;
+        validate_admin_role(&new_name, ctx.accounts.old_role.permissions)?;
         require!(
-            role_name.len() <= 32,
-            RbacError::RoleNameTooLong
+            ctx.accounts.old_role.renamed_to.is_none(),
+            RbacError::RoleAlreadyRenamed
         );
-        // Only admin can assign
+        // The old role is left disabled, which would lock the admin out of it
         require!(
-            ctx.accounts.rbac_state.admin == ctx.accounts.authority.key(),
-            RbacError::NotAuthorized
+            !holds_live_assignment(&ctx.accounts.admin_assignment, current_time)?,
+            RbacError::AdminRoleInUse
         );
-        // Verify target role exists
+
+        let old_role = &ctx.accounts.old_role;
+        ctx.accounts.new_role.set_inner(Role {
+            name: new_name.clone(),
+            bump: ctx.bumps.new_role,
+            assignment_count: 0,
+            version: Role::current_version(),
+            ..(***old_role).clone()
+        });
+
+        let names = &mut ctx.accounts.role_registry.names;
+        match names.iter_mut().find(|name| **name == old_name) {
+            Some(name) => *name = new_name.clone(),
+            None => names.push(new_name.clone()),
+        }
+        // Both roles stay live until the old one's holders are remapped
+        if ctx.accounts.old_role.assignment_count > 0 {
+            ctx.accounts.rbac_state.require_role_capacity()?;
+        }
+        increment(&mut ctx.accounts.rbac_state.role_count)?;
+
+        if ctx.accounts.old_role.assignment_count == 0 {
+            ctx.accounts.old_role.close(ctx.accounts.admin.to_account_info())?;
+            decrement(&mut ctx.accounts.rbac_state.role_count)?;
+        } else {
+            ctx.accounts.old_role.active = false;
+            ctx.accounts.old_role.renamed_to = Some(new_name.clone());
+        }
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleRenamed {
+            old_name,
+            new_name,
+            pending_assignments: ctx.accounts.old_role.assignment_count,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Move `user`'s assignment of a renamed role to the role's new name (and
+    /// PDA), keeping its metadata and escrowed stake. Closes the old role once its
+    /// last assignment has moved. Admin only.
+    pub fn remap_user_role(
+        ctx: Context<RemapUserRole>,
+        user: Pubkey,
+        old_name: String,
+        new_name: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
         require!(
-            ctx.accounts.role.name == role_name,
-            RbacError::RoleNotFound
+            ctx.accounts.old_role.renamed_to.as_deref() == Some(new_name.as_str()),
+            RbacError::RoleNotRenamed
         );
-        
-        // Create or update user role assignment
-        let user_role = &mut ctx.accounts.user_role;
-        let is_new = user_role.assigned_at == 0; // 0 means uninitialized default
-        
-        user_role.user = user;
-        user_role.role = role_name.clone();
-        user_role.assigned_at = Clock::get()?.unix_timestamp;
-        user_role.expires_at = expires_at;
-        user_role.assigned_by = ctx.accounts.authority.key();
-        user_role.bump = ctx.bumps.user_role;
-        
-        // Each (user, role) PDA is unique — this accurately tracks the number of
-        // role assignments across the system. Only increment if brand new PDA.
-        if is_new {
-            ctx.accounts.rbac_state.assignment_count += 1;
+
+        let old_user_role = &ctx.accounts.old_user_role;
+        let mut moved = UserRole {
+            role: new_name.clone(),
+            bump: ctx.bumps.new_user_role,
+            version: UserRole::current_version(),
+            ..(***old_user_role).clone()
+        };
+        moved.refresh_fingerprint();
+        let stake = moved.stake;
+        ctx.accounts.new_user_role.set_inner(moved);
+        if stake > 0 {
+            ctx.accounts.old_user_role.sub_lamports(stake)?;
+            ctx.accounts.new_user_role.add_lamports(stake)?;
         }
-        
-        emit!(RoleAssigned {
+        ctx.accounts.old_user_role.close(ctx.accounts.admin.to_account_info())?;
+
+        unindex_role(&mut ctx.accounts.user_index, &old_name)?;
+        index_role(
+            &mut ctx.accounts.user_index,
+            &new_name,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+        )?;
+
+        increment(&mut ctx.accounts.new_role.assignment_count)?;
+        ctx.accounts.old_role.release_assignment();
+        let old_role_closed = ctx.accounts.old_role.assignment_count == 0;
+        if old_role_closed {
+            ctx.accounts.old_role.close(ctx.accounts.admin.to_account_info())?;
+            decrement(&mut ctx.accounts.rbac_state.role_count)?;
+        }
+
+        emit_state_event(&mut ctx.accounts.rbac_state, UserRoleRemapped {
             user,
-            role: role_name,
-            assigned_by: ctx.accounts.authority.key(),
-            expires_at,
-            timestamp: user_role.assigned_at,
+            old_role: old_name,
+            new_role: new_name,
+            old_role_closed,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
         });
-        
+
         Ok(())
     }
 
-    /// Check if user has specific permission (optimized for CPI).
-    /// Used natively by other programs.
-    pub fn check_permission(
-        ctx: Context<CheckPermission>,
-        required_permission: u32,
-    ) -> Result<bool> {
-        let user_role = &ctx.accounts.user_role;
-        let role = &ctx.accounts.role;
+    /// Page through the registered role names, returning at most
+    /// `MAX_ROLES_PAGE` names starting at index `start`. Read-only.
+    pub fn get_roles(
+        ctx: Context<ReadRegistry>,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<String>> {
+        let limit = (limit as usize).min(MAX_ROLES_PAGE);
+        Ok(ctx.accounts.role_registry.names
+            .iter()
+            .skip(start as usize)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+
+    /// Create a named permission bundle at `[b"perm_group", name]` that roles can
+    /// reference; referencing roles pick up its bits at check time.
+    pub fn create_permission_group(
+        ctx: Context<CreatePermissionGroup>,
+        name: String,
+        permissions: u32,
+    ) -> Result<()> {
+        validate_role_name(&name)?;
         let current_time = Clock::get()?.unix_timestamp;
-        
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(permissions <= 31u32, RbacError::InvalidPermissions);
+
+        let group = &mut ctx.accounts.group;
+        group.name = name.clone();
+        group.permissions = permissions;
+        group.created_at = current_time;
+        group.bump = ctx.bumps.group;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, PermissionGroupCreated {
+            name,
+            permissions,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Replace a permission group's bits; every referencing role follows.
+    pub fn update_permission_group(
+        ctx: Context<UpdatePermissionGroup>,
+        name: String,
+        permissions: u32,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(permissions <= 31u32, RbacError::InvalidPermissions);
+
+        let old_permissions = ctx.accounts.group.permissions;
+        ctx.accounts.group.permissions = permissions;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, PermissionGroupUpdated {
+            name,
+            old_permissions,
+            new_permissions: permissions,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Make a role the flat union of other named roles, resolved at check time.
+    /// Member roles are not expanded recursively. Pass an empty list to clear.
+    pub fn set_role_composition(
+        ctx: Context<UpdateRole>,
+        role_name: String,
+        composed_of: Vec<String>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
         require!(
-            user_role.role == role.name,
-            RbacError::UserRoleMismatch
+            composed_of.len() <= MAX_COMPOSED_ROLES,
+            RbacError::TooManyComposedRoles
         );
+        for member in &composed_of {
+            require!(member.len() <= MAX_ROLE_NAME_LEN, RbacError::RoleNameTooLong);
+            require!(*member != role_name, RbacError::InvalidComposition);
+        }
 
-        // Validation 1: Check time expiry bounds
-        if let Some(expiry) = user_role.expires_at {
-            if current_time >= expiry {
-                emit!(PermissionChecked {
-                    user: user_role.user,
-                    permission_checked: required_permission,
-                    result: false,
-                    reason: "Expired Role".to_string(),
-                    timestamp: current_time,
-                });
-                return Ok(false);
-            }
+        ctx.accounts.role.composed_of = composed_of.clone();
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleCompositionSet {
+            role: role_name,
+            composed_of,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Make a role inherit everything `parent_name` (and its ancestors) grants.
+    /// The whole ancestor chain must be passed in `remaining_accounts` so cycles
+    /// and the depth cap can be checked. Pass `None` to clear.
+    pub fn set_role_parent(
+        ctx: Context<UpdateRole>,
+        role_name: String,
+        parent_name: Option<String>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+
+        let mut next = parent_name.clone();
+        let mut depth = 0;
+        while let Some(ancestor_name) = next {
+            depth += 1;
+            require!(ancestor_name.len() <= MAX_ROLE_NAME_LEN, RbacError::RoleNameTooLong);
+            require!(ancestor_name != role_name, RbacError::CyclicHierarchy);
+            require!(depth <= MAX_HIERARCHY_DEPTH, RbacError::HierarchyTooDeep);
+            let (ancestor_address, _) = Pubkey::find_program_address(
+                &[seeds::ROLE, ancestor_name.as_bytes()],
+                &crate::ID,
+            );
+            let ancestor = find_remaining(ctx.remaining_accounts, &ancestor_address)
+                .map(load_account::<Role>)
+                .transpose()?
+                .ok_or(RbacError::ParentRoleMissing)?;
+            next = ancestor.parent;
         }
-        
-        // Validation 2: Bitwise Permissions mask check
-        // Check if role has the requested bits 
-        let has_permission = (role.permissions & required_permission) == required_permission;
-        
-        emit!(PermissionChecked {
-            user: user_role.user,
-            permission_checked: required_permission,
-            result: has_permission,
-            reason: if has_permission { "Allowed".to_string() } else { "Insufficient Bitmask".to_string() },
+
+        ctx.accounts.role.parent = parent_name.clone();
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleParentSet {
+            role: role_name,
+            parent: parent_name,
+            breakglass,
             timestamp: current_time,
+            slot: Clock::get()?.slot,
         });
-        
-        Ok(has_permission)
+
+        Ok(())
     }
 
-    /// Direct CPI verification that halts execution if it fails.
-    pub fn assert_has_permission(
-        ctx: Context<CheckPermission>,
-        required_permission: u32,
+    /// Gate assignment of a role on an identity attribute (e.g. KYC level) held in
+    /// an account owned by `attribute_program`. Pass `None` to lift the gate.
+    pub fn set_role_attribute_requirement(
+        ctx: Context<UpdateRole>,
+        role_name: String,
+        attribute_program: Option<Pubkey>,
+        min_attribute_value: u64,
     ) -> Result<()> {
-        let user_role = &ctx.accounts.user_role;
-        let role = &ctx.accounts.role;
         let current_time = Clock::get()?.unix_timestamp;
-        
-        require!(
-            user_role.role == role.name,
-            RbacError::UserRoleMismatch
-        );
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+
+        let role = &mut ctx.accounts.role;
+        role.attribute_program = attribute_program;
+        role.min_attribute_value = min_attribute_value;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleAttributeRequirementSet {
+            role: role_name,
+            attribute_program,
+            min_attribute_value,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
 
-        if let Some(expiry) = user_role.expires_at {
-            require!(current_time < expiry, RbacError::PermissionDenied);
+    /// Only allow assigning this role to users who already hold `requires_role`
+    /// (e.g. "approver" requires "employee"). Pass `None` to lift the prerequisite.
+    pub fn set_required_co_role(
+        ctx: Context<UpdateRole>,
+        role_name: String,
+        requires_role: Option<String>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        if let Some(required) = &requires_role {
+            require!(required.len() <= MAX_ROLE_NAME_LEN, RbacError::RoleNameTooLong);
+            require!(*required != role_name, RbacError::InvalidCoRole);
         }
-        
-        let has_permission = (role.permissions & required_permission) == required_permission;
-        require!(has_permission, RbacError::PermissionDenied);
-        
+
+        ctx.accounts.role.requires_role = requires_role.clone();
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RequiredCoRoleSet {
+            role: role_name,
+            requires_role,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
         Ok(())
     }
 
-    /// Revoke a role from user by closing the PDA 
-    pub fn revoke_role(
-        ctx: Context<RevokeRole>,
-        _role_name: String, // Kept to align with Instruction derivation
+    /// Replace a role's permissions in place. Existing assignments keep pointing at
+    /// the role and pick up the new bits on their next check.
+    pub fn update_role_permissions(
+        ctx: Context<UpdateRole>,
+        role_name: String,
+        new_permissions: u32,
     ) -> Result<()> {
-        // Only admin can revoke
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
         require!(
-            ctx.accounts.rbac_state.admin == ctx.accounts.authority.key(),
-            RbacError::NotAuthorized
+            new_permissions <= 31u32,
+            RbacError::InvalidPermissions
         );
+        validate_admin_role(&role_name, new_permissions)?;
 
-        ctx.accounts.rbac_state.assignment_count = ctx.accounts.rbac_state
-            .assignment_count
-            .checked_sub(1)
-            .ok_or(RbacError::CountUnderflow)?;
+        let old_permissions = ctx.accounts.role.permissions;
+        ctx.accounts.rbac_state.require_proposal_for(new_permissions & !old_permissions)?;
+        require_can_grant(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            new_permissions & !old_permissions,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_assignable(&role_name, new_permissions & !old_permissions)?;
+        ctx.accounts.role.permissions = new_permissions;
 
-        emit!(RoleRevoked {
-            user: ctx.accounts.user_role.user,
-            revoked_by: ctx.accounts.authority.key(),
-            role_revoked: ctx.accounts.user_role.role.clone(),
-            timestamp: Clock::get()?.unix_timestamp,
+        emit_state_event(&mut ctx.accounts.rbac_state, RolePermissionsUpdated {
+            role: role_name,
+            old_permissions,
+            new_permissions,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
         });
-        
-        // Anchor automatically returns the rent to `authority` because of `close = authority`
+
         Ok(())
     }
 
-    /// Transfer admin rights to a new wallet
-    pub fn transfer_admin(
-        ctx: Context<TransferAdmin>,
-        new_admin: Pubkey,
+    /// Stage a replacement permission set for a role in a `PendingRoleUpdate`.
+    /// Nothing changes until `commit_role_update`; staging again overwrites it.
+    pub fn stage_role_update(
+        ctx: Context<StageRoleUpdate>,
+        role_name: String,
+        new_permissions: u32,
     ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
         require!(
-            ctx.accounts.rbac_state.admin == ctx.accounts.admin.key(),
-            RbacError::NotAuthorized
+            new_permissions <= 31u32,
+            RbacError::InvalidPermissions
         );
-        
-        let old_admin = ctx.accounts.rbac_state.admin;
-        ctx.accounts.rbac_state.admin = new_admin;
+        require_can_grant(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            new_permissions & !ctx.accounts.role.permissions,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state
+            .require_assignable(&role_name, new_permissions & !ctx.accounts.role.permissions)?;
 
-        emit!(AdminTransferred {
-            old_admin,
-            new_admin,
-            timestamp: Clock::get()?.unix_timestamp,
+        let pending = &mut ctx.accounts.pending;
+        pending.role = role_name.clone();
+        pending.new_permissions = new_permissions;
+        pending.staged_by = ctx.accounts.admin.key();
+        pending.staged_at = current_time;
+        pending.bump = ctx.bumps.pending;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleUpdateStaged {
+            role: role_name,
+            old_permissions: ctx.accounts.role.permissions,
+            new_permissions,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
         });
-        
+
         Ok(())
     }
-}
 
-/// ============ INSTRUCTIONS ============
+    /// Diff between a role's live permissions and its staged update. Read-only.
+    pub fn preview_role_update(
+        ctx: Context<PreviewRoleUpdate>,
+        _role_name: String,
+    ) -> Result<RoleUpdateDiff> {
+        let current = ctx.accounts.role.permissions;
+        let proposed = ctx.accounts.pending.new_permissions;
+        Ok(RoleUpdateDiff {
+            current,
+            proposed,
+            added: proposed & !current,
+            removed: current & !proposed,
+        })
+    }
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = admin,
-        space = 8 + RbacState::INIT_SPACE,
-        seeds = [b"rbac_state"],
-        bump
-    )]
-    pub rbac_state: Account<'info, RbacState>,
-    
-    #[account(mut)]
-    pub admin: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
-}
+    /// Whether `role_name` has a staged update awaiting commit. When it does, the
+    /// staged permissions are also emitted so UIs can warn before acting. Read-only.
+    pub fn has_pending_update(
+        ctx: Context<CheckPendingUpdate>,
+        role_name: String,
+    ) -> Result<bool> {
+        let info = ctx.accounts.pending.to_account_info();
+        if *info.owner != crate::ID || info.data_is_empty() {
+            return Ok(false);
+        }
+        let pending = load_account::<PendingRoleUpdate>(&info)?;
+
+        emit!(RoleUpdatePending {
+            role: role_name,
+            current_permissions: ctx.accounts.role.permissions,
+            new_permissions: pending.new_permissions,
+            staged_by: pending.staged_by,
+            staged_at: pending.staged_at,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(true)
+    }
+
+    /// Apply a staged update to the live role and close the staging account.
+    pub fn commit_role_update(
+        ctx: Context<ResolveRoleUpdate>,
+        role_name: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+
+        let old_permissions = ctx.accounts.role.permissions;
+        let new_permissions = ctx.accounts.pending.new_permissions;
+        validate_admin_role(&role_name, new_permissions)?;
+        ctx.accounts.rbac_state.require_proposal_for(new_permissions & !old_permissions)?;
+        // Re-checked against the committing signer, who may differ from the stager
+        require_can_grant(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            new_permissions & !old_permissions,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_assignable(&role_name, new_permissions & !old_permissions)?;
+        ctx.accounts.role.permissions = new_permissions;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleUpdateCommitted {
+            role: role_name,
+            old_permissions,
+            new_permissions,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Drop a staged update without touching the live role.
+    pub fn discard_role_update(
+        ctx: Context<ResolveRoleUpdate>,
+        role_name: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleUpdateDiscarded {
+            role: role_name,
+            discarded_permissions: ctx.accounts.pending.new_permissions,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Remove several permission bits from a role at once. Bits the role does not
+    /// hold are skipped; returns how many were actually removed.
+    pub fn revoke_permissions(
+        ctx: Context<UpdateRole>,
+        role_name: String,
+        permissions: u32,
+    ) -> Result<u8> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(
+            permissions <= 31u32,
+            RbacError::InvalidPermissions
+        );
+
+        validate_admin_role(&role_name, ctx.accounts.role.permissions & !permissions)?;
+
+        let role = &mut ctx.accounts.role;
+        let removed = role.permissions & permissions;
+        role.permissions &= !permissions;
+        let remaining = role.permissions;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RolePermissionsRevoked {
+            role: role_name,
+            removed,
+            remaining,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(removed.count_ones() as u8)
+    }
+
+    /// Require assigners to escrow `assigner_stake` lamports per new assignment of
+    /// this role, returned on revoke and slashable by the admin.
+    pub fn set_assigner_stake(
+        ctx: Context<UpdateRole>,
+        role_name: String,
+        assigner_stake: u64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+
+        ctx.accounts.role.assigner_stake = assigner_stake;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AssignerStakeSet {
+            role: role_name,
+            assigner_stake,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Cap the lamports a holder of this role may disburse through programs that
+    /// gate spending on `check_disbursement`. 0 permits no disbursement.
+    pub fn set_max_disbursement(
+        ctx: Context<UpdateRole>,
+        role_name: String,
+        max_disbursement: u64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+
+        ctx.accounts.role.max_disbursement = max_disbursement;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, MaxDisbursementSet {
+            role: role_name,
+            max_disbursement,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Set or clear the most assignments `role_name` may have at once. A cap below
+    /// the current count blocks new assignments until enough are revoked.
+    pub fn set_role_cap(
+        ctx: Context<UpdateRole>,
+        role_name: String,
+        max_assignments: Option<u32>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+
+        ctx.accounts.role.max_assignments = max_assignments;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleCapSet {
+            role: role_name,
+            max_assignments,
+            assignment_count: ctx.accounts.role.assignment_count,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Limit when `role_name` grants anything: checks through it are denied before
+    /// `valid_from` and from `valid_until` on, for every holder. `None` leaves that
+    /// side open. Unlike an assignment's expiry this gates the role itself.
+    pub fn set_role_validity(
+        ctx: Context<UpdateRole>,
+        role_name: String,
+        valid_from: Option<i64>,
+        valid_until: Option<i64>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        validate_role_window(valid_from, valid_until)?;
+
+        ctx.accounts.role.valid_from = valid_from;
+        ctx.accounts.role.valid_until = valid_until;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleValiditySet {
+            role: role_name,
+            valid_from,
+            valid_until,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Neutralize `role_name` at once during an incident: every check through it
+    /// fails with `RoleDisabled` until `reenable_role`, without touching its
+    /// assignments. Callable by the admin or the guardian, even while paused or
+    /// config-frozen so an incident pause cannot shield the compromised role.
+    pub fn emergency_disable_role(
+        ctx: Context<SetRoleActive>,
+        role_name: String,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unsealed()?;
+        let current_time = Clock::get()?.unix_timestamp;
+        let signer = ctx.accounts.admin.key();
+        if ctx.accounts.rbac_state.guardian != Some(signer) {
+            ctx.accounts.rbac_state.authorize_admin(&signer, current_time)?;
+        }
+        require!(ctx.accounts.role.disabled_at == 0, RbacError::RoleDisabled);
+        require!(
+            !holds_live_assignment(&ctx.accounts.admin_assignment, current_time)?,
+            RbacError::AdminRoleInUse
+        );
+
+        ctx.accounts.role.disabled_at = current_time;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleEmergencyDisabled {
+            role: role_name,
+            disabled_by: signer,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Lift an emergency disable. Admin only, so a compromised guardian cannot
+    /// restore a role it was never trusted to manage.
+    pub fn reenable_role(
+        ctx: Context<UpdateRole>,
+        role_name: String,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        require!(ctx.accounts.role.disabled_at != 0, RbacError::RoleNotDisabled);
+
+        let disabled_at = ctx.accounts.role.disabled_at;
+        ctx.accounts.role.disabled_at = 0;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleReenabled {
+            role: role_name,
+            disabled_at,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Soft-disable (or re-enable) a role without deleting it. While inactive every
+    /// permission check through it is denied, existing assignments included. A role
+    /// the system admin currently holds can't be disabled, so the admin can't lock
+    /// themselves out; `delete_role` already refuses any role with assignments.
+    pub fn set_role_active(
+        ctx: Context<SetRoleActive>,
+        role_name: String,
+        active: bool,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(
+            active || !holds_live_assignment(&ctx.accounts.admin_assignment, current_time)?,
+            RbacError::AdminRoleInUse
+        );
+
+        ctx.accounts.role.active = active;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleActiveChanged {
+            role: role_name,
+            active,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Grant a role extra permission bits once the system-wide assignment count
+    /// reaches each entry's threshold, evaluated at check time. Pass an empty list to clear.
+    pub fn set_conditional_grants(
+        ctx: Context<UpdateRole>,
+        role_name: String,
+        conditional_grants: Vec<ConditionalGrant>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(
+            conditional_grants.len() <= MAX_CONDITIONAL_GRANTS,
+            RbacError::TooManyConditionalGrants
+        );
+        let granted = conditional_grants
+            .iter()
+            .fold(0u32, |bits, grant| bits | grant.permissions);
+        require!(granted <= 31u32, RbacError::InvalidPermissions);
+        ctx.accounts.rbac_state.require_proposal_for(granted & !ctx.accounts.role.permissions)?;
+        require_can_grant(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            granted,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_assignable(&role_name, granted)?;
+
+        ctx.accounts.role.conditional_grants = conditional_grants.clone();
+
+        emit_state_event(&mut ctx.accounts.rbac_state, ConditionalGrantsSet {
+            role: role_name,
+            conditional_grants,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Require holders of a role to accept it with `acknowledge_role` before any
+    /// permission check passes. Existing unacknowledged holders are affected too.
+    pub fn set_role_acknowledgment(
+        ctx: Context<UpdateRole>,
+        role_name: String,
+        require_acknowledgment: bool,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+
+        ctx.accounts.role.require_acknowledgment = require_acknowledgment;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleAcknowledgmentSet {
+            role: role_name,
+            require_acknowledgment,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Assign a role to a user. Supports multi-role per user via PDA structure and Time-bound expiry.
+    pub fn assign_role<'info>(
+        ctx: Context<'_, '_, '_, 'info, AssignRole<'info>>,
+        user: Pubkey,
+        role_name: String,
+        expires_at: Option<i64>,
+        note: Option<String>,
+        jurisdiction: Option<String>,
+    ) -> Result<AssignmentResult> {
+        // Guard: Validate role_name length to prevent oversized PDA seed injection.
+        require!(
+            role_name.len() <= MAX_ROLE_NAME_LEN,
+            RbacError::RoleNameTooLong
+        );
+        // Only admin, an active break-glass key, an org admin scoped to the role or
+        // one of the role's managers can assign
+        let current_time = Clock::get()?.unix_timestamp;
+        let (breakglass, delegation_depth) = authorize_assigner(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.authority.key(),
+            &role_name,
+            current_time,
+        )?;
+        require_wildcard_authority(&ctx.accounts.rbac_state, &ctx.accounts.authority.key(), &role_name, current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        // Verify target role exists
+        require!(
+            ctx.accounts.role.name == role_name,
+            RbacError::RoleNotFound
+        );
+        if let Some(note) = &note {
+            require!(note.len() <= MAX_NOTE_LEN, RbacError::NoteTooLong);
+        }
+        if let Some(jurisdiction) = &jurisdiction {
+            require!(
+                jurisdiction.len() <= MAX_JURISDICTION_LEN,
+                RbacError::JurisdictionTooLong
+            );
+        }
+        require_privileged_note(&ctx.accounts.rbac_state, &ctx.accounts.role, note.as_deref())?;
+        require_attribute(ctx.remaining_accounts, &ctx.accounts.role, &user)?;
+        require_token_gate(ctx.remaining_accounts, &ctx.accounts.role, &user)?;
+        require_co_role(ctx.remaining_accounts, &ctx.accounts.role, &user, current_time)?;
+        
+        // 0 means uninitialized default; a revoked assignment awaiting finalization is re-granted afresh
+        let is_new = ctx.accounts.user_role.assigned_at == 0 || ctx.accounts.user_role.revoked;
+        let stake = if is_new { ctx.accounts.role.assigner_stake } else { 0 };
+        escrow_assigner_stake(
+            &ctx.accounts.system_program,
+            &ctx.accounts.authority,
+            ctx.accounts.user_role.to_account_info(),
+            stake,
+        )?;
+
+        // Create or update user role assignment
+        let user_role = &mut ctx.accounts.user_role;
+        if is_new {
+            user_role.stake = stake;
+            user_role.staked_by = ctx.accounts.authority.key();
+            // Carve-outs and elevations belong to the previous grant, not a fresh one
+            user_role.denied_permissions = 0;
+            user_role.temp_permission = None;
+        }
+        
+        user_role.user = user;
+        user_role.role = role_name.clone();
+        user_role.assigned_at = current_time;
+        user_role.expires_at = expires_at;
+        user_role.warned = false;
+        user_role.revoked = false;
+        user_role.revoked_at = 0;
+        user_role.last_changed_at = current_time;
+        user_role.assigned_by = ctx.accounts.authority.key();
+        user_role.delegation_depth = delegation_depth;
+        user_role.bump = ctx.bumps.user_role;
+        user_role.version = UserRole::current_version();
+        user_role.note = note.clone();
+        user_role.jurisdiction = jurisdiction.clone();
+        // Roles requiring acknowledgment stay inactive until the user accepts them
+        user_role.acknowledged = !ctx.accounts.role.require_acknowledgment;
+        user_role.refresh_fingerprint();
+        
+        // Each (user, role) PDA is unique — this accurately tracks the number of
+        // role assignments across the system. Only increment if brand new PDA.
+        if is_new {
+            increment(&mut ctx.accounts.rbac_state.assignment_count)?;
+            ctx.accounts.role.claim_assignment()?;
+        }
+
+        ctx.accounts.user_index.user = user;
+        ctx.accounts.user_index.bump = ctx.bumps.user_index;
+        index_role(
+            &mut ctx.accounts.user_index,
+            &role_name,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+
+        ctx.accounts.audit_log.record(AuditEntry {
+            actor: ctx.accounts.authority.key(),
+            action_kind: AUDIT_ROLE_ASSIGNED,
+            target: user,
+            timestamp: current_time,
+        });
+
+        invoke_assignment_hook(&ctx.accounts.rbac_state, ctx.remaining_accounts, OnRoleAssignedArgs {
+            user,
+            role: role_name.clone(),
+            assigned_by: ctx.accounts.authority.key(),
+            expires_at,
+        })?;
+        
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleAssigned {
+            user,
+            role: role_name,
+            assigned_by: ctx.accounts.authority.key(),
+            expires_at,
+            note,
+            jurisdiction,
+            breakglass,
+            timestamp: user_role.assigned_at,
+            slot: Clock::get()?.slot,
+            permissions: ctx.accounts.role.permissions,
+            delegation_depth,
+        });
+        
+        Ok(AssignmentResult {
+            user,
+            role: ctx.accounts.role.key(),
+            user_role: ctx.accounts.user_role.key(),
+            expires_at,
+        })
+    }
+
+    /// Assign up to `MAX_BATCH_ASSIGNMENTS` `(user, role)` pairs at once, e.g. to
+    /// onboard a team. Every entry's `UserRole` and `UserRoleIndex` PDAs and each
+    /// referenced `Role` go writable in `remaining_accounts`, matched by address
+    /// together with whatever the roles' gates need. Entries carry no expiry or note
+    /// and must be fresh assignments; any failing entry reverts the whole batch. Admin only.
+    pub fn assign_roles_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AssignRolesBatch<'info>>,
+        assignments: Vec<BatchAssignment>,
+    ) -> Result<()> {
+        require!(
+            assignments.len() <= MAX_BATCH_ASSIGNMENTS,
+            RbacError::BatchTooLarge
+        );
+        let current_time = Clock::get()?.unix_timestamp;
+        let authority = ctx.accounts.authority.key();
+        let breakglass = ctx.accounts.rbac_state.authorize_admin(&authority, current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        let remaining = ctx.remaining_accounts;
+        let rent = Rent::get()?;
+
+        for BatchAssignment { user, role: role_name } in assignments {
+            require!(
+                role_name.len() <= MAX_ROLE_NAME_LEN,
+                RbacError::RoleNameTooLong
+            );
+            let (role_address, _) = Pubkey::find_program_address(
+                &[seeds::ROLE, role_name.as_bytes()],
+                &crate::ID,
+            );
+            let role_info = find_remaining(remaining, &role_address)
+                .ok_or(RbacError::RoleNotFound)?;
+            let mut role = Account::<Role>::try_from(role_info)?;
+            if ctx.accounts.rbac_state.require_note_for_privileged {
+                require!(
+                    role.permissions & PRIVILEGED_PERMISSIONS == 0,
+                    RbacError::NoteRequired
+                );
+            }
+            require_attribute(remaining, &role, &user)?;
+            require_token_gate(remaining, &role, &user)?;
+            require_co_role(remaining, &role, &user, current_time)?;
+
+            // The assignment must not exist yet: creating it fails otherwise
+            let (user_role_address, user_role_bump) = Pubkey::find_program_address(
+                &[seeds::USER_ROLE, user.as_ref(), role_name.as_bytes()],
+                &crate::ID,
+            );
+            let user_role_info = find_remaining(remaining, &user_role_address)
+                .ok_or(RbacError::UserRoleMismatch)?;
+            let space = 8 + UserRole::INIT_SPACE;
+            create_account(
+                CpiContext::new_with_signer(
+                    ctx.accounts.system_program.to_account_info(),
+                    CreateAccount {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: user_role_info.clone(),
+                    },
+                    &[&[seeds::USER_ROLE, user.as_ref(), role_name.as_bytes(), &[user_role_bump]]],
+                ),
+                rent.minimum_balance(space) + role.assigner_stake,
+                space as u64,
+                &crate::ID,
+            )?;
+
+            let mut user_role = Account::<UserRole>::try_from_unchecked(user_role_info)?;
+            user_role.user = user;
+            user_role.role = role_name.clone();
+            user_role.assigned_at = current_time;
+            user_role.last_changed_at = current_time;
+            user_role.assigned_by = authority;
+            user_role.bump = user_role_bump;
+            user_role.version = UserRole::current_version();
+            user_role.stake = role.assigner_stake;
+            user_role.staked_by = authority;
+            user_role.acknowledged = !role.require_acknowledgment;
+            user_role.refresh_fingerprint();
+            user_role.exit(&crate::ID)?;
+
+            increment(&mut ctx.accounts.rbac_state.assignment_count)?;
+            role.claim_assignment()?;
+            role.exit(&crate::ID)?;
+
+            let (index_address, index_bump) = Pubkey::find_program_address(
+                &[seeds::USER_INDEX, user.as_ref()],
+                &crate::ID,
+            );
+            let index_info = find_remaining(remaining, &index_address)
+                .ok_or(RbacError::UserIndexMissing)?;
+            let mut user_index = if *index_info.owner == crate::ID {
+                Account::<UserRoleIndex>::try_from(index_info)?
+            } else {
+                let space = UserRoleIndex::space(&[]);
+                create_account(
+                    CpiContext::new_with_signer(
+                        ctx.accounts.system_program.to_account_info(),
+                        CreateAccount {
+                            from: ctx.accounts.authority.to_account_info(),
+                            to: index_info.clone(),
+                        },
+                        &[&[seeds::USER_INDEX, user.as_ref(), &[index_bump]]],
+                    ),
+                    rent.minimum_balance(space),
+                    space as u64,
+                    &crate::ID,
+                )?;
+                Account::<UserRoleIndex>::try_from_unchecked(index_info)?
+            };
+            user_index.user = user;
+            user_index.bump = index_bump;
+            index_role(
+                &mut user_index,
+                &role_name,
+                &ctx.accounts.authority,
+                &ctx.accounts.system_program,
+            )?;
+            user_index.exit(&crate::ID)?;
+
+            ctx.accounts.audit_log.record(AuditEntry {
+                actor: authority,
+                action_kind: AUDIT_ROLE_ASSIGNED,
+                target: user,
+                timestamp: current_time,
+            });
+
+            invoke_assignment_hook(&ctx.accounts.rbac_state, remaining, OnRoleAssignedArgs {
+                user,
+                role: role_name.clone(),
+                assigned_by: authority,
+                expires_at: None,
+            })?;
+
+            emit_state_event(&mut ctx.accounts.rbac_state, RoleAssigned {
+                user,
+                role: role_name,
+                assigned_by: authority,
+                expires_at: None,
+                note: None,
+                jurisdiction: None,
+                breakglass,
+                timestamp: current_time,
+                slot: Clock::get()?.slot,
+                permissions: role.permissions,
+                delegation_depth: 0,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Assign a role to a user for a single `resource` only, e.g. "editor of
+    /// document X". Scoped assignments live at `[b"user_role", user, resource,
+    /// role_name]` and only grant through `check_permission_scoped` for that resource.
+    pub fn assign_role_scoped<'info>(
+        ctx: Context<'_, '_, '_, 'info, AssignRoleScoped<'info>>,
+        user: Pubkey,
+        resource: Pubkey,
+        role_name: String,
+        expires_at: Option<i64>,
+        note: Option<String>,
+    ) -> Result<()> {
+        require!(role_name.len() <= MAX_ROLE_NAME_LEN, RbacError::RoleNameTooLong);
+        require!(resource != GLOBAL_RESOURCE, RbacError::InvalidResource);
+        let current_time = Clock::get()?.unix_timestamp;
+        let (breakglass, delegation_depth) = authorize_assigner(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.authority.key(),
+            &role_name,
+            current_time,
+        )?;
+        require_wildcard_authority(&ctx.accounts.rbac_state, &ctx.accounts.authority.key(), &role_name, current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(
+            ctx.accounts.role.name == role_name,
+            RbacError::RoleNotFound
+        );
+        if let Some(note) = &note {
+            require!(note.len() <= MAX_NOTE_LEN, RbacError::NoteTooLong);
+        }
+        require_privileged_note(&ctx.accounts.rbac_state, &ctx.accounts.role, note.as_deref())?;
+        require_attribute(ctx.remaining_accounts, &ctx.accounts.role, &user)?;
+        require_token_gate(ctx.remaining_accounts, &ctx.accounts.role, &user)?;
+        require_co_role(ctx.remaining_accounts, &ctx.accounts.role, &user, current_time)?;
+
+        let is_new = ctx.accounts.user_role.assigned_at == 0;
+        let stake = if is_new { ctx.accounts.role.assigner_stake } else { 0 };
+        escrow_assigner_stake(
+            &ctx.accounts.system_program,
+            &ctx.accounts.authority,
+            ctx.accounts.user_role.to_account_info(),
+            stake,
+        )?;
+
+        let user_role = &mut ctx.accounts.user_role;
+        if is_new {
+            user_role.stake = stake;
+            user_role.staked_by = ctx.accounts.authority.key();
+        }
+        user_role.user = user;
+        user_role.role = role_name.clone();
+        user_role.resource = resource;
+        user_role.assigned_at = current_time;
+        user_role.last_changed_at = current_time;
+        user_role.expires_at = expires_at;
+        user_role.assigned_by = ctx.accounts.authority.key();
+        user_role.delegation_depth = delegation_depth;
+        user_role.bump = ctx.bumps.user_role;
+        user_role.version = UserRole::current_version();
+        user_role.note = note.clone();
+        user_role.acknowledged = !ctx.accounts.role.require_acknowledgment;
+        user_role.refresh_fingerprint();
+
+        if is_new {
+            increment(&mut ctx.accounts.rbac_state.assignment_count)?;
+            ctx.accounts.role.claim_assignment()?;
+        }
+
+        ctx.accounts.audit_log.record(AuditEntry {
+            actor: ctx.accounts.authority.key(),
+            action_kind: AUDIT_ROLE_ASSIGNED,
+            target: user,
+            timestamp: current_time,
+        });
+
+        invoke_assignment_hook(&ctx.accounts.rbac_state, ctx.remaining_accounts, OnRoleAssignedArgs {
+            user,
+            role: role_name.clone(),
+            assigned_by: ctx.accounts.authority.key(),
+            expires_at,
+        })?;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, ScopedRoleAssigned {
+            user,
+            role: role_name,
+            resource,
+            assigned_by: ctx.accounts.authority.key(),
+            expires_at,
+            note,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a resource-scoped assignment, closing it and refunding its rent to the signer.
+    pub fn revoke_role_scoped(
+        ctx: Context<RevokeRoleScoped>,
+        resource: Pubkey,
+        role_name: String,
+        bypass_cooldown: bool,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.authority.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(!ctx.accounts.user_role.revoked, RbacError::AssignmentRevoked);
+        ctx.accounts.rbac_state.require_change_cooled(
+            &ctx.accounts.user_role,
+            &ctx.accounts.authority.key(),
+            bypass_cooldown,
+            current_time,
+        )?;
+
+        decrement(&mut ctx.accounts.rbac_state.assignment_count)?;
+        ctx.accounts.role.release_assignment();
+
+        // The account closes to the revoker, so only a third-party bond needs moving
+        if let Some((staker, stake)) = return_stake(
+            &mut ctx.accounts.user_role,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+            true,
+        )? {
+            emit_state_event(&mut ctx.accounts.rbac_state, StakeReturned {
+                user: ctx.accounts.user_role.user,
+                role: role_name.clone(),
+                staked_by: staker,
+                amount: stake,
+                timestamp: current_time,
+                slot: Clock::get()?.slot,
+            });
+        }
+
+        ctx.accounts.audit_log.record(AuditEntry {
+            actor: ctx.accounts.authority.key(),
+            action_kind: AUDIT_ROLE_REVOKED,
+            target: ctx.accounts.user_role.user,
+            timestamp: current_time,
+        });
+
+        emit_state_event(&mut ctx.accounts.rbac_state, ScopedRoleRevoked {
+            user: ctx.accounts.user_role.user,
+            role: role_name,
+            resource,
+            revoked_by: ctx.accounts.authority.key(),
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Publish a Merkle root of `(user, role_name, expires_at)` leaves so eligible
+    /// users can self-mint their assignment with `claim_role_merkle`. Calling it
+    /// again rotates the root, which lets every leaf in the new root claim afresh.
+    pub fn create_role_claim(
+        ctx: Context<CreateRoleClaim>,
+        role_name: String,
+        merkle_root: [u8; 32],
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        require_wildcard_authority(&ctx.accounts.rbac_state, &ctx.accounts.admin.key(), &role_name, current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+
+        let claim = &mut ctx.accounts.claim;
+        claim.role = role_name.clone();
+        claim.merkle_root = merkle_root;
+        claim.bump = ctx.bumps.claim;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleClaimCreated {
+            role: role_name,
+            merkle_root,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Self-mint a `UserRole` by proving the signer's leaf is in the role's claim
+    /// root. The claimant pays rent; the attribute gate and compliance hook apply.
+    /// Each claimant may claim once per root: the `ClaimReceipt` outlives a revoke,
+    /// so only rotating the root re-opens the claim.
+    pub fn claim_role_merkle<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimRoleMerkle<'info>>,
+        role_name: String,
+        proof: Vec<[u8; 32]>,
+        expires_at: Option<i64>,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        let current_time = Clock::get()?.unix_timestamp;
+        let claimant = ctx.accounts.claimant.key();
+
+        let leaf = claim_leaf(&claimant, &role_name, expires_at)?;
+        let merkle_root = ctx.accounts.claim.merkle_root;
+        require!(
+            verify_merkle_proof(&proof, &merkle_root, leaf),
+            RbacError::InvalidProof
+        );
+        require!(
+            ctx.accounts.claim_receipt.merkle_root != merkle_root,
+            RbacError::RoleAlreadyClaimed
+        );
+        let receipt = &mut ctx.accounts.claim_receipt;
+        receipt.user = claimant;
+        receipt.role = role_name.clone();
+        receipt.merkle_root = merkle_root;
+        receipt.claimed_at = current_time;
+        receipt.bump = ctx.bumps.claim_receipt;
+        // A claim carries no justification, so it cannot satisfy the note policy
+        require!(
+            !(ctx.accounts.rbac_state.require_note_for_privileged
+                && ctx.accounts.role.permissions & PRIVILEGED_PERMISSIONS != 0),
+            RbacError::NoteRequired
+        );
+        require_attribute(ctx.remaining_accounts, &ctx.accounts.role, &claimant)?;
+        require_token_gate(ctx.remaining_accounts, &ctx.accounts.role, &claimant)?;
+        require_co_role(ctx.remaining_accounts, &ctx.accounts.role, &claimant, current_time)?;
+
+        let user_role = &mut ctx.accounts.user_role;
+        user_role.user = claimant;
+        user_role.role = role_name.clone();
+        user_role.assigned_at = current_time;
+        user_role.last_changed_at = current_time;
+        user_role.expires_at = expires_at;
+        user_role.assigned_by = claimant;
+        user_role.bump = ctx.bumps.user_role;
+        user_role.version = UserRole::current_version();
+        // Signing the claim is itself the user's acceptance of the role
+        user_role.acknowledged = true;
+        user_role.refresh_fingerprint();
+
+        increment(&mut ctx.accounts.rbac_state.assignment_count)?;
+        ctx.accounts.role.claim_assignment()?;
+
+        ctx.accounts.user_index.user = claimant;
+        ctx.accounts.user_index.bump = ctx.bumps.user_index;
+        index_role(
+            &mut ctx.accounts.user_index,
+            &role_name,
+            &ctx.accounts.claimant,
+            &ctx.accounts.system_program,
+        )?;
+
+        ctx.accounts.audit_log.record(AuditEntry {
+            actor: claimant,
+            action_kind: AUDIT_ROLE_ASSIGNED,
+            target: claimant,
+            timestamp: current_time,
+        });
+
+        invoke_assignment_hook(&ctx.accounts.rbac_state, ctx.remaining_accounts, OnRoleAssignedArgs {
+            user: claimant,
+            role: role_name.clone(),
+            assigned_by: claimant,
+            expires_at,
+        })?;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleAssigned {
+            user: claimant,
+            role: role_name,
+            assigned_by: claimant,
+            expires_at,
+            note: None,
+            jurisdiction: None,
+            breakglass: false,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+            permissions: ctx.accounts.role.permissions,
+            delegation_depth: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Check if user has specific permission (optimized for CPI).
+    /// Used natively by other programs.
+    pub fn check_permission(
+        ctx: Context<CheckPermission>,
+        required_permission: u32,
+    ) -> Result<bool> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let evaluation = evaluate(ctx.accounts, ctx.remaining_accounts, required_permission, &GLOBAL_RESOURCE, current_time)?;
+
+        emit!(PermissionChecked {
+            user: ctx.accounts.subject(),
+            permission_checked: required_permission,
+            result: evaluation.allowed,
+            reason: evaluation.reason.label().to_string(),
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+        
+        Ok(evaluation.allowed)
+    }
+
+    /// `check_permission` for any `user`, assigned or not. The `UserRole` is derived
+    /// from `user` and `role_name` and may not exist, in which case the answer is
+    /// `false` (unless `user` is the system admin or holds a delegation) rather
+    /// than an account error, so clients can ask about arbitrary users.
+    pub fn check_user_permission(
+        ctx: Context<CheckUserPermission>,
+        user: Pubkey,
+        _role_name: String,
+        required_permission: u32,
+    ) -> Result<bool> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let subject = ctx.accounts.subject(user)?;
+        let evaluation = evaluate(&subject, ctx.remaining_accounts, required_permission, &GLOBAL_RESOURCE, current_time)?;
+
+        emit!(PermissionChecked {
+            user,
+            permission_checked: required_permission,
+            result: evaluation.allowed,
+            reason: evaluation.reason.label().to_string(),
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(evaluation.allowed)
+    }
+
+    /// `check_permission` for several bit sets at once, against the same loaded
+    /// assignment. Returns one flag per entry, in order, and emits a single summary.
+    pub fn batch_check_permissions(
+        ctx: Context<CheckPermission>,
+        permissions: Vec<u32>,
+    ) -> Result<Vec<bool>> {
+        require!(
+            permissions.len() <= MAX_BATCH_PERMISSIONS,
+            RbacError::TooManyPermissions
+        );
+        let current_time = Clock::get()?.unix_timestamp;
+        let results = permissions
+            .iter()
+            .map(|&required| {
+                evaluate(ctx.accounts, ctx.remaining_accounts, required, &GLOBAL_RESOURCE, current_time)
+                    .map(|evaluation| evaluation.allowed)
+            })
+            .collect::<Result<Vec<bool>>>()?;
+
+        emit!(PermissionsBatchChecked {
+            user: ctx.accounts.subject(),
+            permissions,
+            results: results.clone(),
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(results)
+    }
+
+    /// `check_permission` for a specific resource: grants through a global assignment
+    /// or one scoped to `resource` (see `assign_role_scoped`), never another resource's.
+    pub fn check_permission_scoped(
+        ctx: Context<CheckPermission>,
+        resource: Pubkey,
+        required_permission: u32,
+    ) -> Result<bool> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let evaluation = evaluate(ctx.accounts, ctx.remaining_accounts, required_permission, &resource, current_time)?;
+
+        emit!(PermissionChecked {
+            user: ctx.accounts.subject(),
+            permission_checked: required_permission,
+            result: evaluation.allowed,
+            reason: evaluation.reason.label().to_string(),
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(evaluation.allowed)
+    }
+
+    /// Lightweight CPI view: whether `user` holds `permission` through the given
+    /// assignment. Takes no state account and emits nothing; a mismatched, scoped or
+    /// inactive assignment, or a frozen user, yields `false` rather than an error
+    /// so callers can branch on the result. Composite member roles go in `remaining_accounts`.
+    pub fn has_permission(
+        ctx: Context<HasPermission>,
+        user: Pubkey,
+        permission: u32,
+    ) -> Result<bool> {
+        let role = &ctx.accounts.role;
+        let user_role = &ctx.accounts.user_role;
+        // A resource-scoped assignment is no global grant
+        if user_role.user != user
+            || user_role.role != role.name
+            || user_role.resource != GLOBAL_RESOURCE
+        {
+            return Ok(false);
+        }
+        if user_status_frozen(&ctx.accounts.user_status)? {
+            return Ok(false);
+        }
+        let current_time = Clock::get()?.unix_timestamp;
+        if !role.is_enabled()
+            || !role.in_window(current_time)
+            || user_role.revoked
+            || user_role.is_expired(current_time)
+            || user_role.suspended
+            || !user_role.is_acknowledged(role)
+        {
+            return Ok(false);
+        }
+        let permissions = resolve_role_permissions(ctx.remaining_accounts, role, current_time)?
+            | user_role.active_temp_permission(current_time);
+        Ok(user_role.allows(permissions, permission))
+    }
+
+    /// Direct CPI verification that halts execution if it fails.
+    pub fn assert_has_permission(
+        ctx: Context<CheckPermission>,
+        required_permission: u32,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let evaluation = evaluate(ctx.accounts, ctx.remaining_accounts, required_permission, &GLOBAL_RESOURCE, current_time)?;
+        require_allowed(ctx.accounts, &evaluation, required_permission, current_time)
+    }
+
+    /// Authorization gateway: once the checked user holds `required_permission`,
+    /// forward `data` to `target_program` (which must be on `cpi_targets`). The first
+    /// `check_accounts` remaining accounts feed the permission check as usual; the
+    /// rest become the forwarded instruction's accounts, where the `gateway` PDA
+    /// signs for itself so targets can trust it as "RBAC approved this". Only the
+    /// checked user may forward, and nothing is forwarded while paused.
+    pub fn execute_action_cpi<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExecuteActionCpi<'info>>,
+        required_permission: u32,
+        check_accounts: u8,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.check.rbac_state.paused, RbacError::SystemPaused);
+        require_keys_eq!(
+            ctx.accounts.check.caller.key(),
+            ctx.accounts.check.subject(),
+            RbacError::NotAuthorized
+        );
+        let target_program = ctx.accounts.target_program.key();
+        require!(
+            ctx.accounts.check.rbac_state.cpi_targets.contains(&target_program),
+            RbacError::CpiTargetNotAllowed
+        );
+        let split = usize::from(check_accounts);
+        require!(
+            split <= ctx.remaining_accounts.len(),
+            ErrorCode::AccountNotEnoughKeys
+        );
+        let (check_remaining, forwarded) = ctx.remaining_accounts.split_at(split);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let evaluation = evaluate(&ctx.accounts.check, check_remaining, required_permission, &GLOBAL_RESOURCE, current_time)?;
+        require_allowed(&ctx.accounts.check, &evaluation, required_permission, current_time)?;
+
+        let gateway = ctx.accounts.gateway.key();
+        let instruction = Instruction {
+            program_id: target_program,
+            accounts: forwarded
+                .iter()
+                .map(|info| AccountMeta {
+                    pubkey: *info.key,
+                    is_signer: info.is_signer || *info.key == gateway,
+                    is_writable: info.is_writable,
+                })
+                .collect(),
+            data,
+        };
+        let mut infos = forwarded.to_vec();
+        infos.push(ctx.accounts.gateway.to_account_info());
+        infos.push(ctx.accounts.target_program.to_account_info());
+        invoke_signed(&instruction, &infos, &[&[seeds::GATEWAY, &[ctx.bumps.gateway]]])?;
+
+        emit!(ActionForwarded {
+            user: ctx.accounts.check.subject(),
+            permission: required_permission,
+            target_program,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Halt unless `user` holds at least `threshold` of `required_roles`, each
+    /// granting `required_permission`, for especially sensitive actions. The
+    /// user's `(UserRole, Role)` pairs are read from `remaining_accounts`.
+    pub fn assert_role_quorum(
+        ctx: Context<ReadState>,
+        user: Pubkey,
+        required_permission: u32,
+        required_roles: Vec<String>,
+        threshold: u8,
+    ) -> Result<()> {
+        require!(
+            required_roles.len() <= MAX_QUORUM_ROLES
+                && threshold > 0
+                && usize::from(threshold) <= required_roles.len(),
+            RbacError::InvalidQuorum
+        );
+        let current_time = Clock::get()?.unix_timestamp;
+
+        let mut held = 0u8;
+        for (index, role_name) in required_roles.iter().enumerate() {
+            require!(
+                !required_roles[..index].contains(role_name),
+                RbacError::InvalidQuorum
+            );
+            let (user_role_address, _) = Pubkey::find_program_address(
+                &[seeds::USER_ROLE, user.as_ref(), role_name.as_bytes()],
+                &crate::ID,
+            );
+            let (role_address, _) = Pubkey::find_program_address(
+                &[seeds::ROLE, role_name.as_bytes()],
+                &crate::ID,
+            );
+            let (Some(user_role_info), Some(role_info)) = (
+                find_remaining(ctx.remaining_accounts, &user_role_address),
+                find_remaining(ctx.remaining_accounts, &role_address),
+            ) else {
+                continue;
+            };
+            let user_role = load_account::<UserRole>(user_role_info)?;
+            let role = load_account::<Role>(role_info)?;
+            if role.is_live(current_time)
+                && !user_role.revoked
+                && !user_role.is_expired(current_time)
+                && !user_role.suspended
+                && user_role.is_acknowledged(&role)
+                && user_role.allows(resolve_role_permissions(ctx.remaining_accounts, &role, current_time)?, required_permission)
+            {
+                held += 1;
+            }
+        }
+
+        require!(held >= threshold, RbacError::QuorumNotMet);
+        Ok(())
+    }
+
+    /// Whether the named role grants `required_permission`, independent of any
+    /// user. With `include_composition`, member roles passed in `remaining_accounts`
+    /// are unioned in as in a regular check.
+    pub fn check_by_role_name(
+        ctx: Context<ReadRole>,
+        _role_name: String,
+        required_permission: u32,
+        include_composition: bool,
+    ) -> Result<bool> {
+        let role = &ctx.accounts.role;
+        let permissions = if include_composition {
+            resolve_role_permissions(ctx.remaining_accounts, role, Clock::get()?.unix_timestamp)?
+        } else {
+            role.permissions
+        };
+        Ok(grants(permissions, required_permission))
+    }
+
+    /// Like `check_permission`, but additionally fails with `JurisdictionMismatch`
+    /// unless the assignment is tagged with `jurisdiction`. The system admin is exempt.
+    pub fn check_permission_in_jurisdiction(
+        ctx: Context<CheckPermission>,
+        required_permission: u32,
+        jurisdiction: String,
+    ) -> Result<bool> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let evaluation = evaluate(ctx.accounts, ctx.remaining_accounts, required_permission, &GLOBAL_RESOURCE, current_time)?;
+        if evaluation.reason != EvaluationReason::SystemAdmin {
+            let (user_role, _) = ctx.accounts.assignment()?;
+            require!(
+                user_role.jurisdiction.as_deref() == Some(jurisdiction.as_str()),
+                RbacError::JurisdictionMismatch
+            );
+        }
+
+        emit!(PermissionChecked {
+            user: ctx.accounts.subject(),
+            permission_checked: required_permission,
+            result: evaluation.allowed,
+            reason: evaluation.reason.label().to_string(),
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(evaluation.allowed)
+    }
+
+    /// Whether the subject may disburse `amount` lamports: the permission check must
+    /// pass and `amount` must be within the assigned role's `max_disbursement`.
+    /// The system admin is uncapped.
+    pub fn check_disbursement(
+        ctx: Context<CheckPermission>,
+        required_permission: u32,
+        amount: u64,
+    ) -> Result<bool> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let evaluation = evaluate(ctx.accounts, ctx.remaining_accounts, required_permission, &GLOBAL_RESOURCE, current_time)?;
+        let allowed = match evaluation.reason {
+            EvaluationReason::SystemAdmin => true,
+            _ if evaluation.allowed => {
+                let (_, role) = ctx.accounts.assignment()?;
+                amount <= role.max_disbursement
+            }
+            _ => false,
+        };
+
+        emit!(PermissionChecked {
+            user: ctx.accounts.subject(),
+            permission_checked: required_permission,
+            result: allowed,
+            reason: evaluation.reason.label().to_string(),
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(allowed)
+    }
+
+    /// Return the full structured decision (outcome plus the deciding layer)
+    /// for a permission check, without emitting events.
+    pub fn evaluate_permission(
+        ctx: Context<CheckPermission>,
+        required_permission: u32,
+    ) -> Result<Evaluation> {
+        let current_time = Clock::get()?.unix_timestamp;
+        evaluate(ctx.accounts, ctx.remaining_accounts, required_permission, &GLOBAL_RESOURCE, current_time)
+    }
+
+    /// Project the subject's effective permission bits at `at_timestamp`, e.g. for
+    /// planning access reviews. Expiry, the role's validity window and the global
+    /// grant window are evaluated at that time; other state is taken as it stands
+    /// now. Read-only.
+    pub fn effective_permissions_at(
+        ctx: Context<CheckPermission>,
+        at_timestamp: i64,
+    ) -> Result<u32> {
+        if ctx.accounts.is_frozen()? {
+            return Ok(0);
+        }
+        if ctx.accounts.rbac_state.is_admin(&ctx.accounts.subject()) {
+            return Ok(PERM_READ | PERM_CREATE | PERM_UPDATE | PERM_DELETE | PERM_ADMIN);
+        }
+        let (user_role, role) = ctx.accounts.assignment()?;
+        if !role.is_enabled()
+            || !role.in_window(at_timestamp)
+            || user_role.revoked
+            || user_role.is_expired(at_timestamp)
+            || user_role.suspended
+            || !user_role.is_acknowledged(role)
+        {
+            return Ok(0);
+        }
+        let permissions = resolve_role_permissions(ctx.remaining_accounts, role, at_timestamp)?
+            | role.met_conditional_grants(ctx.accounts.rbac_state.assignment_count)
+            | inherited_base_permissions(&ctx.accounts.rbac_state, ctx.remaining_accounts)?
+            | ctx.accounts.rbac_state.active_global_grant(at_timestamp)
+            | user_role.active_temp_permission(at_timestamp);
+        Ok(permissions & !user_role.denied_permissions)
+    }
+
+    /// Resolve a role's permissions through its whole role graph: parents and
+    /// composition members, each expanded transitively and counted once. Pass every
+    /// reachable role in `remaining_accounts`; unsupplied ones are skipped. Read-only.
+    pub fn resolve_effective_permissions(
+        ctx: Context<ReadRole>,
+        _role_name: String,
+    ) -> Result<u32> {
+        resolve_role_graph(ctx.remaining_accounts, &ctx.accounts.role, Clock::get()?.unix_timestamp)
+    }
+
+    /// Whether `role_name`'s permission set covers `permission`, no assignment
+    /// needed. Group and composed-role bits count when their accounts are passed in
+    /// `remaining_accounts`, and the Admin bit implies every other permission, as in
+    /// a check. Whether the role itself is active or in its validity window is
+    /// ignored, but disabled members add nothing. Emits nothing.
+    pub fn role_has_permission(
+        ctx: Context<ReadRole>,
+        _role_name: String,
+        permission: u32,
+    ) -> Result<bool> {
+        let permissions = resolve_role_permissions(
+            ctx.remaining_accounts,
+            &ctx.accounts.role,
+            Clock::get()?.unix_timestamp,
+        )?;
+        Ok(grants(permissions, permission))
+    }
+
+    /// Number of live assignments of a role, read from its counter rather than by
+    /// scanning `UserRole` accounts. Read-only.
+    pub fn role_user_count(
+        ctx: Context<ReadRole>,
+        _role_name: String,
+    ) -> Result<u32> {
+        Ok(ctx.accounts.role.assignment_count)
+    }
+
+    /// Names of every role `user` holds a global assignment of, from their
+    /// `UserRoleIndex`. Scoped assignments are not indexed. Read-only.
+    pub fn get_user_roles(
+        ctx: Context<ReadUserIndex>,
+        _user: Pubkey,
+    ) -> Result<Vec<String>> {
+        Ok(ctx.accounts.user_index.roles.clone())
+    }
+
+    /// Permission check pinned to the slot it executed in, so downstream consumers
+    /// can reject stale results. With `as_of_slot` set the check fails unless it
+    /// runs in exactly that slot.
+    pub fn check_permission_at_slot(
+        ctx: Context<CheckPermission>,
+        required_permission: u32,
+        as_of_slot: Option<u64>,
+    ) -> Result<SlotPinnedCheck> {
+        let clock = Clock::get()?;
+        if let Some(expected) = as_of_slot {
+            require!(expected == clock.slot, RbacError::StaleSlot);
+        }
+        let evaluation = evaluate(
+            ctx.accounts,
+            ctx.remaining_accounts,
+            required_permission,
+            &GLOBAL_RESOURCE,
+            clock.unix_timestamp,
+        )?;
+        Ok(SlotPinnedCheck {
+            allowed: evaluation.allowed,
+            slot: clock.slot,
+        })
+    }
+
+    /// Accept an assignment's responsibilities, activating it for roles that
+    /// require acknowledgment. Signed by the assigned user.
+    pub fn acknowledge_role(
+        ctx: Context<AcknowledgeRole>,
+        role_name: String,
+    ) -> Result<()> {
+        ctx.accounts.user_role.acknowledged = true;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleAcknowledged {
+            user: ctx.accounts.user.key(),
+            role: role_name,
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Restrict an assignment so permission checks additionally require a
+    /// co-signature from one of up to 4 allowlisted keys. An empty list lifts it.
+    pub fn set_signer_allowlist(
+        ctx: Context<UpdateAssignment>,
+        user: Pubkey,
+        role_name: String,
+        signer_allowlist: Vec<Pubkey>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(
+            signer_allowlist.len() <= MAX_SIGNER_ALLOWLIST,
+            RbacError::SignerAllowlistTooLong
+        );
+
+        ctx.accounts.user_role.signer_allowlist = signer_allowlist.clone();
+        ctx.accounts.user_role.refresh_fingerprint();
+
+        emit_state_event(&mut ctx.accounts.rbac_state, SignerAllowlistSet {
+            user,
+            role: role_name,
+            signer_allowlist,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Opt a time-bound assignment into automatic renewal by `renew_interval` seconds.
+    pub fn enable_auto_renew(
+        ctx: Context<UpdateAssignment>,
+        user: Pubkey,
+        role_name: String,
+        renew_interval: i64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(renew_interval > 0, RbacError::InvalidRenewInterval);
+
+        let user_role = &mut ctx.accounts.user_role;
+        require!(
+            user_role.expires_at.is_some(),
+            RbacError::AutoRenewRequiresExpiry
+        );
+        user_role.auto_renew = true;
+        user_role.renew_interval = renew_interval;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AutoRenewChanged {
+            user,
+            role: role_name,
+            auto_renew: true,
+            renew_interval,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Stop an assignment from renewing; it will lapse at its current expiry.
+    pub fn cancel_auto_renew(
+        ctx: Context<UpdateAssignment>,
+        user: Pubkey,
+        role_name: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+
+        let user_role = &mut ctx.accounts.user_role;
+        user_role.auto_renew = false;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AutoRenewChanged {
+            user,
+            role: role_name,
+            auto_renew: false,
+            renew_interval: user_role.renew_interval,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Carve `permission` bits out of one assignment: checks through it deny them
+    /// even when the role (or its Admin bit) would otherwise grant them.
+    pub fn deny_permission(
+        ctx: Context<UpdateAssignment>,
+        user: Pubkey,
+        role_name: String,
+        permission: u32,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(
+            permission != 0 && permission <= 31u32,
+            RbacError::InvalidPermissions
+        );
+
+        let user_role = &mut ctx.accounts.user_role;
+        user_role.denied_permissions |= permission;
+        user_role.refresh_fingerprint();
+
+        emit_state_event(&mut ctx.accounts.rbac_state, DeniedPermissionsChanged {
+            user,
+            role: role_name,
+            denied_permissions: user_role.denied_permissions,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Lift a `deny_permission` carve-out, restoring the bits to the assignment.
+    pub fn clear_denied_permission(
+        ctx: Context<UpdateAssignment>,
+        user: Pubkey,
+        role_name: String,
+        permission: u32,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(
+            permission != 0 && permission <= 31u32,
+            RbacError::InvalidPermissions
+        );
+
+        let user_role = &mut ctx.accounts.user_role;
+        user_role.denied_permissions &= !permission;
+        user_role.refresh_fingerprint();
+
+        emit_state_event(&mut ctx.accounts.rbac_state, DeniedPermissionsChanged {
+            user,
+            role: role_name,
+            denied_permissions: user_role.denied_permissions,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Break-glass elevation: grant `permission` (e.g. Admin) through one assignment
+    /// for `duration_secs` without touching the role. Checks ignore the grant once it
+    /// lapses, so no cleanup is needed; elevating again replaces it.
+    pub fn elevate_user(
+        ctx: Context<UpdateAssignment>,
+        user: Pubkey,
+        role_name: String,
+        permission: u32,
+        duration_secs: i64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(
+            permission != 0 && permission <= 31u32,
+            RbacError::InvalidPermissions
+        );
+        require!(
+            duration_secs > 0 && duration_secs <= MAX_BREAKGLASS_DURATION,
+            RbacError::InvalidBreakglassDuration
+        );
+
+        let expires_at = current_time + duration_secs;
+        let user_role = &mut ctx.accounts.user_role;
+        user_role.temp_permission = Some(permission);
+        user_role.temp_expires_at = expires_at;
+        user_role.refresh_fingerprint();
+
+        emit_state_event(&mut ctx.accounts.rbac_state, PermissionElevated {
+            user,
+            role: role_name,
+            permission,
+            expires_at,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Suspend every permission `user` holds at once (e.g. on suspected key
+    /// compromise): checks that pass their `UserStatus` deny regardless of roles.
+    /// Works while paused or config-frozen, since checks keep passing then.
+    pub fn freeze_user(ctx: Context<SetUserStatus>, user: Pubkey) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+
+        let user_status = &mut ctx.accounts.user_status;
+        user_status.user = user;
+        user_status.frozen = true;
+        user_status.updated_at = current_time;
+        user_status.bump = ctx.bumps.user_status;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, UserFrozen {
+            user,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Lift a `freeze_user`, restoring whatever the user's roles grant.
+    pub fn unfreeze_user(ctx: Context<SetUserStatus>, user: Pubkey) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+
+        let user_status = &mut ctx.accounts.user_status;
+        user_status.user = user;
+        user_status.frozen = false;
+        user_status.updated_at = current_time;
+        user_status.bump = ctx.bumps.user_status;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, UserUnfrozen {
+            user,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank: extend an auto-renewing assignment by its interval
+    /// once it is within one interval of expiring (and has not yet lapsed).
+    pub fn process_renewal(
+        ctx: Context<ProcessRenewal>,
+        user: Pubkey,
+        role_name: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let user_role = &mut ctx.accounts.user_role;
+        require!(user_role.auto_renew, RbacError::AutoRenewDisabled);
+        require!(
+            ctx.accounts.role.name == user_role.role,
+            RbacError::UserRoleMismatch
+        );
+
+        let expiry = user_role.expires_at.ok_or(RbacError::AutoRenewRequiresExpiry)?;
+        require!(current_time < expiry, RbacError::AssignmentExpired);
+        require!(
+            current_time >= expiry.saturating_sub(user_role.renew_interval),
+            RbacError::RenewalNotDue
+        );
+
+        let new_expiry = expiry
+            .checked_add(user_role.renew_interval)
+            .ok_or(RbacError::InvalidRenewInterval)?;
+        user_role.expires_at = Some(new_expiry);
+        user_role.warned = false;
+        user_role.refresh_fingerprint();
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleRenewed {
+            user,
+            role: role_name,
+            previous_expiry: expiry,
+            expires_at: new_expiry,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Freeze a role's current permissions for the current slot so every check in
+    /// the same transaction sees a consistent set, even if the role changes mid-flight.
+    /// Checks pick the snapshot up when it is passed in `remaining_accounts`.
+    pub fn snapshot_permissions(
+        ctx: Context<SnapshotPermissions>,
+        role_name: String,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.role = role_name.clone();
+        snapshot.permissions = ctx.accounts.role.permissions;
+        snapshot.slot = clock.slot;
+        snapshot.bump = ctx.bumps.snapshot;
+
+        emit!(PermissionsSnapshotted {
+            role: role_name,
+            permissions: snapshot.permissions,
+            slot: snapshot.slot,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Hand `permission` (a subset of the signer's role `role_name`) to `to_user`
+    /// until `expires_at`, without admin involvement. The token is honoured by
+    /// permission checks only while the delegator still holds the permission.
+    pub fn delegate_permission(
+        ctx: Context<DelegatePermission>,
+        to_user: Pubkey,
+        role_name: String,
+        permission: u32,
+        expires_at: i64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            permission != 0 && permission <= 31u32,
+            RbacError::InvalidPermissions
+        );
+        require!(expires_at > current_time, RbacError::InvalidExpiry);
+
+        let delegator_role = &ctx.accounts.delegator_role;
+        require!(
+            !delegator_role.is_expired(current_time),
+            RbacError::AssignmentExpired
+        );
+        // Co-signer restrictions cannot be carried over to the recipient
+        require!(
+            delegator_role.signer_allowlist.is_empty(),
+            RbacError::SignerNotAllowed
+        );
+        require!(
+            ctx.accounts.role.permissions & permission == permission,
+            RbacError::DelegationExceedsRole
+        );
+        // Bits denied to the delegator cannot be handed on
+        require!(
+            delegator_role.denied_permissions & permission == 0,
+            RbacError::DelegationExceedsRole
+        );
+
+        let token = &mut ctx.accounts.token;
+        token.delegator = ctx.accounts.delegator.key();
+        token.delegate = to_user;
+        token.role = role_name.clone();
+        token.permissions = permission;
+        token.expires_at = expires_at;
+        token.bump = ctx.bumps.token;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, PermissionDelegated {
+            delegator: ctx.accounts.delegator.key(),
+            delegate: to_user,
+            role: role_name,
+            permissions: permission,
+            expires_at,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a delegation early, refunding its rent to the delegator.
+    pub fn revoke_delegation(
+        ctx: Context<RevokeDelegation>,
+        to_user: Pubkey,
+        role_name: String,
+    ) -> Result<()> {
+        emit_state_event(&mut ctx.accounts.rbac_state, DelegationRevoked {
+            delegator: ctx.accounts.delegator.key(),
+            delegate: to_user,
+            role: role_name,
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Suspend up to 10 holders of `role_name` at once (their `UserRole` accounts
+    /// passed writable in `remaining_accounts`), e.g. during an incident.
+    pub fn suspend_role_holders<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateRole<'info>>,
+        role_name: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        let users = set_holders_suspended(ctx.remaining_accounts, &role_name, true)?;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, BulkSuspended {
+            role: role_name,
+            users,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Lift a bulk suspension; the inverse of `suspend_role_holders`.
+    pub fn unsuspend_role_holders<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UpdateRole<'info>>,
+        role_name: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.admin.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        let users = set_holders_suspended(ctx.remaining_accounts, &role_name, false)?;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, BulkUnsuspended {
+            role: role_name,
+            users,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a role from user by closing the PDA. With a revocation grace period
+    /// configured the PDA is only marked revoked, stays inspectable, and is closed
+    /// later by `finalize_revocation`.
+    pub fn revoke_role(
+        ctx: Context<RevokeRole>,
+        role_name: String,
+        bypass_cooldown: bool,
+    ) -> Result<()> {
+        // Only admin, an active break-glass key or an org admin scoped to the role can revoke
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &ctx.accounts.authority.key(),
+            &role_name,
+            current_time,
+        )?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(!ctx.accounts.user_role.revoked, RbacError::AssignmentRevoked);
+        ctx.accounts.rbac_state.require_change_cooled(
+            &ctx.accounts.user_role,
+            &ctx.accounts.authority.key(),
+            bypass_cooldown,
+            current_time,
+        )?;
+
+        decrement(&mut ctx.accounts.rbac_state.assignment_count)?;
+        ctx.accounts.role.release_assignment();
+        let close_now = ctx.accounts.rbac_state.revocation_grace_secs == 0;
+
+        // A clean revoke returns the assigner's bond before the account is closed
+        if let Some((staker, stake)) = return_stake(
+            &mut ctx.accounts.user_role,
+            &ctx.accounts.authority,
+            ctx.remaining_accounts,
+            close_now,
+        )? {
+            emit_state_event(&mut ctx.accounts.rbac_state, StakeReturned {
+                user: ctx.accounts.user_role.user,
+                role: role_name.clone(),
+                staked_by: staker,
+                amount: stake,
+                timestamp: current_time,
+                slot: Clock::get()?.slot,
+            });
+        }
+
+        unindex_role(&mut ctx.accounts.user_index, &role_name)?;
+
+        ctx.accounts.audit_log.record(AuditEntry {
+            actor: ctx.accounts.authority.key(),
+            action_kind: AUDIT_ROLE_REVOKED,
+            target: ctx.accounts.user_role.user,
+            timestamp: current_time,
+        });
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleRevoked {
+            user: ctx.accounts.user_role.user,
+            revoked_by: ctx.accounts.authority.key(),
+            role_revoked: ctx.accounts.user_role.role.clone(),
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        if close_now {
+            return ctx.accounts.user_role.close(ctx.accounts.authority.to_account_info());
+        }
+        // Lapse the assignment everywhere validity is checked until it is finalized
+        let user_role = &mut ctx.accounts.user_role;
+        user_role.revoked = true;
+        user_role.revoked_at = current_time;
+        user_role.last_changed_at = current_time;
+        user_role.expires_at = Some(current_time);
+        user_role.auto_renew = false;
+        user_role.refresh_fingerprint();
+        Ok(())
+    }
+
+    /// Move a user from `old_role_name` to `new_role_name` in one transaction, so
+    /// they are never left without a role. The old `UserRole` PDA is closed and the
+    /// new one created with the same expiry, note and jurisdiction. Assignments
+    /// carrying an assigner stake must go through revoke and assign instead.
+    pub fn reassign_role<'info>(
+        ctx: Context<'_, '_, '_, 'info, ReassignRole<'info>>,
+        user: Pubkey,
+        old_role_name: String,
+        new_role_name: String,
+        bypass_cooldown: bool,
+    ) -> Result<()> {
+        require!(new_role_name.len() <= MAX_ROLE_NAME_LEN, RbacError::RoleNameTooLong);
+        require!(old_role_name != new_role_name, RbacError::InvalidReassignment);
+        let current_time = Clock::get()?.unix_timestamp;
+        let authority = ctx.accounts.authority.key();
+        authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &authority,
+            &old_role_name,
+            current_time,
+        )?;
+        let breakglass = authorize_role_admin(
+            &ctx.accounts.rbac_state,
+            ctx.remaining_accounts,
+            &authority,
+            &new_role_name,
+            current_time,
+        )?;
+        require_wildcard_authority(&ctx.accounts.rbac_state, &authority, &new_role_name, current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(
+            ctx.accounts.new_role.name == new_role_name,
+            RbacError::RoleNotFound
+        );
+
+        let old_assignment = &ctx.accounts.old_user_role;
+        require!(!old_assignment.revoked, RbacError::AssignmentRevoked);
+        ctx.accounts.rbac_state.require_change_cooled(
+            old_assignment,
+            &authority,
+            bypass_cooldown,
+            current_time,
+        )?;
+        require!(
+            old_assignment.stake == 0 && ctx.accounts.new_role.assigner_stake == 0,
+            RbacError::StakedReassignment
+        );
+        if ctx.accounts.rbac_state.require_note_for_privileged
+            && ctx.accounts.new_role.permissions & PRIVILEGED_PERMISSIONS != 0
+        {
+            require!(
+                old_assignment.note.as_ref().is_some_and(|n| !n.is_empty()),
+                RbacError::NoteRequired
+            );
+        }
+        require_attribute(ctx.remaining_accounts, &ctx.accounts.new_role, &user)?;
+        require_token_gate(ctx.remaining_accounts, &ctx.accounts.new_role, &user)?;
+        require_co_role(ctx.remaining_accounts, &ctx.accounts.new_role, &user, current_time)?;
+
+        let expires_at = old_assignment.expires_at;
+        let note = old_assignment.note.clone();
+        let jurisdiction = old_assignment.jurisdiction.clone();
+
+        let user_role = &mut ctx.accounts.new_user_role;
+        user_role.user = user;
+        user_role.role = new_role_name.clone();
+        user_role.assigned_at = current_time;
+        user_role.last_changed_at = current_time;
+        user_role.expires_at = expires_at;
+        user_role.assigned_by = authority;
+        user_role.staked_by = authority;
+        user_role.bump = ctx.bumps.new_user_role;
+        user_role.version = UserRole::current_version();
+        user_role.note = note;
+        user_role.jurisdiction = jurisdiction;
+        user_role.acknowledged = !ctx.accounts.new_role.require_acknowledgment;
+        user_role.refresh_fingerprint();
+
+        ctx.accounts.old_role.release_assignment();
+        ctx.accounts.new_role.claim_assignment()?;
+
+        unindex_role(&mut ctx.accounts.user_index, &old_role_name)?;
+        index_role(
+            &mut ctx.accounts.user_index,
+            &new_role_name,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+        )?;
+
+        invoke_assignment_hook(&ctx.accounts.rbac_state, ctx.remaining_accounts, OnRoleAssignedArgs {
+            user,
+            role: new_role_name.clone(),
+            assigned_by: authority,
+            expires_at,
+        })?;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RoleReassigned {
+            user,
+            old_role: old_role_name,
+            new_role: new_role_name,
+            reassigned_by: authority,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Close an assignment revoked under a grace period once the period has
+    /// elapsed. Permissionless; rent is refunded to the original payer.
+    pub fn finalize_revocation(
+        ctx: Context<FinalizeRevocation>,
+        user: Pubkey,
+        role_name: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let user_role = &ctx.accounts.user_role;
+        require!(user_role.revoked, RbacError::AssignmentNotRevoked);
+        require!(
+            current_time >= user_role.revoked_at.saturating_add(ctx.accounts.rbac_state.revocation_grace_secs),
+            RbacError::GracePeriodActive
+        );
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RevocationFinalized {
+            user,
+            role: role_name,
+            refunded_to: ctx.accounts.assigned_by.key(),
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Keep revoked assignments inspectable for `revocation_grace_secs` before
+    /// they can be closed. 0 closes assignments immediately on revoke.
+    pub fn set_revocation_grace(
+        ctx: Context<UpdateConfig>,
+        revocation_grace_secs: i64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        require!(revocation_grace_secs >= 0, RbacError::InvalidGracePeriod);
+
+        ctx.accounts.rbac_state.revocation_grace_secs = revocation_grace_secs;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RevocationGraceSet {
+            revocation_grace_secs,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Require `change_cooldown_secs` between changes (revoke, reassign) to the same
+    /// assignment, so a compromised manager key cannot thrash it. 0 disables it.
+    pub fn set_change_cooldown(
+        ctx: Context<UpdateConfig>,
+        change_cooldown_secs: i64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        require!(change_cooldown_secs >= 0, RbacError::InvalidCooldown);
+
+        ctx.accounts.rbac_state.change_cooldown_secs = change_cooldown_secs;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, ChangeCooldownSet {
+            change_cooldown_secs,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Cap how many roles may exist at once (0 = unlimited). Roles already
+    /// beyond a lowered cap are kept, but no new ones can be created.
+    pub fn set_max_roles(
+        ctx: Context<UpdateConfig>,
+        max_roles: u32,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+
+        ctx.accounts.rbac_state.max_roles = max_roles;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, MaxRolesSet {
+            max_roles,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Cap how many `grant_assigner` hops may separate a role manager from the root
+    /// admin (1 = only admin-granted managers). Existing deeper managers are kept.
+    pub fn set_max_delegation_depth(
+        ctx: Context<UpdateConfig>,
+        max_delegation_depth: u8,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+
+        ctx.accounts.rbac_state.max_delegation_depth = max_delegation_depth;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, MaxDelegationDepthSet {
+            max_delegation_depth,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Confiscate the assigner's bond on an improper assignment, paying it to the
+    /// admin. The assignment itself is left in place.
+    pub fn slash_assigner(
+        ctx: Context<SlashAssigner>,
+        user: Pubkey,
+        role_name: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+
+        let stake = ctx.accounts.user_role.stake;
+        require!(stake > 0, RbacError::NoStake);
+        ctx.accounts.user_role.sub_lamports(stake)?;
+        ctx.accounts.admin.add_lamports(stake)?;
+        ctx.accounts.user_role.stake = 0;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AssignerSlashed {
+            user,
+            role: role_name,
+            staked_by: ctx.accounts.user_role.staked_by,
+            amount: stake,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Compare the effective permission sets of two users for access reviews.
+    /// Expects `(UserRole, Role)` pairs for either user in `remaining_accounts`;
+    /// expired assignments are ignored.
+    /// Returns -1 if A ⊂ B, 0 if A == B, 1 if A ⊃ B and 2 if incomparable.
+    pub fn compare_users<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReadState<'info>>,
+        user_a: Pubkey,
+        user_b: Pubkey,
+    ) -> Result<i8> {
+        require!(
+            ctx.remaining_accounts.len() % 2 == 0,
+            RbacError::InvalidRemainingAccounts
+        );
+        let current_time = Clock::get()?.unix_timestamp;
+
+        let mut permissions_a = 0u32;
+        let mut permissions_b = 0u32;
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let (user_role, role) = load_assignment(&pair[0], &pair[1])?;
+            require!(
+                user_role.user == user_a || user_role.user == user_b,
+                RbacError::UserRoleMismatch
+            );
+            if user_role.is_expired(current_time) {
+                continue;
+            }
+            if user_role.user == user_a {
+                permissions_a |= role.permissions;
+            }
+            if user_role.user == user_b {
+                permissions_b |= role.permissions;
+            }
+        }
+
+        let a_covers_b = permissions_a & permissions_b == permissions_b;
+        let b_covers_a = permissions_a & permissions_b == permissions_a;
+        Ok(match (a_covers_b, b_covers_a) {
+            (true, true) => 0,
+            (true, false) => 1,
+            (false, true) => -1,
+            (false, false) => 2,
+        })
+    }
+
+    /// Temporarily broaden every assigned user's permissions (e.g. global Read
+    /// during a migration). Pass `None` to clear the grant early.
+    pub fn set_global_grant(
+        ctx: Context<UpdateConfig>,
+        grant: Option<GlobalGrant>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        if let Some(grant) = grant {
+            require!(
+                grant.permissions <= 31u32,
+                RbacError::InvalidPermissions
+            );
+            require!(
+                grant.expires_at > current_time,
+                RbacError::InvalidExpiry
+            );
+        }
+
+        ctx.accounts.rbac_state.global_grant = grant;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, GlobalGrantSet {
+            permissions: grant.map_or(0, |g| g.permissions),
+            expires_at: grant.map(|g| g.expires_at),
+            set_by: ctx.accounts.admin.key(),
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Require every permission check's subject to also hold a baseline role
+    /// (e.g. "system_user"). Checks must then pass the base `UserRole` and
+    /// `Role` in `remaining_accounts`, and the base assignment has to be live.
+    /// Pass `None` to drop the requirement.
+    pub fn set_required_base_role(
+        ctx: Context<UpdateConfig>,
+        base_role: Option<String>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        if let Some(name) = &base_role {
+            require!(name.len() <= MAX_ROLE_NAME_LEN, RbacError::RoleNameTooLong);
+        }
+
+        ctx.accounts.rbac_state.required_base_role = base_role.clone();
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RequiredBaseRoleSet {
+            base_role,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Make every user holding any active role inherit the permissions of
+    /// `base_role` (e.g. "authenticated"). Pass `None` to stop inheriting.
+    pub fn set_base_role(
+        ctx: Context<UpdateConfig>,
+        base_role: Option<String>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        if let Some(name) = &base_role {
+            require!(name.len() <= MAX_ROLE_NAME_LEN, RbacError::RoleNameTooLong);
+        }
+
+        ctx.accounts.rbac_state.base_role = base_role.clone();
+
+        emit_state_event(&mut ctx.accounts.rbac_state, BaseRoleSet {
+            base_role,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Restrict which permission bits may be granted to roles other than the admin
+    /// role (e.g. everything but Admin). Pass 0 to make every bit assignable again.
+    pub fn set_assignable_permissions(
+        ctx: Context<UpdateConfig>,
+        assignable_permissions: u32,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        require!(
+            assignable_permissions <= 31u32,
+            RbacError::InvalidPermissions
+        );
+
+        ctx.accounts.rbac_state.assignable_permissions = assignable_permissions;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AssignablePermissionsSet {
+            assignable_permissions,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Configure how many seconds before expiry `emit_expiry_warnings` starts
+    /// warning an assignment. Pass 0 to disable warnings.
+    pub fn set_expiry_warning_window(
+        ctx: Context<UpdateConfig>,
+        window: i64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        require!(window >= 0, RbacError::InvalidExpiry);
+
+        ctx.accounts.rbac_state.expiry_warning_window = window;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, ExpiryWarningWindowSet {
+            window,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Rewrite `rbac_state.bump` with the canonical bump if a bad migration ever
+    /// corrupted it. Refuses to run when the stored bump is already correct.
+    pub fn repair_state_bump(ctx: Context<RepairStateBump>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+
+        let canonical_bump = ctx.bumps.rbac_state;
+        let rbac_state = &mut ctx.accounts.rbac_state;
+        require!(
+            rbac_state.bump != canonical_bump,
+            RbacError::BumpAlreadyCanonical
+        );
+
+        let old_bump = rbac_state.bump;
+        rbac_state.bump = canonical_bump;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, StateBumpRepaired {
+            old_bump,
+            new_bump: canonical_bump,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Toggle the compliance policy requiring a non-empty note on every assignment
+    /// of a role holding Admin or Delete.
+    pub fn set_note_policy(
+        ctx: Context<UpdateConfig>,
+        require_note_for_privileged: bool,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+
+        ctx.accounts.rbac_state.require_note_for_privileged = require_note_for_privileged;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, NotePolicySet {
+            require_note_for_privileged,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Switch every mutating instruction between its specific events and a single
+    /// borsh-encoded `StateDelta` per change, for deterministic state mirroring.
+    pub fn set_replication_mode(
+        ctx: Context<UpdateConfig>,
+        enabled: bool,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+
+        ctx.accounts.rbac_state.replication_mode = enabled;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, ReplicationModeSet {
+            enabled,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Toggle key=value `msg!` lines alongside each state event, for indexers
+    /// that cannot decode Anchor events.
+    pub fn set_text_logs(
+        ctx: Context<UpdateConfig>,
+        enabled: bool,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+
+        ctx.accounts.rbac_state.text_logs = enabled;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, TextLogsSet {
+            enabled,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Configure an external compliance program invoked on every `assign_role`
+    /// with the `on_role_assigned` instruction. Pass `None` to disable.
+    pub fn set_hook_program(
+        ctx: Context<UpdateConfig>,
+        hook_program: Option<Pubkey>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+
+        ctx.accounts.rbac_state.hook_program = hook_program;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, HookProgramSet {
+            hook_program,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the programs `execute_action_cpi` may forward to. The RBAC program
+    /// itself can never be a target.
+    pub fn set_cpi_targets(
+        ctx: Context<UpdateConfig>,
+        cpi_targets: Vec<Pubkey>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+        require!(
+            cpi_targets.len() <= MAX_CPI_TARGETS,
+            RbacError::TooManyCpiTargets
+        );
+        require!(
+            !cpi_targets.contains(&crate::ID),
+            RbacError::CpiTargetNotAllowed
+        );
+
+        ctx.accounts.rbac_state.cpi_targets = cpi_targets.clone();
+
+        emit_state_event(&mut ctx.accounts.rbac_state, CpiTargetsSet {
+            cpi_targets,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Freeze (or unfreeze) every role and assignment mutation, e.g. during an
+    /// audit snapshot. Permission checks are unaffected.
+    pub fn set_config_frozen(
+        ctx: Context<UpdateConfig>,
+        frozen: bool,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+
+        ctx.accounts.rbac_state.config_frozen = frozen;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, ConfigFrozenSet {
+            frozen,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Register (or clear) a guardian key that may pause the system but not
+    /// undo the pause. Admin only.
+    pub fn set_guardian(
+        ctx: Context<UpdateConfig>,
+        guardian: Option<Pubkey>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+
+        ctx.accounts.rbac_state.guardian = guardian;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, GuardianSet {
+            guardian,
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Halt every role and assignment mutation at once during an incident.
+    /// Callable by the admin or the guardian; permission checks keep working.
+    pub fn pause(ctx: Context<UpdateConfig>) -> Result<()> {
+        ctx.accounts.rbac_state.require_unsealed()?;
+        let current_time = Clock::get()?.unix_timestamp;
+        let signer = ctx.accounts.admin.key();
+        if ctx.accounts.rbac_state.guardian != Some(signer) {
+            ctx.accounts.rbac_state.authorize_admin(&signer, current_time)?;
+        }
+
+        ctx.accounts.rbac_state.paused = true;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, SystemPaused {
+            paused_by: signer,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Permanently seal the configuration, e.g. after a launch review: every role,
+    /// assignment and config mutation fails with `ConfigSealed` from then on, with
+    /// no way back. Permission checks keep working. Root admin only (no break-glass).
+    pub fn seal_config(ctx: Context<UpdateConfig>) -> Result<()> {
+        let rbac_state = &mut ctx.accounts.rbac_state;
+        rbac_state.require_unsealed()?;
+        require!(
+            rbac_state.is_admin(&ctx.accounts.admin.key()),
+            RbacError::NotAuthorized
+        );
+
+        rbac_state.sealed = true;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, ConfigSealedForever {
+            sealed_by: ctx.accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Lift a pause. Admin only, so a compromised guardian cannot resume operations.
+    pub fn unpause(ctx: Context<UpdateConfig>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let breakglass = ctx.accounts.rbac_state
+            .authorize_admin(&ctx.accounts.admin.key(), current_time)?;
+
+        ctx.accounts.rbac_state.paused = false;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, SystemUnpaused {
+            unpaused_by: ctx.accounts.admin.key(),
+            breakglass,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Register (or clear) the break-glass key that may assume admin powers
+    /// for a limited window during an emergency. Root admin only.
+    pub fn register_breakglass(
+        ctx: Context<UpdateConfig>,
+        key: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unsealed()?;
+        require!(
+            ctx.accounts.rbac_state.is_admin(&ctx.accounts.admin.key()),
+            RbacError::NotAuthorized
+        );
+
+        let rbac_state = &mut ctx.accounts.rbac_state;
+        rbac_state.breakglass_key = key;
+        rbac_state.breakglass_expires_at = 0;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, BreakGlassRegistered {
+            key,
+            registered_by: ctx.accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Delegate management of roles whose names start with one of `prefixes`
+    /// (e.g. "eng-") to `admin_key`. An empty list revokes the delegation. Root admin only.
+    pub fn set_org_admin(
+        ctx: Context<SetOrgAdmin>,
+        admin_key: Pubkey,
+        prefixes: Vec<String>,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unsealed()?;
+        require!(
+            ctx.accounts.rbac_state.is_admin(&ctx.accounts.admin.key()),
+            RbacError::NotAuthorized
+        );
+        require!(
+            prefixes.len() <= MAX_ORG_ADMIN_PREFIXES,
+            RbacError::TooManyAdminPrefixes
+        );
+        for prefix in &prefixes {
+            require!(prefix.len() <= MAX_ROLE_NAME_LEN, RbacError::RoleNameTooLong);
+        }
+
+        let org_admin = &mut ctx.accounts.org_admin;
+        org_admin.admin = admin_key;
+        org_admin.prefixes = prefixes.clone();
+        org_admin.bump = ctx.bumps.org_admin;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, OrgAdminSet {
+            admin: admin_key,
+            prefixes,
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Let `manager` assign `role_name` (and only that role) by passing its
+    /// `RoleManager` PDA to `assign_role`. The admin role cannot be delegated. A root
+    /// admin grants at depth 1; an existing manager of the role may sub-delegate one
+    /// level deeper by passing its own `RoleManager` PDA in `remaining_accounts`, up
+    /// to `max_delegation_depth`.
+    pub fn grant_assigner(
+        ctx: Context<GrantAssigner>,
+        manager: Pubkey,
+        role_name: String,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(role_name != ADMIN_ROLE_NAME, RbacError::AdminRoleNotDelegable);
+        let granted_by = ctx.accounts.admin.key();
+        let depth = if ctx.accounts.rbac_state.is_admin(&granted_by) {
+            1
+        } else {
+            role_manager_of(ctx.remaining_accounts, &granted_by, &role_name)?
+                .ok_or(RbacError::NotAuthorized)?
+                .depth
+                .saturating_add(1)
+        };
+        require!(
+            depth <= ctx.accounts.rbac_state.max_delegation_depth,
+            RbacError::DelegationTooDeep
+        );
+
+        let role_manager = &mut ctx.accounts.role_manager;
+        role_manager.manager = manager;
+        role_manager.role = role_name.clone();
+        role_manager.bump = ctx.bumps.role_manager;
+        role_manager.depth = depth;
+        role_manager.granted_by = granted_by;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AssignerGranted {
+            manager,
+            role: role_name,
+            granted_by,
+            depth,
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw a manager's authority to assign `role_name`, refunding the
+    /// `RoleManager` rent to the admin. Root admin only.
+    pub fn revoke_assigner(
+        ctx: Context<RevokeAssigner>,
+        manager: Pubkey,
+        role_name: String,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        require!(
+            ctx.accounts.rbac_state.is_admin(&ctx.accounts.admin.key()),
+            RbacError::NotAuthorized
+        );
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AssignerRevoked {
+            manager,
+            role: role_name,
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Activate break-glass admin powers for `duration` seconds.
+    /// Every use is loudly audited and expires automatically.
+    pub fn invoke_breakglass(
+        ctx: Context<InvokeBreakglass>,
+        duration: i64,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unsealed()?;
+        let rbac_state = &mut ctx.accounts.rbac_state;
+        require!(
+            rbac_state.breakglass_key == Some(ctx.accounts.breakglass.key()),
+            RbacError::NotAuthorized
+        );
+        require!(
+            duration > 0 && duration <= MAX_BREAKGLASS_DURATION,
+            RbacError::InvalidBreakglassDuration
+        );
+
+        let current_time = Clock::get()?.unix_timestamp;
+        rbac_state.breakglass_expires_at = current_time + duration;
+
+        msg!("BREAK-GLASS ACTIVATED by {}", ctx.accounts.breakglass.key());
+        let event = BreakGlassActivated {
+            key: ctx.accounts.breakglass.key(),
+            admin: rbac_state.admin,
+            expires_at: rbac_state.breakglass_expires_at,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        };
+        emit_state_event(rbac_state, event);
+
+        Ok(())
+    }
+
+    /// Close a `UserRole` whose `Role` PDA no longer exists. Permissionless;
+    /// rent is refunded to whoever originally paid for the assignment.
+    pub fn close_orphan(
+        ctx: Context<CloseOrphan>,
+        user: Pubkey,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.role.data_is_empty(),
+            RbacError::RoleStillExists
+        );
+        // Revoked assignments were already uncounted and close via finalize_revocation
+        require!(!ctx.accounts.user_role.revoked, RbacError::AssignmentRevoked);
+
+        decrement(&mut ctx.accounts.rbac_state.assignment_count)?;
+
+        unindex_role(&mut ctx.accounts.user_index, &ctx.accounts.user_role.role)?;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, OrphanClosed {
+            user,
+            role: ctx.accounts.user_role.role.clone(),
+            refunded_to: ctx.accounts.assigned_by.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Close a `UserRole` whose assignment has expired. Permissionless; rent is
+    /// refunded to whoever originally paid for the assignment.
+    pub fn cleanup_expired_role(
+        ctx: Context<CleanupExpiredRole>,
+        user: Pubkey,
+        role_name: String,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.user_role.is_expired(current_time),
+            RbacError::AssignmentNotExpired
+        );
+        // Revoked assignments were already uncounted and close via finalize_revocation
+        require!(!ctx.accounts.user_role.revoked, RbacError::AssignmentRevoked);
+
+        decrement(&mut ctx.accounts.rbac_state.assignment_count)?;
+        ctx.accounts.role.release_assignment();
+
+        unindex_role(&mut ctx.accounts.user_index, &role_name)?;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, ExpiredRoleCleaned {
+            user,
+            role: role_name,
+            refunded_to: ctx.accounts.assigned_by.key(),
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Batch `cleanup_expired_role`: close up to `limit` of the `UserRole`s passed
+    /// writable in `remaining_accounts` whose assignment has expired, skipping the
+    /// rest. Each closed assignment's `Role`, the user's `UserRoleIndex` and its
+    /// original payer (who gets the rent back) go in `remaining_accounts` too.
+    /// Permissionless, so any crank can run it.
+    pub fn sweep_expired_roles<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepExpiredRoles<'info>>,
+        limit: u8,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let remaining = ctx.remaining_accounts;
+
+        let mut processed = 0u8;
+        let mut users = Vec::new();
+        let mut roles = Vec::new();
+        for info in remaining.iter() {
+            if processed == limit {
+                break;
+            }
+            // Only assignments count towards `limit`; the accounts backing them are skipped
+            if *info.owner != crate::ID || !info.try_borrow_data()?.starts_with(&UserRole::DISCRIMINATOR) {
+                continue;
+            }
+            processed += 1;
+
+            let user_role = Account::<UserRole>::try_from(info)?;
+            // Revoked assignments were already uncounted and close via finalize_revocation
+            if user_role.revoked || !user_role.is_expired(current_time) {
+                continue;
+            }
+            let role_info = find_remaining(remaining, &seeds::role_address(&user_role.role))
+                .ok_or(RbacError::RoleNotFound)?;
+            // Assignments of a deleted role close via close_orphan
+            if role_info.data_is_empty() {
+                continue;
+            }
+            let mut role = Account::<Role>::try_from(role_info)?;
+            let index_address = Pubkey::find_program_address(
+                &[seeds::USER_INDEX, user_role.user.as_ref()],
+                &crate::ID,
+            ).0;
+            let mut user_index = Account::<UserRoleIndex>::try_from(
+                find_remaining(remaining, &index_address).ok_or(RbacError::UserIndexMissing)?,
+            )?;
+            let assigned_by = find_remaining(remaining, &user_role.assigned_by)
+                .ok_or(RbacError::InvalidRemainingAccounts)?;
+
+            decrement(&mut ctx.accounts.rbac_state.assignment_count)?;
+            role.release_assignment();
+            role.exit(&crate::ID)?;
+            unindex_role(&mut user_index, &user_role.role)?;
+            user_index.exit(&crate::ID)?;
+
+            users.push(user_role.user);
+            roles.push(user_role.role.clone());
+            user_role.close(assigned_by.clone())?;
+        }
+
+        emit_state_event(&mut ctx.accounts.rbac_state, RolesSwept {
+            processed,
+            closed: users.len() as u8,
+            users,
+            roles,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Sum the lamports locked in `RbacState` plus every program-owned account
+    /// (roles, assignments, ...) passed in `remaining_accounts`, for teardown budgeting.
+    pub fn total_rent_locked<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReadState<'info>>,
+    ) -> Result<u64> {
+        let mut total = ctx.accounts.rbac_state.to_account_info().lamports();
+        for (index, account) in ctx.remaining_accounts.iter().enumerate() {
+            require_keys_eq!(*account.owner, crate::ID, RbacError::InvalidRemainingAccounts);
+            require!(
+                *account.key != ctx.accounts.rbac_state.key()
+                    && ctx.remaining_accounts[..index].iter().all(|prev| prev.key != account.key),
+                RbacError::InvalidRemainingAccounts
+            );
+            total = total
+                .checked_add(account.lamports())
+                .ok_or(RbacError::CounterOverflow)?;
+        }
+        Ok(total)
+    }
+
+    /// Report assignments (passed in `remaining_accounts`) expiring within the next
+    /// `within_secs` seconds via an `ExpiringAssignments` event. Read-only.
+    pub fn find_expiring<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReadState<'info>>,
+        within_secs: i64,
+    ) -> Result<()> {
+        require!(within_secs >= 0, RbacError::InvalidExpiry);
+        let current_time = Clock::get()?.unix_timestamp;
+        let window_end = current_time.saturating_add(within_secs);
+
+        let mut users = Vec::new();
+        let mut roles = Vec::new();
+        let mut expires_at = Vec::new();
+        for account in ctx.remaining_accounts.iter() {
+            let user_role = load_account::<UserRole>(account)?;
+            if let Some(expiry) = user_role.expires_at {
+                if expiry > current_time && expiry <= window_end {
+                    users.push(user_role.user);
+                    roles.push(user_role.role);
+                    expires_at.push(expiry);
+                }
+            }
+        }
+
+        emit!(ExpiringAssignments {
+            users,
+            roles,
+            expires_at,
+            window_end,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Report assignments (passed in `remaining_accounts`) made by `assigner` via an
+    /// `AssignmentsByAssigner` event, e.g. to audit a compromised manager. Read-only.
+    pub fn find_by_assigner<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReadState<'info>>,
+        assigner: Pubkey,
+    ) -> Result<()> {
+        let mut users = Vec::new();
+        let mut roles = Vec::new();
+        for account in ctx.remaining_accounts.iter() {
+            let user_role = load_account::<UserRole>(account)?;
+            if user_role.assigned_by == assigner {
+                users.push(user_role.user);
+                roles.push(user_role.role);
+            }
+        }
+
+        emit!(AssignmentsByAssigner {
+            assigner,
+            users,
+            roles,
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Emit an `AccessDescription` of everything `user` can do: each assignment in
+    /// `remaining_accounts` with its role's effective (composite) permissions and
+    /// validity, plus the union of active ones. Pass each assignment's `Role` and any
+    /// member roles alongside. Read-only.
+    pub fn describe_access<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReadState<'info>>,
+        user: Pubkey,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let remaining = ctx.remaining_accounts;
+
+        let mut roles = Vec::new();
+        let mut role_permissions = Vec::new();
+        let mut expires_at = Vec::new();
+        let mut active = Vec::new();
+        let mut effective_permissions = ctx.accounts.rbac_state.active_global_grant(current_time);
+        for info in remaining.iter().filter(|info| *info.owner == crate::ID) {
+            let Ok(user_role) = UserRole::try_deserialize(&mut &info.try_borrow_data()?[..]) else {
+                continue;
+            };
+            require_keys_eq!(user_role.user, user, RbacError::UserRoleMismatch);
+
+            let (role_address, _) = Pubkey::find_program_address(
+                &[seeds::ROLE, user_role.role.as_bytes()],
+                &crate::ID,
+            );
+            let role = find_remaining(remaining, &role_address)
+                .map(load_account::<Role>)
+                .transpose()?
+                .ok_or(RbacError::RoleNotFound)?;
+            let permissions = resolve_role_permissions(remaining, &role, current_time)?;
+            let is_active = !user_role.is_expired(current_time)
+                && !user_role.suspended
+                && user_role.is_acknowledged(&role);
+            if is_active {
+                effective_permissions |= permissions;
+            }
+
+            roles.push(user_role.role);
+            role_permissions.push(permissions);
+            expires_at.push(user_role.expires_at);
+            active.push(is_active);
+        }
+        if active.contains(&true) {
+            effective_permissions |= inherited_base_permissions(&ctx.accounts.rbac_state, remaining)?;
+        }
+
+        emit!(AccessDescription {
+            user,
+            roles,
+            role_permissions,
+            expires_at,
+            active,
+            effective_permissions,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Emit a `RoleGraph` of every `Role` passed in `remaining_accounts` with its
+    /// composition edges, so clients can render the role hierarchy. Read-only.
+    pub fn export_role_graph<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReadState<'info>>,
+    ) -> Result<()> {
+        let mut roles = Vec::with_capacity(ctx.remaining_accounts.len());
+        let mut composed_of = Vec::with_capacity(ctx.remaining_accounts.len());
+        for info in ctx.remaining_accounts.iter() {
+            let role = load_account::<Role>(info)?;
+            roles.push(role.name);
+            composed_of.push(role.composed_of);
+        }
+
+        emit!(RoleGraph {
+            roles,
+            composed_of,
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank: emit one `ExpiryWarning` per assignment (passed writable
+    /// in `remaining_accounts`) that has entered the configured warning window.
+    /// Each assignment is warned once until it is renewed or reassigned.
+    pub fn emit_expiry_warnings<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReadState<'info>>,
+    ) -> Result<()> {
+        let window = ctx.accounts.rbac_state.expiry_warning_window;
+        require!(window > 0, RbacError::ExpiryWarningsDisabled);
+        let current_time = Clock::get()?.unix_timestamp;
+
+        for info in ctx.remaining_accounts.iter() {
+            let mut user_role = Account::<UserRole>::try_from(info)?;
+            let Some(expiry) = user_role.expires_at else {
+                continue;
+            };
+            if user_role.warned
+                || current_time >= expiry
+                || current_time < expiry.saturating_sub(window)
+            {
+                continue;
+            }
+
+            user_role.warned = true;
+            user_role.exit(&crate::ID)?;
+            emit!(ExpiryWarning {
+                user: user_role.user,
+                role: user_role.role.clone(),
+                expires_at: expiry,
+                timestamp: current_time,
+                slot: Clock::get()?.slot,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Seconds until an assignment expires (negative once lapsed), or `NO_EXPIRY`
+    /// for permanent assignments. Read-only; intended for client countdowns.
+    pub fn time_until_expiry(
+        ctx: Context<ReadAssignment>,
+        _user: Pubkey,
+        _role_name: String,
+    ) -> Result<i64> {
+        let current_time = Clock::get()?.unix_timestamp;
+        Ok(ctx.accounts.user_role
+            .expires_at
+            .map_or(NO_EXPIRY, |expiry| expiry.saturating_sub(current_time)))
+    }
+
+    /// Recompute an assignment's fingerprint and compare it to the stored one,
+    /// emitting `TamperDetected` on mismatch. Returns whether they match. Read-only.
+    pub fn verify_assignment_integrity(
+        ctx: Context<ReadAssignment>,
+        user: Pubkey,
+        role_name: String,
+    ) -> Result<bool> {
+        let user_role = &ctx.accounts.user_role;
+        let computed = user_role.compute_fingerprint();
+        let intact = computed == user_role.fingerprint;
+        if !intact {
+            emit!(TamperDetected {
+                user,
+                role: role_name,
+                stored: user_role.fingerprint,
+                computed,
+                timestamp: Clock::get()?.unix_timestamp,
+                slot: Clock::get()?.slot,
+            });
+        }
+        Ok(intact)
+    }
+
+    /// Transfer admin rights to a new wallet
+    pub fn transfer_admin(
+        ctx: Context<TransferAdmin>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unsealed()?;
+        require!(
+            ctx.accounts.rbac_state.admin == ctx.accounts.admin.key(),
+            RbacError::NotAuthorized
+        );
+        
+        let old_admin = ctx.accounts.rbac_state.replace_master_admin(new_admin);
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AdminTransferred {
+            old_admin,
+            new_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+        
+        Ok(())
+    }
+
+    /// Propose `new_admin` as the next master admin. The handoff only takes
+    /// effect once `new_admin` signs `accept_admin`. Master admin only.
+    pub fn propose_admin(
+        ctx: Context<TransferAdmin>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unsealed()?;
+        require!(
+            ctx.accounts.rbac_state.admin == ctx.accounts.admin.key(),
+            RbacError::NotAuthorized
+        );
+
+        ctx.accounts.rbac_state.pending_admin = Some(new_admin);
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AdminTransferProposed {
+            current_admin: ctx.accounts.admin.key(),
+            pending_admin: new_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Complete a proposed handoff. Only the pending admin may accept.
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        ctx.accounts.rbac_state.require_unsealed()?;
+        let new_admin = ctx.accounts.new_admin.key();
+        require!(
+            ctx.accounts.rbac_state.pending_admin == Some(new_admin),
+            RbacError::NotAuthorized
+        );
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let rbac_state = &mut ctx.accounts.rbac_state;
+        rbac_state.pending_admin = None;
+        let old_admin = rbac_state.replace_master_admin(new_admin);
+        ctx.accounts.admin_history.record(AdminTransferRecord {
+            old_admin,
+            new_admin,
+            timestamp: current_time,
+        });
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AdminTransferAccepted {
+            old_admin,
+            new_admin,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Return up to `MAX_AUDIT_PAGE` audit entries in chronological order, starting
+    /// `start` entries after the oldest one retained. Paged because the full buffer
+    /// exceeds the return-data limit. Read-only.
+    pub fn read_audit_log(
+        ctx: Context<ReadAuditLog>,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<AuditEntry>> {
+        let limit = (limit as usize).min(MAX_AUDIT_PAGE);
+        Ok(ctx.accounts.audit_log
+            .chronological()
+            .skip(start as usize)
+            .take(limit)
+            .copied()
+            .collect())
+    }
+
+    /// Return the most recent accepted admin handoffs, oldest first. Read-only.
+    pub fn get_admin_history(ctx: Context<ReadAdminHistory>) -> Result<Vec<AdminTransferRecord>> {
+        Ok(ctx.accounts.admin_history.entries.clone())
+    }
+
+    /// Withdraw a pending handoff before it is accepted. Master admin only.
+    pub fn cancel_admin_transfer(ctx: Context<TransferAdmin>) -> Result<()> {
+        ctx.accounts.rbac_state.require_unsealed()?;
+        require!(
+            ctx.accounts.rbac_state.admin == ctx.accounts.admin.key(),
+            RbacError::NotAuthorized
+        );
+        let pending_admin = ctx.accounts.rbac_state
+            .pending_admin
+            .take()
+            .ok_or(RbacError::NoPendingAdmin)?;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AdminTransferCancelled {
+            pending_admin,
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Add a co-admin who shares root admin powers. Any current admin may call this.
+    pub fn add_admin(
+        ctx: Context<UpdateConfig>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unsealed()?;
+        let rbac_state = &mut ctx.accounts.rbac_state;
+        require!(
+            rbac_state.is_admin(&ctx.accounts.admin.key()),
+            RbacError::NotAuthorized
+        );
+        require!(!rbac_state.is_admin(&new_admin), RbacError::AlreadyAdmin);
+        require!(rbac_state.admins.len() < MAX_ADMINS, RbacError::TooManyAdmins);
+
+        rbac_state.admins.push(new_admin);
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AdminAdded {
+            admin: new_admin,
+            added_by: ctx.accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Remove an admin from the set. The last remaining admin cannot be removed;
+    /// removing the master admin promotes the longest-standing co-admin.
+    pub fn remove_admin(
+        ctx: Context<UpdateConfig>,
+        admin: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unsealed()?;
+        let rbac_state = &mut ctx.accounts.rbac_state;
+        require!(
+            rbac_state.is_admin(&ctx.accounts.admin.key()),
+            RbacError::NotAuthorized
+        );
+        let position = rbac_state.admins
+            .iter()
+            .position(|existing| *existing == admin)
+            .ok_or(RbacError::NotAnAdmin)?;
+        require!(rbac_state.admins.len() > 1, RbacError::CannotRemoveLastAdmin);
+        require!(
+            usize::from(rbac_state.admin_threshold) < rbac_state.admins.len(),
+            RbacError::InvalidThreshold
+        );
+
+        rbac_state.admins.remove(position);
+        if rbac_state.admin == admin {
+            rbac_state.admin = rbac_state.admins[0];
+        }
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AdminRemoved {
+            admin,
+            removed_by: ctx.accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Require `threshold` distinct admins to approve a `Proposal` before an
+    /// admin-level role is created or any role is deleted (0 or 1 = off). Only the
+    /// master admin may change it, and never above the size of the admin set.
+    pub fn set_admin_threshold(
+        ctx: Context<UpdateConfig>,
+        threshold: u8,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unsealed()?;
+        require_keys_eq!(
+            ctx.accounts.admin.key(),
+            ctx.accounts.rbac_state.admin,
+            RbacError::NotAuthorized
+        );
+        require!(
+            usize::from(threshold) <= ctx.accounts.rbac_state.admins.len(),
+            RbacError::InvalidThreshold
+        );
+
+        ctx.accounts.rbac_state.admin_threshold = threshold;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, AdminThresholdSet {
+            threshold,
+            set_by: ctx.accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Open a proposal for a quorum-gated action at `[b"proposal", id]`, where `id`
+    /// is the current `proposal_count`. The proposer's approval is recorded.
+    pub fn propose_action(
+        ctx: Context<ProposeAction>,
+        action: ProposalAction,
+        role_name: String,
+        permissions: u32,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        let proposer = ctx.accounts.admin.key();
+        require!(ctx.accounts.rbac_state.is_admin(&proposer), RbacError::NotAuthorized);
+        // Delete proposals only need the name; anything else is rejected up front
+        match action {
+            ProposalAction::CreateRole => {
+                validate_role_name(&role_name)?;
+                require!(permissions <= 31u32, RbacError::InvalidPermissions);
+                validate_admin_role(&role_name, permissions)?;
+            }
+            ProposalAction::DeleteRole => {
+                require!(!role_name.is_empty(), RbacError::EmptyRoleName);
+                require!(permissions == 0, RbacError::InvalidPermissions);
+            }
+        }
+
+        let id = ctx.accounts.rbac_state.proposal_count;
+        let current_time = Clock::get()?.unix_timestamp;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.id = id;
+        proposal.action = action;
+        proposal.role = role_name.clone();
+        proposal.permissions = permissions;
+        proposal.proposer = proposer;
+        proposal.approvals = vec![proposer];
+        proposal.created_at = current_time;
+        proposal.bump = ctx.bumps.proposal;
+        ctx.accounts.rbac_state.proposal_count += 1;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, ProposalCreated {
+            id,
+            action,
+            role: role_name,
+            permissions,
+            proposer,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Add the signing admin's approval to an open proposal.
+    pub fn approve_action(
+        ctx: Context<ApproveAction>,
+        proposal_id: u64,
+    ) -> Result<()> {
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        let approver = ctx.accounts.admin.key();
+        require!(ctx.accounts.rbac_state.is_admin(&approver), RbacError::NotAuthorized);
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.approvals.contains(&approver), RbacError::AlreadyApproved);
+        require!(proposal.approvals.len() < MAX_ADMINS, RbacError::TooManyAdmins);
+
+        proposal.approvals.push(approver);
+        let approvals = proposal.approvals.len() as u8;
+
+        emit_state_event(&mut ctx.accounts.rbac_state, ProposalApproved {
+            id: proposal_id,
+            approver,
+            approvals,
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Carry out a proposal once `admin_threshold` of its approvers are still
+    /// admins, then close it and refund the proposer. Any admin may execute.
+    pub fn execute_proposal(
+        ctx: Context<ExecuteProposal>,
+        proposal_id: u64,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        ctx.accounts.rbac_state.require_unfrozen()?;
+        let executor = ctx.accounts.admin.key();
+        require!(ctx.accounts.rbac_state.is_admin(&executor), RbacError::NotAuthorized);
+        let approvals = ctx.accounts.proposal.approvals
+            .iter()
+            .filter(|approver| ctx.accounts.rbac_state.is_admin(approver))
+            .count();
+        require!(
+            approvals >= usize::from(ctx.accounts.rbac_state.admin_threshold.max(1)),
+            RbacError::ApprovalsBelowThreshold
+        );
+
+        let role_name = ctx.accounts.proposal.role.clone();
+        match ctx.accounts.proposal.action {
+            ProposalAction::CreateRole => {
+                require!(ctx.accounts.role.created_at == 0, RbacError::RoleAlreadyExists);
+                let permissions = ctx.accounts.proposal.permissions;
+                ctx.accounts.rbac_state.require_assignable(&role_name, permissions)?;
+                ctx.accounts.rbac_state.require_role_capacity()?;
+                ctx.accounts.role_registry.names.push(role_name.clone());
+
+                let role = &mut ctx.accounts.role;
+                role.name = role_name.clone();
+                role.permissions = permissions;
+                role.created_at = current_time;
+                role.bump = ctx.bumps.role;
+                role.active = true;
+                role.version = Role::current_version();
+                increment(&mut ctx.accounts.rbac_state.role_count)?;
+
+                emit_state_event(&mut ctx.accounts.rbac_state, RoleCreated {
+                    name: role_name,
+                    permissions,
+                    breakglass: false,
+                    timestamp: current_time,
+                    slot: Clock::get()?.slot,
+                });
+            }
+            ProposalAction::DeleteRole => {
+                require!(ctx.accounts.role.created_at != 0, RbacError::RoleNotFound);
+                require!(ctx.accounts.role.assignment_count == 0, RbacError::RoleInUse);
+                ctx.accounts.role_registry.names.retain(|name| *name != role_name);
+                decrement(&mut ctx.accounts.rbac_state.role_count)?;
+                ctx.accounts.role.close(ctx.accounts.admin.to_account_info())?;
+
+                emit_state_event(&mut ctx.accounts.rbac_state, RoleDeleted {
+                    name: role_name,
+                    breakglass: false,
+                    timestamp: current_time,
+                    slot: Clock::get()?.slot,
+                });
+            }
+        }
+
+        emit_state_event(&mut ctx.accounts.rbac_state, ProposalExecuted {
+            id: proposal_id,
+            action: ctx.accounts.proposal.action,
+            executed_by: executor,
+            approvals: approvals as u8,
+            timestamp: current_time,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade a stale `RbacState`, `Role` or `UserRole` to the current layout:
+    /// reallocate it, default the fields added since its version and stamp the
+    /// current one. Master admin only. Allowed while sealed since only the layout
+    /// changes, never a configured value.
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        // The state itself may be the stale account, so read the admin raw; it is
+        // the first field and has never moved
+        let admin = {
+            let data = ctx.accounts.rbac_state.try_borrow_data()?;
+            require!(data.len() >= 8 + 32, RbacError::UnknownAccountLayout);
+            Pubkey::try_from(&data[8..8 + 32]).unwrap()
+        };
+        require_keys_eq!(ctx.accounts.admin.key(), admin, RbacError::NotAuthorized);
+
+        let target = ctx.accounts.target.to_account_info();
+        let (from_version, to_version, data) = {
+            let data = target.try_borrow_data()?;
+            let discriminator = data.get(..8).ok_or(RbacError::UnknownAccountLayout)?;
+            if discriminator == RbacState::DISCRIMINATOR {
+                migrated_data::<RbacState>(&data)?
+            } else if discriminator == Role::DISCRIMINATOR {
+                migrated_data::<Role>(&data)?
+            } else if discriminator == UserRole::DISCRIMINATOR {
+                migrated_data::<UserRole>(&data)?
+            } else {
+                return err!(RbacError::UnknownAccountLayout);
+            }
+        };
+
+        let shortfall = Rent::get()?.minimum_balance(data.len()).saturating_sub(target.lamports());
+        if shortfall > 0 {
+            transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.admin.to_account_info(),
+                        to: target.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        target.realloc(data.len(), false)?;
+        target.try_borrow_mut_data()?.copy_from_slice(&data);
+
+        emit!(AccountMigrated {
+            account: target.key(),
+            from_version,
+            to_version,
+            migrated_by: ctx.accounts.admin.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+            slot: Clock::get()?.slot,
+        });
+
+        Ok(())
+    }
+}
+
+/// ============ INSTRUCTIONS ============
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    // init_if_needed so a second call fails with AlreadyInitialized below rather
+    // than the system program's opaque "account already in use"
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + RbacState::INIT_SPACE,
+        seeds = [seeds::RBAC_STATE],
+        bump,
+        constraint = rbac_state.admin == Pubkey::default() @ RbacError::AlreadyInitialized,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Role::INIT_SPACE,
+        seeds = [seeds::ROLE, ADMIN_ROLE_NAME.as_bytes()],
+        bump
+    )]
+    pub admin_role: Box<Account<'info, Role>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = RoleRegistry::space(&[ADMIN_ROLE_NAME.to_string()]),
+        seeds = [seeds::ROLE_REGISTRY],
+        bump
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AdminHistory::INIT_SPACE,
+        seeds = [seeds::ADMIN_HISTORY],
+        bump
+    )]
+    pub admin_history: Account<'info, AdminHistory>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + AuditLog::INIT_SPACE,
+        seeds = [seeds::AUDIT_LOG],
+        bump
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRbacState<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+        close = admin,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE_REGISTRY],
+        bump = role_registry.bump,
+        close = admin,
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ADMIN_HISTORY],
+        bump = admin_history.bump,
+        close = admin,
+    )]
+    pub admin_history: Account<'info, AdminHistory>,
+
+    #[account(
+        mut,
+        seeds = [seeds::AUDIT_LOG],
+        bump = audit_log.bump,
+        close = admin,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String, permissions: u32, description: String)]
+pub struct CreateRole<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+    
+    // Seeded through `role_name_seed` so an oversized name fails with
+    // `RoleNameTooLong` instead of aborting PDA derivation
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Role::INIT_SPACE,
+        seeds = [seeds::ROLE, seeds::role_name_seed(&role_name)?],
+        bump
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE_REGISTRY],
+        bump = role_registry.bump,
+        realloc = role_registry.space_with(&role_name),
+        realloc::payer = admin,
+        realloc::zero = false,
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String)]
+pub struct DeleteRole<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+        close = admin,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE_REGISTRY],
+        bump = role_registry.bump,
+        realloc = role_registry.space_without(&role_name),
+        realloc::payer = admin,
+        realloc::zero = false,
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(old_name: String, new_name: String)]
+pub struct RenameRole<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, old_name.as_bytes()],
+        bump = old_role.bump,
+    )]
+    pub old_role: Box<Account<'info, Role>>,
+
+    /// CHECK: The system admin's UserRole PDA for the old role; it may not exist.
+    #[account(
+        seeds = [seeds::USER_ROLE, rbac_state.admin.as_ref(), old_name.as_bytes()],
+        bump,
+    )]
+    pub admin_assignment: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Role::INIT_SPACE,
+        seeds = [seeds::ROLE, seeds::role_name_seed(&new_name)?],
+        bump
+    )]
+    pub new_role: Box<Account<'info, Role>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE_REGISTRY],
+        bump = role_registry.bump,
+        realloc = role_registry.space_without(&old_name) + 4 + new_name.len(),
+        realloc::payer = admin,
+        realloc::zero = false,
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, old_name: String, new_name: String)]
+pub struct RemapUserRole<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, old_name.as_bytes()],
+        bump = old_role.bump,
+    )]
+    pub old_role: Box<Account<'info, Role>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, new_name.as_bytes()],
+        bump = new_role.bump,
+    )]
+    pub new_role: Box<Account<'info, Role>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_ROLE, user.as_ref(), old_name.as_bytes()],
+        bump = old_user_role.bump,
+    )]
+    pub old_user_role: Box<Account<'info, UserRole>>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + UserRole::INIT_SPACE,
+        seeds = [seeds::USER_ROLE, user.as_ref(), seeds::role_name_seed(&new_name)?],
+        bump
+    )]
+    pub new_user_role: Box<Account<'info, UserRole>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_INDEX, user.as_ref()],
+        bump = user_index.bump,
+    )]
+    pub user_index: Box<Account<'info, UserRoleIndex>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreatePermissionGroup<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + PermissionGroup::INIT_SPACE,
+        seeds = [seeds::PERM_GROUP, name.as_bytes()],
+        bump
+    )]
+    pub group: Account<'info, PermissionGroup>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct UpdatePermissionGroup<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::PERM_GROUP, name.as_bytes()],
+        bump = group.bump,
+    )]
+    pub group: Account<'info, PermissionGroup>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String)]
+pub struct UpdateRole<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String)]
+pub struct SetRoleActive<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    /// CHECK: The system admin's UserRole PDA for this role; it may not exist.
+    #[account(
+        seeds = [seeds::USER_ROLE, rbac_state.admin.as_ref(), role_name.as_bytes()],
+        bump,
+    )]
+    pub admin_assignment: UncheckedAccount<'info>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String)]
+pub struct StageRoleUpdate<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + PendingRoleUpdate::INIT_SPACE,
+        seeds = [seeds::PENDING_ROLE_UPDATE, role_name.as_bytes()],
+        bump
+    )]
+    pub pending: Account<'info, PendingRoleUpdate>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String)]
+pub struct ReadRole<'info> {
+    #[account(
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String)]
+pub struct PreviewRoleUpdate<'info> {
+    #[account(
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        seeds = [seeds::PENDING_ROLE_UPDATE, role_name.as_bytes()],
+        bump = pending.bump,
+    )]
+    pub pending: Account<'info, PendingRoleUpdate>,
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String)]
+pub struct CheckPendingUpdate<'info> {
+    #[account(
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    /// CHECK: Staging PDA for the role; may be uninitialized.
+    #[account(
+        seeds = [seeds::PENDING_ROLE_UPDATE, role_name.as_bytes()],
+        bump,
+    )]
+    pub pending: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String)]
+pub struct ResolveRoleUpdate<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        mut,
+        seeds = [seeds::PENDING_ROLE_UPDATE, role_name.as_bytes()],
+        bump = pending.bump,
+        close = staged_by,
+    )]
+    pub pending: Account<'info, PendingRoleUpdate>,
+
+    /// CHECK: Admin who staged the update, receives the refunded rent.
+    #[account(
+        mut,
+        address = pending.staged_by,
+    )]
+    pub staged_by: UncheckedAccount<'info>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, role_name: String)]
+pub struct AssignRole<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+    
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + UserRole::INIT_SPACE,
+        seeds = [seeds::USER_ROLE, user.as_ref(), role_name.as_bytes()],
+        bump
+    )]
+    pub user_role: Account<'info, UserRole>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = UserRoleIndex::space(&[]),
+        seeds = [seeds::USER_INDEX, user.as_ref()],
+        bump
+    )]
+    pub user_index: Box<Account<'info, UserRoleIndex>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::AUDIT_LOG],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AssignRolesBatch<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::AUDIT_LOG],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, resource: Pubkey, role_name: String)]
+pub struct AssignRoleScoped<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + UserRole::INIT_SPACE,
+        seeds = [seeds::USER_ROLE, user.as_ref(), resource.as_ref(), role_name.as_bytes()],
+        bump
+    )]
+    pub user_role: Account<'info, UserRole>,
+
+    #[account(
+        mut,
+        seeds = [seeds::AUDIT_LOG],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(resource: Pubkey, role_name: String)]
+pub struct RevokeRoleScoped<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_ROLE, user_role.user.as_ref(), resource.as_ref(), role_name.as_bytes()],
+        bump = user_role.bump,
+        close = authority,
+    )]
+    pub user_role: Account<'info, UserRole>,
+
+    #[account(
+        mut,
+        seeds = [seeds::AUDIT_LOG],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String)]
+pub struct CreateRoleClaim<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + RoleClaim::INIT_SPACE,
+        seeds = [seeds::ROLE_CLAIM, role_name.as_bytes()],
+        bump
+    )]
+    pub claim: Account<'info, RoleClaim>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String)]
+pub struct ClaimRoleMerkle<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        seeds = [seeds::ROLE_CLAIM, role_name.as_bytes()],
+        bump = claim.bump,
+    )]
+    pub claim: Account<'info, RoleClaim>,
+
+    #[account(
+        init,
+        payer = claimant,
+        space = 8 + UserRole::INIT_SPACE,
+        seeds = [seeds::USER_ROLE, claimant.key().as_ref(), role_name.as_bytes()],
+        bump
+    )]
+    pub user_role: Account<'info, UserRole>,
+
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        space = UserRoleIndex::space(&[]),
+        seeds = [seeds::USER_INDEX, claimant.key().as_ref()],
+        bump
+    )]
+    pub user_index: Box<Account<'info, UserRoleIndex>>,
+
+    #[account(
+        init_if_needed,
+        payer = claimant,
+        space = 8 + ClaimReceipt::INIT_SPACE,
+        seeds = [seeds::CLAIM_RECEIPT, claimant.key().as_ref(), role_name.as_bytes()],
+        bump
+    )]
+    pub claim_receipt: Box<Account<'info, ClaimReceipt>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::AUDIT_LOG],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct HasPermission<'info> {
+    pub role: Account<'info, Role>,
+    pub user_role: Account<'info, UserRole>,
+
+    /// CHECK: `user`'s UserStatus PDA; deserialized only if it exists.
+    #[account(
+        seeds = [seeds::USER_STATUS, user.as_ref()],
+        bump,
+    )]
+    pub user_status: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, role_name: String)]
+pub struct CheckUserPermission<'info> {
+    // Absent when the role was never created (or has been deleted)
+    #[account(
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Option<Account<'info, Role>>,
+
+    /// CHECK: `user`'s UserRole PDA for `role_name`; deserialized only if it exists.
+    #[account(
+        seeds = [seeds::USER_ROLE, user.as_ref(), role_name.as_bytes()],
+        bump,
+    )]
+    pub user_role: UncheckedAccount<'info>,
+
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    /// CHECK: `user`'s UserStatus PDA; deserialized only if it exists.
+    #[account(
+        seeds = [seeds::USER_STATUS, user.as_ref()],
+        bump,
+    )]
+    pub user_status: UncheckedAccount<'info>,
+}
+impl<'info> CheckUserPermission<'info> {
+    /// `user` with their assignment loaded, if it exists.
+    fn subject(&self, user: Pubkey) -> Result<UserSubject<'_, 'info>> {
+        let user_role = if self.user_role.data_is_empty() {
+            None
+        } else {
+            Some(load_account::<UserRole>(&self.user_role)?)
+        };
+        Ok(UserSubject { accounts: self, user, user_role })
+    }
+}
+
+/// `CheckUserPermission`'s view for `evaluate`.
+struct UserSubject<'a, 'info> {
+    accounts: &'a CheckUserPermission<'info>,
+    user: Pubkey,
+    user_role: Option<UserRole>,
+}
+
+#[derive(Accounts)]
+pub struct CheckPermission<'info> {
+    // Optional so the system admin can be checked without an assignment;
+    // PDA derivation is verified in `assignment()`.
+    pub role: Option<Account<'info, Role>>,
+    
+    pub user_role: Option<Account<'info, UserRole>>,
+
+    pub caller: Signer<'info>,
+
+    #[account(
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    /// CHECK: the subject's UserStatus PDA, required so a freeze cannot be skipped
+    /// by leaving it out. The subject is only known at runtime, so the address is
+    /// verified in `is_frozen()`; deserialized only if it exists.
+    pub user_status: UncheckedAccount<'info>,
+}
+impl<'info> CheckPermission<'info> {
+    /// The user being checked: the assignment holder, or the caller when no
+    /// assignment was passed.
+    pub fn subject(&self) -> Pubkey {
+        self.user_role
+            .as_ref()
+            .map_or(self.caller.key(), |user_role| user_role.user)
+    }
+
+    /// Whether the subject's `UserStatus` freezes them. A status account
+    /// belonging to anyone else is rejected rather than ignored.
+    pub fn is_frozen(&self) -> Result<bool> {
+        require_keys_eq!(
+            self.user_status.key(),
+            seeds::user_status_address(&self.subject()),
+            RbacError::UserStatusMismatch
+        );
+        user_status_frozen(&self.user_status)
+    }
+
+    /// The passed `(UserRole, Role)` pair, verified to be canonical PDAs that
+    /// reference each other.
+    pub fn assignment(&self) -> Result<(&UserRole, &Role)> {
+        let (Some(user_role), Some(role)) = (&self.user_role, &self.role) else {
+            return err!(RbacError::MissingAssignment);
+        };
+        verify_assignment(&user_role.key(), user_role, &role.key(), role)?;
+        Ok((user_role, role))
+    }
+
+    /// Like `assignment`, but also accepts an assignment scoped to `resource`.
+    pub fn scoped_assignment(&self, resource: &Pubkey) -> Result<(&UserRole, &Role)> {
+        match &self.user_role {
+            Some(user_role) if user_role.resource != GLOBAL_RESOURCE => {
+                let Some(role) = &self.role else {
+                    return err!(RbacError::MissingAssignment);
+                };
+                require_keys_eq!(user_role.resource, *resource, RbacError::UserRoleMismatch);
+                let expected_user_role = Pubkey::create_program_address(
+                    &[
+                        seeds::USER_ROLE,
+                        user_role.user.as_ref(),
+                        resource.as_ref(),
+                        user_role.role.as_bytes(),
+                        &[user_role.bump],
+                    ],
+                    &crate::ID,
+                )
+                .map_err(|_| RbacError::UserRoleMismatch)?;
+                require_keys_eq!(user_role.key(), expected_user_role, RbacError::UserRoleMismatch);
+                let expected_role = Pubkey::create_program_address(
+                    &[seeds::ROLE, role.name.as_bytes(), &[role.bump]],
+                    &crate::ID,
+                )
+                .map_err(|_| RbacError::RoleNotFound)?;
+                require_keys_eq!(role.key(), expected_role, RbacError::RoleNotFound);
+                require!(user_role.role == role.name, RbacError::UserRoleMismatch);
+                Ok((user_role, role))
+            }
+            _ => self.assignment(),
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String)]
+pub struct SnapshotPermissions<'info> {
+    #[account(
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PermissionSnapshot::INIT_SPACE,
+        seeds = [seeds::PERM_SNAPSHOT, role_name.as_bytes()],
+        bump
+    )]
+    pub snapshot: Account<'info, PermissionSnapshot>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, old_role_name: String, new_role_name: String)]
+pub struct ReassignRole<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, old_role_name.as_bytes()],
+        bump = old_role.bump,
+    )]
+    pub old_role: Account<'info, Role>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, new_role_name.as_bytes()],
+        bump = new_role.bump,
+    )]
+    pub new_role: Account<'info, Role>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_ROLE, user.as_ref(), old_role_name.as_bytes()],
+        bump = old_user_role.bump,
+        close = authority,
+    )]
+    pub old_user_role: Account<'info, UserRole>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + UserRole::INIT_SPACE,
+        seeds = [seeds::USER_ROLE, user.as_ref(), new_role_name.as_bytes()],
+        bump
+    )]
+    pub new_user_role: Account<'info, UserRole>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = UserRoleIndex::space(&[]),
+        seeds = [seeds::USER_INDEX, user.as_ref()],
+        bump
+    )]
+    pub user_index: Box<Account<'info, UserRoleIndex>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String)]
+pub struct RevokeRole<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_ROLE, user_role.user.as_ref(), role_name.as_bytes()],
+        bump = user_role.bump,
+    )]
+    pub user_role: Account<'info, UserRole>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_INDEX, user_role.user.as_ref()],
+        bump = user_index.bump,
+    )]
+    pub user_index: Box<Account<'info, UserRoleIndex>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::AUDIT_LOG],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(to_user: Pubkey, role_name: String)]
+pub struct DelegatePermission<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        seeds = [seeds::USER_ROLE, delegator.key().as_ref(), role_name.as_bytes()],
+        bump = delegator_role.bump,
+    )]
+    pub delegator_role: Account<'info, UserRole>,
+
+    #[account(
+        init,
+        payer = delegator,
+        space = 8 + DelegationToken::INIT_SPACE,
+        seeds = [seeds::DELEGATION, delegator.key().as_ref(), to_user.as_ref(), role_name.as_bytes()],
+        bump
+    )]
+    pub token: Account<'info, DelegationToken>,
+
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(to_user: Pubkey, role_name: String)]
+pub struct RevokeDelegation<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::DELEGATION, delegator.key().as_ref(), to_user.as_ref(), role_name.as_bytes()],
+        bump = token.bump,
+        close = delegator,
+    )]
+    pub token: Account<'info, DelegationToken>,
+
+    #[account(mut)]
+    pub delegator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, role_name: String)]
+pub struct UpdateAssignment<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_ROLE, user.as_ref(), role_name.as_bytes()],
+        bump = user_role.bump,
+    )]
+    pub user_role: Account<'info, UserRole>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct SetUserStatus<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + UserStatus::INIT_SPACE,
+        seeds = [seeds::USER_STATUS, user.as_ref()],
+        bump
+    )]
+    pub user_status: Account<'info, UserStatus>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(role_name: String)]
+pub struct AcknowledgeRole<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_ROLE, user.key().as_ref(), role_name.as_bytes()],
+        bump = user_role.bump,
+    )]
+    pub user_role: Account<'info, UserRole>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, role_name: String)]
+pub struct ReadAssignment<'info> {
+    #[account(
+        seeds = [seeds::USER_ROLE, user.as_ref(), role_name.as_bytes()],
+        bump = user_role.bump,
+    )]
+    pub user_role: Account<'info, UserRole>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, role_name: String)]
+pub struct SlashAssigner<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_ROLE, user.as_ref(), role_name.as_bytes()],
+        bump = user_role.bump,
+    )]
+    pub user_role: Account<'info, UserRole>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, role_name: String)]
+pub struct ProcessRenewal<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_ROLE, user.as_ref(), role_name.as_bytes()],
+        bump = user_role.bump,
+    )]
+    pub user_role: Account<'info, UserRole>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct CloseOrphan<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    /// CHECK: Canonical Role PDA for the assignment; must be uninitialized.
+    #[account(
+        seeds = [seeds::ROLE, user_role.role.as_bytes()],
+        bump,
+    )]
+    pub role: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_ROLE, user.as_ref(), user_role.role.as_bytes()],
+        bump = user_role.bump,
+        close = assigned_by,
+    )]
+    pub user_role: Account<'info, UserRole>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_INDEX, user.as_ref()],
+        bump = user_index.bump,
+    )]
+    pub user_index: Box<Account<'info, UserRoleIndex>>,
+
+    /// CHECK: Original payer of the assignment, receives the refunded rent.
+    #[account(
+        mut,
+        address = user_role.assigned_by,
+    )]
+    pub assigned_by: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, role_name: String)]
+pub struct FinalizeRevocation<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_ROLE, user.as_ref(), role_name.as_bytes()],
+        bump = user_role.bump,
+        close = assigned_by,
+    )]
+    pub user_role: Account<'info, UserRole>,
+
+    /// CHECK: Original payer of the assignment, receives the refunded rent.
+    #[account(
+        mut,
+        address = user_role.assigned_by,
+    )]
+    pub assigned_by: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, role_name: String)]
+pub struct CleanupExpiredRole<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_ROLE, user.as_ref(), role_name.as_bytes()],
+        bump = user_role.bump,
+        close = assigned_by,
+    )]
+    pub user_role: Account<'info, UserRole>,
+
+    #[account(
+        mut,
+        seeds = [seeds::USER_INDEX, user.as_ref()],
+        bump = user_index.bump,
+    )]
+    pub user_index: Box<Account<'info, UserRoleIndex>>,
+
+    /// CHECK: Original payer of the assignment, receives the refunded rent.
+    #[account(
+        mut,
+        address = user_role.assigned_by,
+    )]
+    pub assigned_by: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SweepExpiredRoles<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+}
+
+#[derive(Accounts)]
+pub struct TransferAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ADMIN_HISTORY],
+        bump = admin_history.bump,
+    )]
+    pub admin_history: Account<'info, AdminHistory>,
+
+    pub new_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReadAuditLog<'info> {
+    #[account(
+        seeds = [seeds::AUDIT_LOG],
+        bump = audit_log.bump,
+    )]
+    pub audit_log: Box<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+pub struct ReadAdminHistory<'info> {
+    #[account(
+        seeds = [seeds::ADMIN_HISTORY],
+        bump = admin_history.bump,
+    )]
+    pub admin_history: Account<'info, AdminHistory>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteActionCpi<'info> {
+    pub check: CheckPermission<'info>,
+
+    /// CHECK: data-less PDA whose signature marks a forwarded instruction as authorized
+    #[account(seeds = [seeds::GATEWAY], bump)]
+    pub gateway: UncheckedAccount<'info>,
+
+    /// CHECK: checked against `RbacState::cpi_targets` in the handler
+    #[account(executable)]
+    pub target_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAction<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Proposal::INIT_SPACE,
+        seeds = [seeds::PROPOSAL, rbac_state.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
 
-#[derive(Accounts)]
-#[instruction(role_name: String, permissions: u32)]
-pub struct CreateRole<'info> {
-    #[account(
-        mut,
-        seeds = [b"rbac_state"],
-        bump = rbac_state.bump,
-    )]
-    pub rbac_state: Account<'info, RbacState>,
-    
-    #[account(
-        init,
-        payer = admin,
-        space = 8 + Role::INIT_SPACE,
-        seeds = [b"role", role_name.as_bytes()],
-        bump
-    )]
-    pub role: Account<'info, Role>,
-    
     #[account(mut)]
     pub admin: Signer<'info>,
-    
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ApproveAction<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::PROPOSAL, proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(proposal_id: u64)]
+pub struct ExecuteProposal<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::PROPOSAL, proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        close = proposer,
+    )]
+    pub proposal: Account<'info, Proposal>,
+
+    /// CHECK: Admin who opened the proposal, receives the refunded rent.
+    #[account(
+        mut,
+        address = proposal.proposer,
+    )]
+    pub proposer: UncheckedAccount<'info>,
+
+    /// Created for a create proposal; must already exist for a delete proposal.
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + Role::INIT_SPACE,
+        seeds = [seeds::ROLE, proposal.role.as_bytes()],
+        bump
+    )]
+    pub role: Box<Account<'info, Role>>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE_REGISTRY],
+        bump = role_registry.bump,
+        realloc = proposal.registry_space(&role_registry),
+        realloc::payer = admin,
+        realloc::zero = false,
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: Read raw, as it may itself be the stale account being migrated.
+    #[account(
+        seeds = [seeds::RBAC_STATE],
+        bump,
+    )]
+    pub rbac_state: UncheckedAccount<'info>,
+
+    /// CHECK: Program-owned account to migrate; its discriminator selects the layout.
+    #[account(
+        mut,
+        owner = crate::ID,
+    )]
+    pub target: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(admin_key: Pubkey)]
+pub struct SetOrgAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + OrgAdmin::INIT_SPACE,
+        seeds = [seeds::ORG_ADMIN, admin_key.as_ref()],
+        bump
+    )]
+    pub org_admin: Account<'info, OrgAdmin>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(manager: Pubkey, role_name: String)]
+pub struct GrantAssigner<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        seeds = [seeds::ROLE, role_name.as_bytes()],
+        bump = role.bump,
+    )]
+    pub role: Account<'info, Role>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + RoleManager::INIT_SPACE,
+        seeds = [seeds::ROLE_MANAGER, manager.as_ref(), role_name.as_bytes()],
+        bump
+    )]
+    pub role_manager: Account<'info, RoleManager>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
-#[derive(Accounts)]
-#[instruction(user: Pubkey, role_name: String)]
-pub struct AssignRole<'info> {
-    #[account(
-        mut,
-        seeds = [b"rbac_state"],
-        bump = rbac_state.bump,
-    )]
-    pub rbac_state: Account<'info, RbacState>,
-    
-    #[account(
-        seeds = [b"role", role_name.as_bytes()],
-        bump = role.bump,
-    )]
-    pub role: Account<'info, Role>,
-    
-    #[account(
-        init_if_needed,
-        payer = authority,
-        space = 8 + UserRole::INIT_SPACE,
-        seeds = [b"user_role", user.as_ref(), role_name.as_bytes()],
-        bump
-    )]
-    pub user_role: Account<'info, UserRole>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    
-    pub system_program: Program<'info, System>,
+#[derive(Accounts)]
+#[instruction(manager: Pubkey, role_name: String)]
+pub struct RevokeAssigner<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+
+    #[account(
+        mut,
+        seeds = [seeds::ROLE_MANAGER, manager.as_ref(), role_name.as_bytes()],
+        bump = role_manager.bump,
+        close = admin,
+    )]
+    pub role_manager: Account<'info, RoleManager>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RepairStateBump<'info> {
+    // Deliberately re-derives the canonical bump instead of trusting the stored one
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+    
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InvokeBreakglass<'info> {
+    #[account(
+        mut,
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+    
+    pub breakglass: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReadState<'info> {
+    #[account(
+        seeds = [seeds::RBAC_STATE],
+        bump = rbac_state.bump,
+    )]
+    pub rbac_state: Account<'info, RbacState>,
+}
+
+#[derive(Accounts)]
+pub struct ReadRegistry<'info> {
+    #[account(
+        seeds = [seeds::ROLE_REGISTRY],
+        bump = role_registry.bump,
+    )]
+    pub role_registry: Account<'info, RoleRegistry>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct ReadUserIndex<'info> {
+    #[account(
+        seeds = [seeds::USER_INDEX, user.as_ref()],
+        bump = user_index.bump,
+    )]
+    pub user_index: Account<'info, UserRoleIndex>,
+}
+
+/// ============ CONSTANTS ============
+/// Permission bits (mirrors the client-side bitmask)
+pub const PERM_READ: u32 = 1 << 0;
+pub const PERM_CREATE: u32 = 1 << 1;
+pub const PERM_UPDATE: u32 = 1 << 2;
+pub const PERM_DELETE: u32 = 1 << 3;
+pub const PERM_ADMIN: u32 = 1 << 4;
+/// Role exempt from the system-wide assignable-permission allowlist
+pub const ADMIN_ROLE_NAME: &str = "admin";
+/// Role granting every permission bit, current and future, e.g. for service accounts
+pub const WILDCARD_ROLE_NAME: &str = "*";
+/// Permissions whose assignment counts as privileged for compliance purposes
+pub const PRIVILEGED_PERMISSIONS: u32 = PERM_ADMIN | PERM_DELETE;
+
+/// Anchor discriminator of the hook program's `on_role_assigned` instruction,
+/// i.e. `sha256("global:on_role_assigned")[..8]`
+pub const ON_ROLE_ASSIGNED_DISCRIMINATOR: [u8; 8] = [0x10, 0x8f, 0x03, 0x97, 0xdf, 0xc0, 0x6c, 0x5f];
+
+/// `UserRole::resource` of global (unscoped) assignments
+pub const GLOBAL_RESOURCE: Pubkey = Pubkey::new_from_array([0; 32]);
+
+/// Sentinel returned by `time_until_expiry` for assignments that never expire
+pub const NO_EXPIRY: i64 = i64::MAX;
+
+/// Maximum length in bytes (not characters) of a role name: the capacity of
+/// `Role::name` and the longest single PDA seed Solana accepts
+pub const MAX_ROLE_NAME_LEN: usize = 32;
+/// Maximum length in bytes of an assignment's jurisdiction tag
+pub const MAX_JURISDICTION_LEN: usize = 8;
+/// Maximum length in bytes of an assignment justification note
+pub const MAX_NOTE_LEN: usize = 64;
+/// Maximum length in bytes of a role description
+pub const MAX_DESCRIPTION_LEN: usize = 128;
+/// Longest window a break-glass activation may last (24 hours)
+pub const MAX_BREAKGLASS_DURATION: i64 = 24 * 60 * 60;
+/// Maximum number of co-signers that can be allowlisted on an assignment
+pub const MAX_SIGNER_ALLOWLIST: usize = 4;
+/// Maximum number of member roles a composite role may reference
+pub const MAX_COMPOSED_ROLES: usize = 4;
+/// Maximum number of roles an `assert_role_quorum` call may list
+pub const MAX_QUORUM_ROLES: usize = 8;
+/// Maximum number of conditional grants a role may carry
+pub const MAX_CONDITIONAL_GRANTS: usize = 4;
+/// Maximum number of ancestors walked when resolving a role's parent chain
+pub const MAX_HIERARCHY_DEPTH: usize = 5;
+/// Maximum number of roles `resolve_effective_permissions` visits, the root included
+pub const MAX_RESOLVED_ROLES: usize = 32;
+/// Maximum number of assignments a bulk instruction may touch in one call
+pub const MAX_BULK_ASSIGNMENTS: usize = 10;
+/// Maximum number of assignments `assign_roles_batch` creates in one call; each
+/// creates two accounts, which bounds both compute and transaction size
+pub const MAX_BATCH_ASSIGNMENTS: usize = 5;
+/// Maximum number of bit sets `batch_check_permissions` evaluates in one call
+pub const MAX_BATCH_PERMISSIONS: usize = 16;
+/// Maximum number of role-name prefixes an org admin may be scoped to
+pub const MAX_ORG_ADMIN_PREFIXES: usize = 4;
+/// Default `max_delegation_depth`: managers granted by the root admin, no sub-delegates
+pub const DEFAULT_MAX_DELEGATION_DEPTH: u8 = 1;
+/// Maximum number of programs `execute_action_cpi` may forward to
+pub const MAX_CPI_TARGETS: usize = 4;
+/// Maximum number of admins (master admin included) in `RbacState::admins`
+pub const MAX_ADMINS: usize = 8;
+/// Maximum number of role names `get_roles` returns per page
+pub const MAX_ROLES_PAGE: usize = 16;
+/// Number of accepted admin handoffs kept in `AdminHistory`
+pub const MAX_ADMIN_HISTORY: usize = 8;
+/// Number of entries the on-chain `AuditLog` ring buffer retains
+pub const AUDIT_LOG_CAPACITY: usize = 64;
+/// Maximum number of audit entries `read_audit_log` returns per page
+pub const MAX_AUDIT_PAGE: usize = 12;
+/// `AuditEntry::action_kind` values
+pub const AUDIT_ROLE_ASSIGNED: u8 = 0;
+pub const AUDIT_ROLE_REVOKED: u8 = 1;
+
+/// ============ HELPERS ============
+/// Emit a state-change event, or — in replication mode — wrap it in a single
+/// sequenced `StateDelta` so cross-chain mirrors ingest one canonical stream.
+fn emit_state_event<E: anchor_lang::Event + TextLog>(rbac_state: &mut RbacState, event: E) {
+    if rbac_state.text_logs {
+        rbac_state.text_log_seq = rbac_state.text_log_seq.wrapping_add(1);
+        msg!("{}", text_log_line(&event, rbac_state.text_log_seq));
+    }
+    if rbac_state.replication_mode {
+        rbac_state.replication_seq = rbac_state.replication_seq.wrapping_add(1);
+        emit!(StateDelta {
+            seq: rbac_state.replication_seq,
+            data: event.data(),
+            slot: Clock::get().map_or(0, |clock| clock.slot),
+        });
+    } else {
+        emit!(event);
+    }
+}
+
+/// Authorize management of `role_name`. The root admin and an active break-glass key
+/// are unrestricted; otherwise the signer's `OrgAdmin` PDA must be passed in
+/// `remaining_accounts` and one of its prefixes must match the role name.
+fn authorize_role_admin(
+    rbac_state: &RbacState,
+    remaining: &[AccountInfo],
+    signer: &Pubkey,
+    role_name: &str,
+    now: i64,
+) -> Result<bool> {
+    rbac_state.require_unsealed()?;
+    if let Ok(breakglass) = rbac_state.authorize_admin(signer, now) {
+        return Ok(breakglass);
+    }
+    let (org_admin_address, _) = Pubkey::find_program_address(
+        &[seeds::ORG_ADMIN, signer.as_ref()],
+        &crate::ID,
+    );
+    let org_admin = find_remaining(remaining, &org_admin_address)
+        .map(load_account::<OrgAdmin>)
+        .transpose()?
+        .ok_or(RbacError::NotAuthorized)?;
+    require!(org_admin.in_scope(role_name), RbacError::OutsideAdminScope);
+    Ok(false)
+}
+
+/// Authorize an assignment of `role_name`: anyone `authorize_role_admin` accepts,
+/// or a manager of that role whose `RoleManager` PDA is in `remaining_accounts`.
+/// Returns the break-glass flag and the signer's delegation depth (0 for admins).
+fn authorize_assigner(
+    rbac_state: &RbacState,
+    remaining: &[AccountInfo],
+    signer: &Pubkey,
+    role_name: &str,
+    now: i64,
+) -> Result<(bool, u8)> {
+    let admin_check = authorize_role_admin(rbac_state, remaining, signer, role_name, now);
+    if let Ok(breakglass) = admin_check {
+        return Ok((breakglass, 0));
+    }
+    match role_manager_of(remaining, signer, role_name)? {
+        Some(manager) => Ok((false, manager.depth)),
+        None => admin_check.map(|breakglass| (breakglass, 0)),
+    }
+}
+
+/// `signer`'s `RoleManager` for `role_name`, if passed in `remaining_accounts`.
+fn role_manager_of(
+    remaining: &[AccountInfo],
+    signer: &Pubkey,
+    role_name: &str,
+) -> Result<Option<RoleManager>> {
+    let (manager_address, _) = Pubkey::find_program_address(
+        &[seeds::ROLE_MANAGER, signer.as_ref(), role_name.as_bytes()],
+        &crate::ID,
+    );
+    // A revoked (closed) manager PDA no longer belongs to the program
+    match find_remaining(remaining, &manager_address) {
+        Some(info) if *info.owner == crate::ID => load_account::<RoleManager>(info).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Escalation guard for delegated flows: callers other than the root admin (or an
+/// active break-glass key) may only grant permission bits they hold themselves,
+/// through live global assignments passed in `remaining_accounts` with their roles.
+/// Bits denied on an assignment do not count towards what it holds.
+fn require_can_grant(
+    rbac_state: &RbacState,
+    remaining: &[AccountInfo],
+    signer: &Pubkey,
+    granted: u32,
+    now: i64,
+) -> Result<()> {
+    if granted == 0 || rbac_state.authorize_admin(signer, now).is_ok() {
+        return Ok(());
+    }
+    let mut held = 0u32;
+    for info in remaining.iter().filter(|info| *info.owner == crate::ID) {
+        let Ok(user_role) = UserRole::try_deserialize(&mut &info.try_borrow_data()?[..]) else {
+            continue;
+        };
+        if user_role.user != *signer
+            || user_role.resource != GLOBAL_RESOURCE
+            || user_role.revoked
+            || user_role.is_expired(now)
+            || user_role.suspended
+        {
+            continue;
+        }
+        let (user_role_address, _) = Pubkey::find_program_address(
+            &[seeds::USER_ROLE, signer.as_ref(), user_role.role.as_bytes()],
+            &crate::ID,
+        );
+        if *info.key != user_role_address {
+            continue;
+        }
+        let (role_address, _) = Pubkey::find_program_address(
+            &[seeds::ROLE, user_role.role.as_bytes()],
+            &crate::ID,
+        );
+        if let Some(role_info) = find_remaining(remaining, &role_address) {
+            let role = load_account::<Role>(role_info)?;
+            if role.is_live(now) && user_role.is_acknowledged(&role) {
+                held |= resolve_role_permissions(remaining, &role, now)? & !user_role.denied_permissions;
+            }
+        }
+    }
+    require!(granted & !held == 0, RbacError::CannotGrantUnheldPermission);
+    Ok(())
+}
+
+/// Deserialize a `(UserRole, Role)` pair passed through `remaining_accounts`,
+/// verifying both are canonical PDAs of this program and reference each other.
+fn load_assignment(
+    user_role_info: &AccountInfo,
+    role_info: &AccountInfo,
+) -> Result<(UserRole, Role)> {
+    let user_role = load_account::<UserRole>(user_role_info)?;
+    let role = load_account::<Role>(role_info)?;
+    verify_assignment(user_role_info.key, &user_role, role_info.key, &role)?;
+    Ok((user_role, role))
+}
+
+/// Verify that `user_role` and `role` live at their canonical PDAs and that the
+/// assignment references the role.
+fn verify_assignment(
+    user_role_key: &Pubkey,
+    user_role: &UserRole,
+    role_key: &Pubkey,
+    role: &Role,
+) -> Result<()> {
+    let expected_user_role = Pubkey::create_program_address(
+        &[seeds::USER_ROLE, user_role.user.as_ref(), user_role.role.as_bytes(), &[user_role.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| RbacError::UserRoleMismatch)?;
+    require_keys_eq!(*user_role_key, expected_user_role, RbacError::UserRoleMismatch);
+
+    let expected_role = Pubkey::create_program_address(
+        &[seeds::ROLE, role.name.as_bytes(), &[role.bump]],
+        &crate::ID,
+    )
+    .map_err(|_| RbacError::RoleNotFound)?;
+    require_keys_eq!(*role_key, expected_role, RbacError::RoleNotFound);
+
+    require!(user_role.role == role.name, RbacError::UserRoleMismatch);
+
+    Ok(())
+}
+
+/// Compliance policy: while `require_note_for_privileged` is on, assigning a role
+/// with a privileged permission must carry a non-empty justification.
+fn require_privileged_note(rbac_state: &RbacState, role: &Role, note: Option<&str>) -> Result<()> {
+    if rbac_state.require_note_for_privileged && role.permissions & PRIVILEGED_PERMISSIONS != 0 {
+        require!(note.is_some_and(|n| !n.is_empty()), RbacError::NoteRequired);
+    }
+    Ok(())
+}
+
+/// Accountability bond: the assigner escrows `stake` lamports in the new assignment.
+fn escrow_assigner_stake<'info>(
+    system_program: &Program<'info, System>,
+    authority: &Signer<'info>,
+    user_role: AccountInfo<'info>,
+    stake: u64,
+) -> Result<()> {
+    if stake > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: authority.to_account_info(),
+                    to: user_role,
+                },
+            ),
+            stake,
+        )?;
+    }
+    Ok(())
+}
+
+/// Return the assigner's bond held by `user_role`, yielding the staker and amount.
+/// A staker other than the revoker must be passed writable in `remaining_accounts`;
+/// when the account is closed to the revoker right away, its own bond comes back
+/// with the rent.
+fn return_stake(
+    user_role: &mut Account<UserRole>,
+    authority: &Signer,
+    remaining: &[AccountInfo],
+    close_now: bool,
+) -> Result<Option<(Pubkey, u64)>> {
+    let stake = user_role.stake;
+    if stake == 0 {
+        return Ok(None);
+    }
+    let staker = user_role.staked_by;
+    if staker == authority.key() {
+        if !close_now {
+            user_role.sub_lamports(stake)?;
+            authority.add_lamports(stake)?;
+        }
+    } else {
+        let staker_info = find_remaining(remaining, &staker)
+            .filter(|info| info.is_writable)
+            .ok_or(RbacError::StakeRecipientMissing)?;
+        user_role.sub_lamports(stake)?;
+        staker_info.add_lamports(stake)?;
+    }
+    user_role.stake = 0;
+    Ok(Some((staker, stake)))
+}
+
+/// Identity gate: when `role` requires an attribute, the user's record must be in
+/// `remaining_accounts` and meet the role's minimum.
+fn require_attribute(remaining: &[AccountInfo], role: &Role, user: &Pubkey) -> Result<()> {
+    if let Some(attribute_program) = role.attribute_program {
+        let attribute_value = remaining
+            .iter()
+            .find_map(|account| read_attribute(account, &attribute_program, user))
+            .ok_or(RbacError::AttributeRequirementNotMet)?;
+        require!(
+            attribute_value >= role.min_attribute_value,
+            RbacError::AttributeRequirementNotMet
+        );
+    }
+    Ok(())
+}
+
+/// Add `role` to a user's index if absent, growing the account and topping up
+/// its rent from `payer`.
+fn index_role<'info>(
+    index: &mut Account<'info, UserRoleIndex>,
+    role: &str,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if index.roles.iter().any(|indexed| indexed == role) {
+        return Ok(());
+    }
+    index.roles.push(role.to_string());
+    let space = UserRoleIndex::space(&index.roles);
+    let info = index.to_account_info();
+    let shortfall = Rent::get()?.minimum_balance(space).saturating_sub(info.lamports());
+    if shortfall > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: payer.to_account_info(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.realloc(space, false)?;
+    Ok(())
+}
+
+/// Drop `role` from a user's index and shrink the account. The freed rent stays
+/// in the account to cover later growth, so no payer is needed.
+fn unindex_role(index: &mut Account<UserRoleIndex>, role: &str) -> Result<()> {
+    index.roles.retain(|indexed| indexed != role);
+    index.to_account_info().realloc(UserRoleIndex::space(&index.roles), false)?;
+    Ok(())
+}
+
+/// Accounts whose layout evolves by appending fields. `LAYOUT_DELTAS[i]` is the
+/// byte size of the fields version `i + 1` appended, so a stale account's
+/// version follows from its length alone. Adding fields to one of these accounts
+/// means appending their size here and defaulting them in `upgrade`.
+trait Versioned: AccountSerialize + AccountDeserialize + Discriminator + Space {
+    const LAYOUT_DELTAS: &'static [usize];
+
+    fn current_version() -> u8 {
+        Self::LAYOUT_DELTAS.len() as u8
+    }
+
+    /// Account size (discriminator included) of layout `version`.
+    fn space_of(version: u8) -> usize {
+        8 + Self::INIT_SPACE - Self::LAYOUT_DELTAS[usize::from(version)..].iter().sum::<usize>()
+    }
+
+    /// Default every field appended after `from_version` and stamp the current version.
+    fn upgrade(&mut self, from_version: u8);
+}
+
+impl Versioned for RbacState {
+    // 1: version; 2: cpi_targets; 3: max_delegation_depth
+    const LAYOUT_DELTAS: &'static [usize] = &[1, 4 + 32 * MAX_CPI_TARGETS, 1];
+
+    fn upgrade(&mut self, from_version: u8) {
+        if from_version < 2 {
+            self.cpi_targets = Vec::new();
+        }
+        if from_version < 3 {
+            self.max_delegation_depth = DEFAULT_MAX_DELEGATION_DEPTH;
+        }
+        self.version = Self::current_version();
+    }
+}
+
+impl Versioned for Role {
+    // 1: version; 2: permission_group; 3: renamed_to; 4: max_assignments;
+    // 5: valid_from, valid_until; 6: disabled_at
+    const LAYOUT_DELTAS: &'static [usize] = &[1, 1 + 4 + 32, 1 + 4 + 32, 1 + 4, 2 * (1 + 8), 8];
+
+    fn upgrade(&mut self, from_version: u8) {
+        if from_version < 2 {
+            self.permission_group = None;
+        }
+        if from_version < 3 {
+            self.renamed_to = None;
+        }
+        if from_version < 4 {
+            self.max_assignments = None;
+        }
+        if from_version < 5 {
+            self.valid_from = None;
+            self.valid_until = None;
+        }
+        if from_version < 6 {
+            self.disabled_at = 0;
+        }
+        self.version = Self::current_version();
+    }
+}
+
+impl Versioned for UserRole {
+    // 1: version; 2: temp_permission, temp_expires_at; 3: delegation_depth
+    const LAYOUT_DELTAS: &'static [usize] = &[1, 1 + 4 + 8, 1];
+
+    fn upgrade(&mut self, from_version: u8) {
+        if from_version < 2 {
+            self.temp_permission = None;
+            self.temp_expires_at = 0;
+        }
+        if from_version < 3 {
+            self.delegation_depth = 0;
+        }
+        self.version = Self::current_version();
+    }
+}
+
+/// Rewrite `data`, a stale `T` account, in the current layout. Returns the old
+/// and new versions and the new account data, zero-padded to the current size.
+fn migrated_data<T: Versioned>(data: &[u8]) -> Result<(u8, u8, Vec<u8>)> {
+    let to_version = T::current_version();
+    let from_version = (0..=to_version)
+        .find(|&version| T::space_of(version) == data.len())
+        .ok_or(RbacError::UnknownAccountLayout)?;
+    require!(from_version < to_version, RbacError::AccountUpToDate);
+
+    // Appended fields read as zeroes (None, empty, false) before being defaulted
+    let mut padded = data.to_vec();
+    padded.resize(T::space_of(to_version), 0);
+    let mut account = T::try_deserialize(&mut &padded[..])?;
+    account.upgrade(from_version);
+
+    let mut migrated = vec![0; padded.len()];
+    account.try_serialize(&mut &mut migrated[..])?;
+    Ok((from_version, to_version, migrated))
+}
+
+/// Token gate: when `role` requires a mint, the user's associated token account
+/// for it must be in `remaining_accounts` holding at least `min_amount`.
+fn require_token_gate(remaining: &[AccountInfo], role: &Role, user: &Pubkey) -> Result<()> {
+    let Some(required_mint) = role.required_mint else {
+        return Ok(());
+    };
+    let token_address = get_associated_token_address(user, &required_mint);
+    let token_info = find_remaining(remaining, &token_address).ok_or(RbacError::TokenGateNotMet)?;
+    require_keys_eq!(*token_info.owner, token::ID, RbacError::TokenGateNotMet);
+    let token_account = TokenAccount::try_deserialize(&mut &token_info.try_borrow_data()?[..])?;
+    require!(
+        token_account.mint == required_mint
+            && token_account.owner == *user
+            && token_account.amount >= role.min_amount,
+        RbacError::TokenGateNotMet
+    );
+    Ok(())
+}
+
+/// Prerequisite gate: when `role` requires a co-role, the user's active assignment
+/// of it must be in `remaining_accounts`.
+fn require_co_role(remaining: &[AccountInfo], role: &Role, user: &Pubkey, now: i64) -> Result<()> {
+    let Some(requires_role) = &role.requires_role else {
+        return Ok(());
+    };
+    let (co_role_address, _) = Pubkey::find_program_address(
+        &[seeds::USER_ROLE, user.as_ref(), requires_role.as_bytes()],
+        &crate::ID,
+    );
+    let co_role = find_remaining(remaining, &co_role_address)
+        .map(load_account::<UserRole>)
+        .transpose()?
+        .ok_or(RbacError::MissingCoRole)?;
+    require!(
+        !co_role.revoked && !co_role.is_expired(now) && !co_role.suspended,
+        RbacError::MissingCoRole
+    );
+    Ok(())
+}
+
+/// Compliance hook: CPI the configured program's `on_role_assigned`, which can
+/// veto the assignment by returning an error. No-op when no hook is set.
+fn invoke_assignment_hook<'info>(
+    rbac_state: &Account<'info, RbacState>,
+    remaining: &[AccountInfo<'info>],
+    args: OnRoleAssignedArgs,
+) -> Result<()> {
+    let Some(hook_program) = rbac_state.hook_program else {
+        return Ok(());
+    };
+    let hook_info = find_remaining(remaining, &hook_program)
+        .ok_or(RbacError::HookProgramMissing)?;
+    let mut data = ON_ROLE_ASSIGNED_DISCRIMINATOR.to_vec();
+    args.serialize(&mut data)?;
+    let instruction = Instruction {
+        program_id: hook_program,
+        accounts: vec![AccountMeta::new_readonly(rbac_state.key(), false)],
+        data,
+    };
+    invoke(&instruction, &[rbac_state.to_account_info(), hook_info.clone()])?;
+    Ok(())
+}
+
+/// Merkle leaf for a role claim: `sha256(0x00 || user || role_name || borsh(expires_at))`.
+/// The prefix byte keeps leaves distinct from interior nodes.
+fn claim_leaf(user: &Pubkey, role_name: &str, expires_at: Option<i64>) -> Result<[u8; 32]> {
+    Ok(hashv(&[&[0u8], user.as_ref(), role_name.as_bytes(), &expires_at.try_to_vec()?]).to_bytes())
+}
+
+/// Verify a sorted-pair Merkle proof, interior nodes being
+/// `sha256(0x01 || min(a, b) || max(a, b))`.
+fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        let (left, right) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+        hashv(&[&[1u8], &left, &right]).to_bytes()
+    });
+    computed == *root
+}
+
+/// Read a user's attribute value from an identity-program account laid out as
+/// `[8-byte discriminator][user: Pubkey][value: u64 LE]`. Returns `None` when the
+/// account is not owned by `attribute_program` or belongs to another user.
+fn read_attribute(info: &AccountInfo, attribute_program: &Pubkey, user: &Pubkey) -> Option<u64> {
+    if info.owner != attribute_program {
+        return None;
+    }
+    let data = info.try_borrow_data().ok()?;
+    let record = data.get(8..48)?;
+    if record[..32] != user.to_bytes() {
+        return None;
+    }
+    Some(u64::from_le_bytes(record[32..40].try_into().ok()?))
+}
+
+/// Deserialize a program-owned account passed through `remaining_accounts`.
+fn load_account<T: AccountDeserialize>(info: &AccountInfo) -> Result<T> {
+    require_keys_eq!(*info.owner, crate::ID, RbacError::InvalidRemainingAccounts);
+    T::try_deserialize(&mut &info.try_borrow_data()?[..])
+}
+
+/// Whether the (address-verified) `UserStatus` PDA `info` freezes its user;
+/// `false` when no status was ever set.
+fn user_status_frozen(info: &AccountInfo) -> Result<bool> {
+    if info.data_is_empty() {
+        return Ok(false);
+    }
+    Ok(load_account::<UserStatus>(info)?.frozen)
+}
+
+/// Key=value rendering of a state event for `RbacState::text_logs`. Fields default
+/// to none, leaving just the event name and sequence number.
+trait TextLog {
+    fn text_fields(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+}
+
+macro_rules! impl_text_log {
+    ($($event:ty),* $(,)?) => {
+        $(impl TextLog for $event {})*
+    };
+}
+
+impl_text_log!(
+    AdminAdded, AdminRemoved, AdminTransferAccepted, AdminTransferCancelled,
+    AdminTransferProposed, AdminTransferred, AssignablePermissionsSet, AssignerGranted,
+    AssignerRevoked, AssignerSlashed, AssignerStakeSet, AutoRenewChanged, BaseRoleSet,
+    BreakGlassActivated, BreakGlassRegistered, BulkSuspended, BulkUnsuspended,
+    ConfigFrozenSet, DelegationRevoked, ExpiredRoleCleaned, ExpiryWarningWindowSet,
+    GlobalGrantSet, GuardianSet, HookProgramSet, MaxDisbursementSet, MaxRolesSet,
+    NotePolicySet, OrgAdminSet, OrphanClosed, PermissionDelegated, ReplicationModeSet,
+    RequiredBaseRoleSet, RequiredCoRoleSet, RevocationFinalized, RevocationGraceSet,
+    RoleAcknowledged, RoleAcknowledgmentSet, RoleAttributeRequirementSet, RoleClaimCreated,
+    RoleCompositionSet, RoleParentSet, RolePermissionsRevoked, RolePermissionsUpdated,
+    RoleReassigned, RoleRenewed, RoleUpdateCommitted, RoleUpdateDiscarded,
+    RoleUpdateStaged, SignerAllowlistSet, StakeReturned, StateBumpRepaired, SystemPaused,
+    SystemUnpaused, TextLogsSet, ChangeCooldownSet, ConditionalGrantsSet, ScopedRoleAssigned,
+    ScopedRoleRevoked, ConfigSealedForever, RoleActiveChanged, AdminThresholdSet,
+    ProposalCreated, ProposalApproved, ProposalExecuted, DeniedPermissionsChanged,
+    PermissionGroupCreated, PermissionGroupUpdated, UserFrozen, UserUnfrozen,
+    PermissionElevated, CpiTargetsSet, RoleRenamed, UserRoleRemapped, RoleCapSet,
+    MaxDelegationDepthSet, RoleValiditySet, RoleEmergencyDisabled, RoleReenabled,
+    RolesSwept
+);
+
+impl TextLog for RoleCreated {
+    fn text_fields(&self) -> Vec<(&'static str, String)> {
+        vec![("role", self.name.clone()), ("permissions", self.permissions.to_string())]
+    }
+}
+
+impl TextLog for RoleDeleted {
+    fn text_fields(&self) -> Vec<(&'static str, String)> {
+        vec![("role", self.name.clone())]
+    }
+}
+
+impl TextLog for RoleAssigned {
+    fn text_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("user", self.user.to_string()),
+            ("role", self.role.clone()),
+            ("by", self.assigned_by.to_string()),
+        ]
+    }
+}
+
+impl TextLog for RoleRevoked {
+    fn text_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("user", self.user.to_string()),
+            ("role", self.role_revoked.clone()),
+            ("by", self.revoked_by.to_string()),
+        ]
+    }
+}
+
+/// Render `evt=<snake_case event name> <fields> seq=<seq>`. The format is stable:
+/// fields only ever get appended so simple scrapers keep working.
+fn text_log_line<E: TextLog>(event: &E, seq: u64) -> String {
+    let type_name = std::any::type_name::<E>();
+    let name = type_name.rsplit("::").next().unwrap_or(type_name);
+    let mut line = String::from("evt=");
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                line.push('_');
+            }
+            line.push(c.to_ascii_lowercase());
+        } else {
+            line.push(c);
+        }
+    }
+    for (key, value) in event.text_fields() {
+        line.push_str(&format!(" {}={}", key, value));
+    }
+    line.push_str(&format!(" seq={}", seq));
+    line
+}
+
+/// Reject role names outside the canonical form clients derive role PDAs from:
+/// 1 to `MAX_ROLE_NAME_LEN` bytes, no surrounding whitespace and no uppercase letters. Rejecting
+/// rather than normalizing keeps the seed identical to the name the caller sent.
+fn validate_role_name(role_name: &str) -> Result<()> {
+    require!(!role_name.is_empty(), RbacError::EmptyRoleName);
+    require!(role_name.len() <= MAX_ROLE_NAME_LEN, RbacError::RoleNameTooLong);
+    require!(
+        role_name.trim() == role_name && !role_name.chars().any(char::is_uppercase),
+        RbacError::NonCanonicalRoleName
+    );
+    Ok(())
+}
+
+/// A role validity window must be non-empty when both ends are set.
+fn validate_role_window(valid_from: Option<i64>, valid_until: Option<i64>) -> Result<()> {
+    if let (Some(from), Some(until)) = (valid_from, valid_until) {
+        require!(from < until, RbacError::InvalidRoleWindow);
+    }
+    Ok(())
+}
+
+/// The reserved admin role must actually carry `PERM_ADMIN`, otherwise whoever is
+/// handed "admin" can't administer anything.
+fn validate_admin_role(role_name: &str, permissions: u32) -> Result<()> {
+    require!(
+        role_name != ADMIN_ROLE_NAME || permissions & PERM_ADMIN != 0,
+        RbacError::InvalidAdminRole
+    );
+    Ok(())
+}
+
+/// Whether `info` is an initialized `UserRole` that hasn't expired at `now`.
+fn holds_live_assignment(info: &AccountInfo, now: i64) -> Result<bool> {
+    if info.data_is_empty() {
+        return Ok(false);
+    }
+    Ok(!load_account::<UserRole>(info)?.is_expired(now))
+}
+
+/// The wildcard role grants everything, so only a root admin (or an active
+/// break-glass key) may create or hand it out, never an org admin or manager.
+fn require_wildcard_authority(
+    rbac_state: &RbacState,
+    signer: &Pubkey,
+    role_name: &str,
+    now: i64,
+) -> Result<()> {
+    if role_name == WILDCARD_ROLE_NAME {
+        rbac_state.authorize_admin(signer, now)?;
+    }
+    Ok(())
+}
+
+/// Bump a role or assignment counter, failing with `CounterOverflow` rather
+/// than wrapping (or panicking, with overflow checks on).
+fn increment(counter: &mut u32) -> Result<()> {
+    *counter = counter.checked_add(1).ok_or(RbacError::CounterOverflow)?;
+    Ok(())
+}
+
+/// Drop a role or assignment counter, failing with `CountUnderflow` at zero.
+fn decrement(counter: &mut u32) -> Result<()> {
+    *counter = counter.checked_sub(1).ok_or(RbacError::CountUnderflow)?;
+    Ok(())
+}
+
+/// Locate an account by address among `remaining_accounts`. Optional accounts are
+/// matched by address rather than position so independent features can share the slice.
+fn find_remaining<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    address: &Pubkey,
+) -> Option<&'a AccountInfo<'info>> {
+    accounts.iter().find(|account| account.key == address)
+}
+
+/// The accounts a permission decision is made over, implemented by both check
+/// contexts so they share one `evaluate`.
+trait PermissionSubject {
+    fn rbac_state(&self) -> &RbacState;
+    /// The signer of the check, which counts towards a signer allowlist.
+    fn caller(&self) -> Pubkey;
+    /// The user being checked.
+    fn subject(&self) -> Pubkey;
+    fn is_frozen(&self) -> Result<bool>;
+    /// The subject's assignment as passed, unverified.
+    fn user_role(&self) -> Option<&UserRole>;
+    /// The verified assignment covering `resource`, or `None` when there is none.
+    fn assignment_for(&self, resource: &Pubkey) -> Result<Option<(&UserRole, &Role)>>;
+}
+
+impl PermissionSubject for CheckPermission<'_> {
+    fn rbac_state(&self) -> &RbacState {
+        &self.rbac_state
+    }
+
+    fn caller(&self) -> Pubkey {
+        self.caller.key()
+    }
+
+    fn subject(&self) -> Pubkey {
+        CheckPermission::subject(self)
+    }
+
+    fn is_frozen(&self) -> Result<bool> {
+        CheckPermission::is_frozen(self)
+    }
+
+    fn user_role(&self) -> Option<&UserRole> {
+        self.user_role.as_deref()
+    }
+
+    fn assignment_for(&self, resource: &Pubkey) -> Result<Option<(&UserRole, &Role)>> {
+        if self.user_role.is_none() && self.role.is_none() {
+            return Ok(None);
+        }
+        self.scoped_assignment(resource).map(Some)
+    }
+}
+
+impl PermissionSubject for UserSubject<'_, '_> {
+    fn rbac_state(&self) -> &RbacState {
+        &self.accounts.rbac_state
+    }
+
+    fn caller(&self) -> Pubkey {
+        self.accounts.caller.key()
+    }
+
+    fn subject(&self) -> Pubkey {
+        self.user
+    }
+
+    fn is_frozen(&self) -> Result<bool> {
+        user_status_frozen(&self.accounts.user_status)
+    }
+
+    fn user_role(&self) -> Option<&UserRole> {
+        self.user_role.as_ref()
+    }
+
+    // Both PDAs are pinned by seeds, so an assignment of a deleted role is just absent
+    fn assignment_for(&self, _resource: &Pubkey) -> Result<Option<(&UserRole, &Role)>> {
+        Ok(self.user_role.as_ref().zip(self.accounts.role.as_deref()))
+    }
+}
+
+/// The single authoritative permission decision shared by every check entrypoint.
+/// Layers apply in this order; the first that decides wins:
+/// 1. a frozen subject (see `freeze_user`) is denied;
+/// 2. the system admin is always allowed;
+/// 3. a valid delegation token covering `required` allows;
+/// 4. the subject's own assignment is then required (denied if absent), and one
+///    scoped to a resource other than `resource` is denied, as is any assignment
+///    of a disabled role or of one outside its validity window;
+/// 5. an expired assignment is denied;
+/// 6. a suspended assignment is denied;
+/// 7. an assignment of a role requiring acknowledgment is denied until acknowledged;
+/// 8. the role's effective bits (snapshot, composition, base role, global grant,
+///    unexpired elevation) must cover `required`;
+/// 9. the required base role, if configured, must be held;
+/// 10. an allowlisted assignment must be co-signed.
+///
+/// Built with the `compute-log` feature, the remaining compute units are logged
+/// on either side of the decision so its cost shows up in transaction logs.
+fn evaluate(
+    accounts: &impl PermissionSubject,
+    remaining: &[AccountInfo],
+    required: u32,
+    resource: &Pubkey,
+    now: i64,
+) -> Result<Evaluation> {
+    log_compute_units();
+    let evaluation = evaluate_layers(accounts, remaining, required, resource, now);
+    log_compute_units();
+    evaluation
+}
+
+/// Logs the remaining compute units; a no-op without the `compute-log` feature.
+#[inline(always)]
+fn log_compute_units() {
+    #[cfg(feature = "compute-log")]
+    anchor_lang::solana_program::log::sol_log_compute_units();
+}
+
+/// The layers of `evaluate`, in order.
+fn evaluate_layers(
+    accounts: &impl PermissionSubject,
+    remaining: &[AccountInfo],
+    required: u32,
+    resource: &Pubkey,
+    now: i64,
+) -> Result<Evaluation> {
+    if accounts.is_frozen()? {
+        return Ok(Evaluation::decide(EvaluationReason::UserFrozen));
+    }
+    let rbac_state = accounts.rbac_state();
+    let subject = accounts.subject();
+    if rbac_state.is_admin(&subject) {
+        return Ok(Evaluation::decide(EvaluationReason::SystemAdmin));
+    }
+    if delegated_grant(remaining, &subject, required, now)? {
+        return Ok(Evaluation::decide(EvaluationReason::Delegated));
+    }
+
+    if accounts.user_role().is_some_and(|user_role| !user_role.covers(resource)) {
+        return Ok(Evaluation::decide(EvaluationReason::OutOfScope));
+    }
+    let Some((user_role, role)) = accounts.assignment_for(resource)? else {
+        return Ok(Evaluation::decide(EvaluationReason::NoAssignment));
+    };
+    if !role.is_enabled() {
+        return Ok(Evaluation::decide(EvaluationReason::RoleDisabled));
+    }
+    if role.valid_from.is_some_and(|from| now < from) {
+        return Ok(Evaluation::decide(EvaluationReason::RoleNotYetValid));
+    }
+    if role.valid_until.is_some_and(|until| now >= until) {
+        return Ok(Evaluation::decide(EvaluationReason::RoleExpired));
+    }
+    if user_role.revoked {
+        return Ok(Evaluation::decide(EvaluationReason::Revoked));
+    }
+    if user_role.is_expired(now) {
+        return Ok(Evaluation::decide(EvaluationReason::Expired));
+    }
+    if user_role.suspended {
+        return Ok(Evaluation::decide(EvaluationReason::Suspended));
+    }
+    if !user_role.is_acknowledged(role) {
+        return Ok(Evaluation::decide(EvaluationReason::NotAcknowledged));
+    }
+
+    let effective_permissions = resolve_role_permissions(remaining, role, now)?
+        | role.met_conditional_grants(rbac_state.assignment_count)
+        | inherited_base_permissions(rbac_state, remaining)?
+        | rbac_state.active_global_grant(now)
+        | user_role.active_temp_permission(now);
+    if required & user_role.denied_permissions != 0 {
+        return Ok(Evaluation::decide(EvaluationReason::ExplicitlyDenied));
+    }
+    if !user_role.allows(effective_permissions, required) {
+        return Ok(Evaluation::decide(EvaluationReason::InsufficientPermissions));
+    }
+
+    // Deployments may require a baseline role on top of the action's permission
+    if let Some(base_role) = &rbac_state.required_base_role {
+        if user_role.role != *base_role {
+            let base_user_role = find_remaining(
+                remaining,
+                &seeds::user_role_address(&user_role.user, base_role),
+            )
+            .map(load_account::<UserRole>)
+            .transpose()?;
+            let base_role_account = find_remaining(remaining, &seeds::role_address(base_role))
+                .map(load_account::<Role>)
+                .transpose()?;
+            // The baseline must be as live as the primary assignment
+            let holds_base = match (&base_user_role, &base_role_account) {
+                (Some(base), Some(base_role)) => {
+                    base_role.is_live(now)
+                        && !base.revoked
+                        && !base.is_expired(now)
+                        && !base.suspended
+                        && base.is_acknowledged(base_role)
+                }
+                _ => false,
+            };
+            if !holds_base {
+                return Ok(Evaluation::decide(EvaluationReason::MissingBaseRole));
+            }
+        }
+    }
+
+    // Allowlisted assignments must also be co-signed by one of the listed keys
+    if !user_role.signer_allowlist.is_empty() {
+        let co_signed = user_role.signer_allowlist.contains(&accounts.caller())
+            || remaining.iter().any(|account| {
+                account.is_signer && user_role.signer_allowlist.contains(account.key)
+            });
+        if !co_signed {
+            return Ok(Evaluation::decide(EvaluationReason::SignerNotAllowed));
+        }
+    }
+
+    Ok(Evaluation::decide(EvaluationReason::Allowed))
+}
+
+/// Turn a denial into its error, first emitting `PermissionDenied` so observers of
+/// the failed transaction can see what was missing without refetching accounts.
+fn require_allowed(
+    accounts: &CheckPermission,
+    evaluation: &Evaluation,
+    required: u32,
+    now: i64,
+) -> Result<()> {
+    let Some(error) = evaluation.reason.denial_error() else {
+        return Ok(());
+    };
+    let role = accounts.role.as_ref();
+    emit!(PermissionDenied {
+        user: accounts.subject(),
+        role: role.map(|role| role.name.clone()),
+        required_permission: required,
+        role_permissions: role.map_or(0, |role| role.permissions),
+        reason: evaluation.reason.label().to_string(),
+        timestamp: now,
+        slot: Clock::get()?.slot,
+    });
+    Err(error.into())
+}
+
+/// Set `suspended` on every `UserRole` of `role_name` in `remaining_accounts`,
+/// skipping any `OrgAdmin` scope account. Returns the affected users.
+fn set_holders_suspended<'info>(
+    remaining: &'info [AccountInfo<'info>],
+    role_name: &str,
+    suspended: bool,
+) -> Result<Vec<Pubkey>> {
+    let holders: Vec<_> = remaining
+        .iter()
+        .filter(|info| load_account::<OrgAdmin>(info).is_err())
+        .collect();
+    require!(
+        holders.len() <= MAX_BULK_ASSIGNMENTS,
+        RbacError::TooManyAssignments
+    );
+
+    let mut users = Vec::with_capacity(holders.len());
+    for info in holders {
+        let mut user_role = Account::<UserRole>::try_from(info)?;
+        let expected = Pubkey::create_program_address(
+            &[seeds::USER_ROLE, user_role.user.as_ref(), role_name.as_bytes(), &[user_role.bump]],
+            &crate::ID,
+        )
+        .map_err(|_| RbacError::UserRoleMismatch)?;
+        require_keys_eq!(*info.key, expected, RbacError::UserRoleMismatch);
+
+        user_role.suspended = suspended;
+        user_role.refresh_fingerprint();
+        user_role.exit(&crate::ID)?;
+        users.push(user_role.user);
+    }
+    Ok(users)
+}
+
+/// Whether a `DelegationToken` in `remaining_accounts` grants `subject` the `required`
+/// bits. Each token is re-validated against the delegator's live assignment and
+/// role, whose `UserRole` and `Role` PDAs must be passed alongside it, and against
+/// the delegator's `UserStatus`: a token whose delegator is frozen, or whose status
+/// account is missing, grants nothing.
+fn delegated_grant(remaining: &[AccountInfo], subject: &Pubkey, required: u32, now: i64) -> Result<bool> {
+    for info in remaining.iter().filter(|info| *info.owner == crate::ID) {
+        let Ok(token) = DelegationToken::try_deserialize(&mut &info.try_borrow_data()?[..]) else {
+            continue;
+        };
+        if token.delegate != *subject
+            || now >= token.expires_at
+            || token.permissions & required != required
+        {
+            continue;
+        }
+
+        let (user_role_address, _) = Pubkey::find_program_address(
+            &[seeds::USER_ROLE, token.delegator.as_ref(), token.role.as_bytes()],
+            &crate::ID,
+        );
+        let (role_address, _) = Pubkey::find_program_address(
+            &[seeds::ROLE, token.role.as_bytes()],
+            &crate::ID,
+        );
+        let (Some(user_role_info), Some(role_info), Some(status_info)) = (
+            find_remaining(remaining, &user_role_address),
+            find_remaining(remaining, &role_address),
+            find_remaining(remaining, &seeds::user_status_address(&token.delegator)),
+        ) else {
+            continue;
+        };
+        if user_status_frozen(status_info)? {
+            continue;
+        }
+        let delegator_role = load_account::<UserRole>(user_role_info)?;
+        let role = load_account::<Role>(role_info)?;
+        if role.is_live(now)
+            && !delegator_role.revoked
+            && !delegator_role.is_expired(now)
+            && !delegator_role.suspended
+            && delegator_role.is_acknowledged(&role)
+            && delegator_role.allows(resolve_role_permissions(remaining, &role, now)?, required)
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Effective permissions of `role`: its own bits (or a same-slot snapshot of them)
+/// unioned with those of its permission group and any roles it is composed of,
+/// read from `remaining_accounts`. Members disabled or outside their validity
+/// window at `now` contribute nothing. The wildcard role resolves to every bit.
+fn resolve_role_permissions(remaining: &[AccountInfo], role: &Role, now: i64) -> Result<u32> {
+    if role.is_wildcard() {
+        return Ok(u32::MAX);
+    }
+    let mut permissions = snapshot_or_live_permissions(remaining, role)?;
+    permissions |= ancestor_permissions(remaining, role, now)?;
+    if let Some(group_name) = &role.permission_group {
+        let (group_address, _) = Pubkey::find_program_address(
+            &[seeds::PERM_GROUP, group_name.as_bytes()],
+            &crate::ID,
+        );
+        if let Some(info) = find_remaining(remaining, &group_address) {
+            permissions |= load_account::<PermissionGroup>(info)?.permissions;
+        }
+    }
+    for member_name in &role.composed_of {
+        let (member_address, _) = Pubkey::find_program_address(
+            &[seeds::ROLE, member_name.as_bytes()],
+            &crate::ID,
+        );
+        if let Some(info) = find_remaining(remaining, &member_address) {
+            let member = load_account::<Role>(info)?;
+            if member.is_live(now) {
+                permissions |= member.permissions;
+            }
+        }
+    }
+    Ok(permissions)
+}
+
+/// Whether a role's effective `permissions` cover `required`. The Admin bit
+/// implies every other permission.
+fn grants(permissions: u32, required: u32) -> bool {
+    permissions & PERM_ADMIN != 0 || permissions & required == required
+}
+
+/// Union of the permissions of `role`'s parent chain, read from `remaining_accounts`.
+/// The walk stops at the first ancestor not supplied or not live at `now`, and
+/// fails beyond `MAX_HIERARCHY_DEPTH`.
+fn ancestor_permissions(remaining: &[AccountInfo], role: &Role, now: i64) -> Result<u32> {
+    let mut permissions = 0;
+    let mut next = role.parent.clone();
+    let mut depth = 0;
+    while let Some(parent_name) = next {
+        depth += 1;
+        require!(depth <= MAX_HIERARCHY_DEPTH, RbacError::HierarchyTooDeep);
+        let (parent_address, _) = Pubkey::find_program_address(
+            &[seeds::ROLE, parent_name.as_bytes()],
+            &crate::ID,
+        );
+        let Some(info) = find_remaining(remaining, &parent_address) else {
+            break;
+        };
+        let parent = load_account::<Role>(info)?;
+        // A disabled ancestor cuts off everything inherited through it
+        if !parent.is_live(now) {
+            break;
+        }
+        permissions |= parent.permissions;
+        next = parent.parent;
+    }
+    Ok(permissions)
+}
+
+/// Union of `role`'s permissions with every role reachable through parents and
+/// composition members in `remaining_accounts`. Each role is visited once, which
+/// also breaks cycles; visiting more than `MAX_RESOLVED_ROLES` fails. Roles not
+/// live at `now` are skipped along with everything reachable only through them.
+fn resolve_role_graph(remaining: &[AccountInfo], role: &Role, now: i64) -> Result<u32> {
+    let mut permissions = role.permissions;
+    let mut visited = vec![role.name.clone()];
+    let mut pending: Vec<String> = role.parent.iter().chain(&role.composed_of).cloned().collect();
+    while let Some(name) = pending.pop() {
+        if visited.contains(&name) {
+            continue;
+        }
+        require!(visited.len() < MAX_RESOLVED_ROLES, RbacError::ResolutionLimitExceeded);
+        let (address, _) = Pubkey::find_program_address(
+            &[seeds::ROLE, name.as_bytes()],
+            &crate::ID,
+        );
+        visited.push(name);
+        let Some(info) = find_remaining(remaining, &address) else {
+            continue;
+        };
+        let member = load_account::<Role>(info)?;
+        if !member.is_live(now) {
+            continue;
+        }
+        permissions |= member.permissions;
+        pending.extend(member.parent.iter().chain(&member.composed_of).cloned());
+    }
+    Ok(permissions)
+}
+
+/// Permissions of the system-wide base role (e.g. "authenticated") that every
+/// assigned user inherits, read from `remaining_accounts`. Zero when unset or absent.
+fn inherited_base_permissions(rbac_state: &RbacState, remaining: &[AccountInfo]) -> Result<u32> {
+    let Some(base_role) = &rbac_state.base_role else {
+        return Ok(0);
+    };
+    let (base_address, _) = Pubkey::find_program_address(
+        &[seeds::ROLE, base_role.as_bytes()],
+        &crate::ID,
+    );
+    Ok(find_remaining(remaining, &base_address)
+        .map(load_account::<Role>)
+        .transpose()?
+        .map_or(0, |role| role.permissions))
+}
+
+/// Permissions of `role`, preferring a same-slot `PermissionSnapshot` when one
+/// was passed in `remaining_accounts`. Stale snapshots fall back to the live role.
+fn snapshot_or_live_permissions(remaining: &[AccountInfo], role: &Role) -> Result<u32> {
+    let (snapshot_address, _) = Pubkey::find_program_address(
+        &[seeds::PERM_SNAPSHOT, role.name.as_bytes()],
+        &crate::ID,
+    );
+    if let Some(info) = find_remaining(remaining, &snapshot_address) {
+        let snapshot = load_account::<PermissionSnapshot>(info)?;
+        if snapshot.slot == Clock::get()?.slot {
+            return Ok(snapshot.permissions);
+        }
+    }
+    Ok(role.permissions)
+}
+
+/// ============ STATE ACCOUNTS ============
+/// Global RBAC Configuration
+#[account]
+#[derive(InitSpace)]
+pub struct RbacState {
+    pub admin: Pubkey,              // Master admin
+    pub role_count: u32,            // Total distinct roles created
+    pub assignment_count: u32,      // Total role assignments (each user+role PDA = 1)
+    pub bump: u8,               
+    pub global_grant: Option<GlobalGrant>, // Temporary permissions granted to every assigned user
+    pub breakglass_key: Option<Pubkey>,    // Emergency key that may assume admin temporarily
+    pub breakglass_expires_at: i64,        // End of the current break-glass window (0 = inactive)
+    #[max_len(32)]
+    pub required_base_role: Option<String>, // Role every assert_has_permission caller must also hold
+    pub require_note_for_privileged: bool,  // Assignments of Admin/Delete roles must carry a note
+    pub replication_mode: bool,             // Emit StateDelta instead of specific events
+    pub replication_seq: u64,               // Sequence number of the last StateDelta
+    pub hook_program: Option<Pubkey>,       // Compliance program CPI'd on every assignment
+    pub config_frozen: bool,                // Blocks role/assignment mutations; checks still work
+    #[max_len(32)]
+    pub base_role: Option<String>,          // Role whose permissions every assigned user inherits
+    pub assignable_permissions: u32,        // Bits non-admin roles may be granted (0 = all)
+    pub expiry_warning_window: i64,         // Seconds before expiry to warn holders (0 = off)
+    #[max_len(8)]
+    pub admins: Vec<Pubkey>,                // Everyone holding root admin powers, master admin first
+    pub pending_admin: Option<Pubkey>,      // Proposed master admin awaiting accept_admin
+    pub revocation_grace_secs: i64,         // Seconds a revoked assignment stays open (0 = close on revoke)
+    pub paused: bool,                       // Emergency stop for every role/assignment mutation
+    pub guardian: Option<Pubkey>,           // Key that may pause (but not unpause) the system
+    pub max_roles: u32,                     // Cap on live roles (0 = unlimited)
+    pub text_logs: bool,                    // Also msg! a key=value line per state event
+    pub text_log_seq: u64,                  // Sequence number of the last text log line
+    pub change_cooldown_secs: i64,          // Min seconds between changes to one assignment (0 = off)
+    pub sealed: bool,                       // Permanently rejects every admin mutation; no unseal
+    pub admin_threshold: u8,                // Admin approvals a proposal needs (0 or 1 = no quorum)
+    pub proposal_count: u64,                // Proposals ever opened; the next one's id
+    pub version: u8,                        // Layout version, see `Versioned`
+    #[max_len(4)]
+    pub cpi_targets: Vec<Pubkey>,           // Programs execute_action_cpi may forward to
+    pub max_delegation_depth: u8,           // Most grant_assigner hops from the root admin to a manager
+}
+impl RbacState {
+    /// Authorize an admin-level action. Returns `true` when the signer is acting
+    /// under an active break-glass window so the action can be tagged in events.
+    pub fn authorize_admin(&self, signer: &Pubkey, now: i64) -> Result<bool> {
+        self.require_unsealed()?;
+        if self.is_admin(signer) {
+            return Ok(false);
+        }
+        require!(
+            self.breakglass_key == Some(*signer) && now < self.breakglass_expires_at,
+            RbacError::NotAuthorized
+        );
+        Ok(true)
+    }
+
+    /// Whether granting a role `PERM_ADMIN` and closing roles must go through a proposal.
+    pub fn requires_quorum(&self) -> bool {
+        self.admin_threshold > 1
+    }
+
+    /// Reject granting a role the `added` bits outside a proposal when they
+    /// include `PERM_ADMIN` and `requires_quorum`.
+    pub fn require_proposal_for(&self, added: u32) -> Result<()> {
+        require!(
+            added & PERM_ADMIN == 0 || !self.requires_quorum(),
+            RbacError::ProposalRequired
+        );
+        Ok(())
+    }
+
+    /// Whether `key` is the master admin or one of the co-admins.
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        *key == self.admin || self.admins.contains(key)
+    }
+
+    /// Make `new_admin` the master admin, moving it to the front of the admin
+    /// set in place of the old master. Returns the old master admin.
+    fn replace_master_admin(&mut self, new_admin: Pubkey) -> Pubkey {
+        let old_admin = self.admin;
+        self.admin = new_admin;
+        self.admins.retain(|admin| *admin != old_admin && *admin != new_admin);
+        self.admins.insert(0, new_admin);
+        old_admin
+    }
+
+    /// Reject creating another role once `max_roles` live roles exist.
+    pub fn require_role_capacity(&self) -> Result<()> {
+        require!(
+            self.max_roles == 0 || self.role_count < self.max_roles,
+            RbacError::TooManyRoles
+        );
+        Ok(())
+    }
+
+    /// Reject teardown while any role or assignment still exists.
+    pub fn require_empty(&self) -> Result<()> {
+        require!(
+            self.role_count == 0 && self.assignment_count == 0,
+            RbacError::SystemNotEmpty
+        );
+        Ok(())
+    }
+
+    /// Reject any mutation once the configuration has been sealed for good.
+    pub fn require_unsealed(&self) -> Result<()> {
+        require!(!self.sealed, RbacError::ConfigSealed);
+        Ok(())
+    }
+
+    /// Reject role and assignment mutations while the system is sealed, paused
+    /// or the configuration is frozen.
+    pub fn require_unfrozen(&self) -> Result<()> {
+        self.require_unsealed()?;
+        require!(!self.paused, RbacError::SystemPaused);
+        require!(!self.config_frozen, RbacError::ConfigFrozen);
+        Ok(())
+    }
+
+    /// Reject a change to `user_role` within `change_cooldown_secs` of its last one.
+    /// `bypass` lets a root admin (or active break-glass key) skip the cooldown.
+    pub fn require_change_cooled(
+        &self,
+        user_role: &UserRole,
+        signer: &Pubkey,
+        bypass: bool,
+        now: i64,
+    ) -> Result<()> {
+        if bypass {
+            self.authorize_admin(signer, now)?;
+            return Ok(());
+        }
+        require!(
+            now >= user_role.last_changed_at.saturating_add(self.change_cooldown_secs),
+            RbacError::ChangeCooldownActive
+        );
+        Ok(())
+    }
+
+    /// Reject granting bits outside the system-wide assignable set to any role
+    /// but the admin role. An empty (zero) set leaves every bit assignable.
+    pub fn require_assignable(&self, role_name: &str, granted: u32) -> Result<()> {
+        if self.assignable_permissions == 0 || role_name == ADMIN_ROLE_NAME {
+            return Ok(());
+        }
+        require!(
+            granted & !self.assignable_permissions == 0,
+            RbacError::PermissionNotAssignable
+        );
+        Ok(())
+    }
+
+    /// Permission bits currently granted to every assigned user, if any.
+    pub fn active_global_grant(&self, now: i64) -> u32 {
+        match self.global_grant {
+            Some(grant) if now < grant.expires_at => grant.permissions,
+            _ => 0,
+        }
+    }
+}
+
+/// Initial configuration passed to `initialize`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct InitializeConfig {
+    pub max_roles: u32,              // Cap on live roles (0 = unlimited)
+    pub change_cooldown_secs: i64,   // Min seconds between changes to one assignment (0 = off)
+    pub guardian: Option<Pubkey>,    // Key that may pause (but not unpause) the system
+}
+
+/// Instruction arguments sent to the compliance hook's `on_role_assigned`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct OnRoleAssignedArgs {
+    pub user: Pubkey,
+    pub role: String,
+    pub assigned_by: Pubkey,
+    pub expires_at: Option<i64>,
+}
+
+/// One `(user, role)` pair for `assign_roles_batch`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BatchAssignment {
+    pub user: Pubkey,
+    pub role: String,
+}
+
+/// Outcome of `assign_role`, set as return data for CPI callers and clients
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct AssignmentResult {
+    pub user: Pubkey,
+    pub role: Pubkey,                // Role PDA
+    pub user_role: Pubkey,           // UserRole PDA holding the assignment
+    pub expires_at: Option<i64>,
+}
+
+/// Outcome of `check_permission_at_slot` with the slot it was decided in
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SlotPinnedCheck {
+    pub allowed: bool,
+    pub slot: u64,
+}
+
+/// Live vs. staged permissions of a role, returned by `preview_role_update`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct RoleUpdateDiff {
+    pub current: u32,
+    pub proposed: u32,
+    pub added: u32,                  // Bits the update would grant
+    pub removed: u32,                // Bits the update would take away
+}
+
+/// Structured outcome of a permission check
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct Evaluation {
+    pub allowed: bool,
+    pub reason: EvaluationReason,    // Layer that decided the outcome
+}
+impl Evaluation {
+    fn decide(reason: EvaluationReason) -> Self {
+        Self { allowed: reason.denial_error().is_none(), reason }
+    }
+}
+
+/// Layer of `evaluate` that decided a permission check
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum EvaluationReason {
+    SystemAdmin,
+    Delegated,
+    Allowed,
+    Expired,
+    Suspended,
+    NotAcknowledged,
+    InsufficientPermissions,
+    MissingBaseRole,
+    SignerNotAllowed,
+    Revoked,
+    OutOfScope,
+    RoleDisabled,
+    ExplicitlyDenied,
+    UserFrozen,
+    NoAssignment,
+    RoleNotYetValid,
+    RoleExpired,
+}
+impl EvaluationReason {
+    /// Human-readable reason recorded in `PermissionChecked` events.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::SystemAdmin => "System Admin",
+            Self::Delegated => "Delegated",
+            Self::Allowed => "Allowed",
+            Self::Expired => "Expired Role",
+            Self::Suspended => "Suspended",
+            Self::NotAcknowledged => "Not Acknowledged",
+            Self::InsufficientPermissions => "Insufficient Bitmask",
+            Self::MissingBaseRole => "Missing Base Role",
+            Self::SignerNotAllowed => "Missing Co-Signer",
+            Self::Revoked => "Revoked",
+            Self::OutOfScope => "Out Of Scope",
+            Self::RoleDisabled => "Role Disabled",
+            Self::ExplicitlyDenied => "Explicitly Denied",
+            Self::UserFrozen => "User Frozen",
+            Self::NoAssignment => "No Assignment",
+            Self::RoleNotYetValid => "Role Not Yet Valid",
+            Self::RoleExpired => "Role Expired",
+        }
+    }
+
+    /// Error `assert_has_permission` fails with, or `None` when access is granted.
+    pub fn denial_error(&self) -> Option<RbacError> {
+        match self {
+            Self::SystemAdmin | Self::Delegated | Self::Allowed => None,
+            Self::Expired
+            | Self::InsufficientPermissions
+            | Self::Revoked
+            | Self::OutOfScope
+            | Self::ExplicitlyDenied => Some(RbacError::PermissionDenied),
+            Self::Suspended => Some(RbacError::AssignmentSuspended),
+            Self::NotAcknowledged => Some(RbacError::NotAcknowledged),
+            Self::MissingBaseRole => Some(RbacError::MissingBaseRole),
+            Self::SignerNotAllowed => Some(RbacError::SignerNotAllowed),
+            Self::RoleDisabled => Some(RbacError::RoleDisabled),
+            Self::UserFrozen => Some(RbacError::UserFrozen),
+            Self::NoAssignment => Some(RbacError::MissingAssignment),
+            Self::RoleNotYetValid => Some(RbacError::RoleNotYetValid),
+            Self::RoleExpired => Some(RbacError::RoleExpired),
+        }
+    }
+}
+
+/// Time-boxed permission broadening layered on top of every role
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct GlobalGrant {
+    pub permissions: u32,            // Bitmask granted to all assigned users
+    pub expires_at: i64,             // Grant is ignored from this timestamp on
+}
+
+/// Permission bits a role gains once the system reaches an assignment threshold
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct ConditionalGrant {
+    pub permissions: u32,            // Bitmask unlocked by the condition
+    pub threshold: u32,              // Minimum `RbacState::assignment_count`
+}
+
+/// On-chain Role Data
+#[account]
+#[derive(InitSpace)]
+pub struct Role {
+    #[max_len(MAX_ROLE_NAME_LEN)]
+    pub name: String,                // Limited to MAX_ROLE_NAME_LEN bytes
+    pub permissions: u32,            // Bitmask. E.g: 0b0001 = read, 0b0010 = create
+    pub created_at: i64,             
+    pub bump: u8,                    
+    #[max_len(4, 32)]
+    pub composed_of: Vec<String>,    // Roles whose permissions are unioned in at check time
+    pub attribute_program: Option<Pubkey>, // Owner of identity attribute accounts gating assignment
+    pub min_attribute_value: u64,    // Minimum attribute value required to be assigned
+    pub require_acknowledgment: bool, // Holders must acknowledge before checks pass
+    pub assigner_stake: u64,         // Lamports the assigner escrows per new assignment
+    pub assignment_count: u32,       // Live UserRole PDAs of this role; must be 0 to delete
+    pub max_disbursement: u64,       // Lamports a holder may disburse per check_disbursement
+    #[max_len(32)]
+    pub parent: Option<String>,      // Role whose permissions (and ancestors') are inherited
+    #[max_len(32)]
+    pub requires_role: Option<String>, // Role a user must already hold to be assigned this one
+    #[max_len(4)]
+    pub conditional_grants: Vec<ConditionalGrant>, // Bits unlocked by system-wide assignment count
+    #[max_len(128)]
+    pub description: String,         // Human-readable purpose of the role
+    pub active: bool,                // Inactive roles deny every check; set to true on creation
+    pub required_mint: Option<Pubkey>, // Mint the assignee must hold to be assigned
+    pub min_amount: u64,             // Minimum balance of `required_mint` required
+    pub version: u8,                 // Layout version, see `Versioned`
+    #[max_len(32)]
+    pub permission_group: Option<String>, // `PermissionGroup` whose bits are unioned in at check time
+    #[max_len(32)]
+    pub renamed_to: Option<String>,  // Set by rename_role while assignments still await remap_user_role
+    pub max_assignments: Option<u32>, // Cap on `assignment_count`; None is unlimited
+    pub valid_from: Option<i64>,     // Checks through the role are denied before this
+    pub valid_until: Option<i64>,    // ...and from this timestamp on
+    pub disabled_at: i64,            // Set by emergency_disable_role; nonzero denies every check
+}
+impl Role {
+    /// Whether this is the `WILDCARD_ROLE_NAME` role.
+    pub fn is_wildcard(&self) -> bool {
+        self.name == WILDCARD_ROLE_NAME
+    }
+
+    /// Whether the role is usable at all: active and not emergency-disabled.
+    pub fn is_enabled(&self) -> bool {
+        self.active && self.disabled_at == 0
+    }
+
+    /// Whether `now` falls inside the role's validity window.
+    pub fn in_window(&self, now: i64) -> bool {
+        self.valid_from.map_or(true, |from| now >= from)
+            && self.valid_until.map_or(true, |until| now < until)
+    }
+
+    /// Whether any check through the role can pass at `now`: enabled and inside
+    /// its validity window.
+    pub fn is_live(&self, now: i64) -> bool {
+        self.is_enabled() && self.in_window(now)
+    }
+
+    /// Account for a new assignment, failing once `max_assignments` is reached.
+    pub fn claim_assignment(&mut self) -> Result<()> {
+        require!(
+            self.max_assignments.map_or(true, |max| self.assignment_count < max),
+            RbacError::RoleAssignmentCapReached
+        );
+        increment(&mut self.assignment_count)
+    }
+
+    /// Account for a closed assignment. Saturates because roles created before
+    /// the counter existed may hold assignments it never saw.
+    pub fn release_assignment(&mut self) {
+        self.assignment_count = self.assignment_count.saturating_sub(1);
+    }
+
+    /// Union of the conditional grants whose threshold `assignment_count` has reached.
+    pub fn met_conditional_grants(&self, assignment_count: u32) -> u32 {
+        self.conditional_grants
+            .iter()
+            .filter(|grant| assignment_count >= grant.threshold)
+            .fold(0, |bits, grant| bits | grant.permissions)
+    }
+}
+/// Enumerable index of every live role name, grown and shrunk with each
+/// create_role / delete_role
+#[account]
+pub struct RoleRegistry {
+    pub bump: u8,
+    pub names: Vec<String>,          // Role names in creation order
+}
+impl RoleRegistry {
+    /// Account size (discriminator included) needed to hold `names`.
+    pub fn space(names: &[String]) -> usize {
+        8 + 1 + 4 + names.iter().map(|name| 4 + name.len()).sum::<usize>()
+    }
+
+    /// Size after registering `name`.
+    pub fn space_with(&self, name: &str) -> usize {
+        Self::space(&self.names) + 4 + name.len()
+    }
+
+    /// Size after unregistering `name`; unchanged if it was never registered
+    /// (roles created before the registry existed).
+    pub fn space_without(&self, name: &str) -> usize {
+        let removed: usize = self.names
+            .iter()
+            .filter(|registered| registered.as_str() == name)
+            .map(|registered| 4 + registered.len())
+            .sum();
+        Self::space(&self.names) - removed
+    }
+}
+
+/// Names of the roles one user holds a global assignment of, grown and shrunk
+/// with each assign / claim / revoke / reassign and the permissionless cleanups
+#[account]
+pub struct UserRoleIndex {
+    pub user: Pubkey,
+    pub bump: u8,
+    pub roles: Vec<String>,          // Role names in assignment order
+}
+impl UserRoleIndex {
+    /// Account size (discriminator included) needed to hold `roles`.
+    pub fn space(roles: &[String]) -> usize {
+        8 + 32 + 1 + 4 + roles.iter().map(|role| 4 + role.len()).sum::<usize>()
+    }
+}
+
+/// Ring buffer of the last `MAX_ADMIN_HISTORY` accepted admin handoffs
+#[account]
+#[derive(InitSpace)]
+pub struct AdminHistory {
+    pub bump: u8,
+    #[max_len(8)]
+    pub entries: Vec<AdminTransferRecord>, // Oldest first
+}
+impl AdminHistory {
+    /// Append a handoff, evicting the oldest once the buffer is full.
+    pub fn record(&mut self, entry: AdminTransferRecord) {
+        if self.entries.len() >= MAX_ADMIN_HISTORY {
+            self.entries.remove(0);
+        }
+        self.entries.push(entry);
+    }
+}
+
+/// Fixed-size ring buffer of the last `AUDIT_LOG_CAPACITY` assignments and
+/// revocations, queryable on-chain after the events are gone
+#[account]
+#[derive(InitSpace)]
+pub struct AuditLog {
+    pub bump: u8,
+    pub head: u16,                   // Slot the next entry is written to
+    #[max_len(64)]
+    pub entries: Vec<AuditEntry>,    // Grows to capacity, then overwritten from `head`
+}
+impl AuditLog {
+    /// Append an entry, overwriting the oldest once the buffer is full.
+    pub fn record(&mut self, entry: AuditEntry) {
+        let head = self.head as usize;
+        if self.entries.len() < AUDIT_LOG_CAPACITY {
+            self.entries.push(entry);
+        } else {
+            self.entries[head] = entry;
+        }
+        self.head = ((head + 1) % AUDIT_LOG_CAPACITY) as u16;
+    }
+
+    /// Retained entries from oldest to newest.
+    pub fn chronological(&self) -> impl Iterator<Item = &AuditEntry> {
+        let split = if self.entries.len() < AUDIT_LOG_CAPACITY { 0 } else { self.head as usize };
+        self.entries[split..].iter().chain(&self.entries[..split])
+    }
+}
+
+/// One audited assignment change
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct AuditEntry {
+    pub actor: Pubkey,               // Signer that made the change
+    pub action_kind: u8,             // AUDIT_ROLE_ASSIGNED or AUDIT_ROLE_REVOKED
+    pub target: Pubkey,              // User whose assignment changed
+    pub timestamp: i64,
+}
+
+/// One completed master-admin handoff
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, InitSpace)]
+pub struct AdminTransferRecord {
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+
+/// Maps User to Role 
+#[account]
+#[derive(InitSpace)]
+pub struct UserRole {
+    pub user: Pubkey,
+    #[max_len(32)]
+    pub role: String,          
+    pub assigned_at: i64,      
+    pub expires_at: Option<i64>, // Time bound constraint. Nullable.
+    pub assigned_by: Pubkey,   
+    pub bump: u8,              
+    #[max_len(4)]
+    pub signer_allowlist: Vec<Pubkey>, // Co-signers required by assert_has_permission when non-empty
+    pub auto_renew: bool,              // Whether process_renewal may extend expires_at
+    pub renew_interval: i64,           // Seconds added to expires_at on each renewal
+    #[max_len(64)]
+    pub note: Option<String>,          // Justification recorded at assignment time
+    pub suspended: bool,               // Temporarily denied by every permission check
+    pub acknowledged: bool,            // User has accepted the role's responsibilities
+    pub stake: u64,                    // Assigner's escrowed bond held in this account
+    pub staked_by: Pubkey,             // Assigner the bond is returned to on revoke
+    pub warned: bool,                  // ExpiryWarning already emitted for the current expiry
+    #[max_len(8)]
+    pub jurisdiction: Option<String>,  // Regulatory tag, e.g. "EU" or "US"
+    pub revoked: bool,                 // Revoked under a grace period, awaiting finalize_revocation
+    pub revoked_at: i64,               // When the assignment was revoked (0 = not revoked)
+    pub fingerprint: [u8; 32],         // Hash of the checked fields, refreshed on every mutation of them
+    pub last_changed_at: i64,          // Last assign/reassign/revoke, for the change cooldown
+    pub resource: Pubkey,              // Resource the assignment is scoped to (GLOBAL_RESOURCE = all)
+    pub denied_permissions: u32,       // Bits never granted through this assignment, Admin included
+    pub version: u8,                   // Layout version, see `Versioned`
+    pub temp_permission: Option<u32>,  // Elevated bits granted by elevate_user until temp_expires_at
+    pub temp_expires_at: i64,
+    pub delegation_depth: u8,          // Manager hops between the root admin and the assigner (admin = 0)
+}
+impl UserRole {
+    /// Whether the assignment applies to `resource`: global ones apply everywhere.
+    pub fn covers(&self, resource: &Pubkey) -> bool {
+        self.resource == GLOBAL_RESOURCE || self.resource == *resource
+    }
+
+    /// Whether the time-bound assignment has lapsed at `now`.
+    pub fn is_expired(&self, now: i64) -> bool {
+        matches!(self.expires_at, Some(expiry) if now >= expiry)
+    }
+
+    /// Hash of the fields a check depends on: holder, role, timing, assigner and
+    /// every restriction or grant layered on the assignment.
+    pub fn compute_fingerprint(&self) -> [u8; 32] {
+        let assigned_at = self.assigned_at.to_le_bytes();
+        let expires_at = self.expires_at.unwrap_or(NO_EXPIRY).to_le_bytes();
+        let denied_permissions = self.denied_permissions.to_le_bytes();
+        let temp_permission = self.temp_permission.unwrap_or(0).to_le_bytes();
+        let temp_expires_at = self.temp_expires_at.to_le_bytes();
+        let suspended = [self.suspended as u8];
+        let mut fields: Vec<&[u8]> = vec![
+            self.user.as_ref(),
+            self.role.as_bytes(),
+            &assigned_at,
+            &expires_at,
+            self.assigned_by.as_ref(),
+            self.resource.as_ref(),
+            &denied_permissions,
+            &temp_permission,
+            &temp_expires_at,
+            &suspended,
+        ];
+        fields.extend(self.signer_allowlist.iter().map(|signer| signer.as_ref()));
+        hashv(&fields).to_bytes()
+    }
+
+    /// Store the fingerprint of the current fields after a legitimate mutation.
+    pub fn refresh_fingerprint(&mut self) {
+        self.fingerprint = self.compute_fingerprint();
+    }
+
+    /// Bits `elevate_user` grants on top of the role at `now`; nothing once lapsed.
+    pub fn active_temp_permission(&self, now: i64) -> u32 {
+        match self.temp_permission {
+            Some(permission) if now < self.temp_expires_at => permission,
+            _ => 0,
+        }
+    }
+
+    /// Whether `permissions` grant `required` through this assignment. Denied bits
+    /// win over everything, including the Admin bit's implication.
+    pub fn allows(&self, permissions: u32, required: u32) -> bool {
+        required & self.denied_permissions == 0
+            && grants(permissions & !self.denied_permissions, required)
+    }
+
+    /// Whether the user has accepted `role`, or the role does not require it.
+    pub fn is_acknowledged(&self, role: &Role) -> bool {
+        self.acknowledged || !role.require_acknowledgment
+    }
+}
+
+/// Slot-scoped copy of a role's permissions for transaction-level consistency
+#[account]
+#[derive(InitSpace)]
+pub struct PermissionSnapshot {
+    #[max_len(32)]
+    pub role: String,
+    pub permissions: u32,            // Role permissions at `slot`
+    pub slot: u64,                   // Snapshot is only honoured during this slot
+    pub bump: u8,
+}
+
+/// Staged replacement permission set, applied only by `commit_role_update`
+#[account]
+#[derive(InitSpace)]
+pub struct PendingRoleUpdate {
+    #[max_len(32)]
+    pub role: String,
+    pub new_permissions: u32,
+    pub staged_by: Pubkey,           // Paid the rent; refunded on commit or discard
+    pub staged_at: i64,
+    pub bump: u8,
+}
+
+/// Named permission bundle referenced by roles through `Role::permission_group`
+#[account]
+#[derive(InitSpace)]
+pub struct PermissionGroup {
+    #[max_len(32)]
+    pub name: String,
+    pub permissions: u32,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+/// Per-user switch that denies every permission check while frozen
+#[account]
+#[derive(InitSpace)]
+pub struct UserStatus {
+    pub user: Pubkey,
+    pub frozen: bool,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+/// Quorum-gated action a `Proposal` carries out
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum ProposalAction {
+    CreateRole,
+    DeleteRole,
+}
+
+/// Admin-approved action awaiting `execute_proposal`
+#[account]
+#[derive(InitSpace)]
+pub struct Proposal {
+    pub id: u64,
+    pub action: ProposalAction,
+    #[max_len(32)]
+    pub role: String,                // Role created or deleted
+    pub permissions: u32,            // Permissions of a created role (0 for deletes)
+    pub proposer: Pubkey,            // Paid the rent; refunded on execution
+    #[max_len(8)]
+    pub approvals: Vec<Pubkey>,      // Distinct admins who approved, proposer first
+    pub created_at: i64,
+    pub bump: u8,
+}
+impl Proposal {
+    /// Size of the role registry once this proposal has been carried out.
+    pub fn registry_space(&self, registry: &RoleRegistry) -> usize {
+        match self.action {
+            ProposalAction::CreateRole => registry.space_with(&self.role),
+            ProposalAction::DeleteRole => registry.space_without(&self.role),
+        }
+    }
+}
+
+/// Merkle root of users eligible to self-claim a role
+#[account]
+#[derive(InitSpace)]
+pub struct RoleClaim {
+    #[max_len(32)]
+    pub role: String,
+    pub merkle_root: [u8; 32],       // Root over (user, role, expires_at) leaves
+    pub bump: u8,
+}
+
+/// Record of a claimant's last `claim_role_merkle`, kept through revocation so the
+/// same proof cannot mint the role again under the same root
+#[account]
+#[derive(InitSpace)]
+pub struct ClaimReceipt {
+    pub user: Pubkey,
+    #[max_len(32)]
+    pub role: String,
+    pub merkle_root: [u8; 32],       // Root the claim was proven against
+    pub claimed_at: i64,
+    pub bump: u8,
+}
+
+/// Time-boxed hand-off of some of a delegator's role permissions to another user
+#[account]
+#[derive(InitSpace)]
+pub struct DelegationToken {
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    #[max_len(32)]
+    pub role: String,                // Delegator's role the permissions are drawn from
+    pub permissions: u32,            // Delegated bits, a subset of the role's
+    pub expires_at: i64,             // Token is ignored from this timestamp on
+    pub bump: u8,
+}
+
+/// Sub-admin allowed to manage only roles within its name-prefix scope
+#[account]
+#[derive(InitSpace)]
+pub struct OrgAdmin {
+    pub admin: Pubkey,
+    #[max_len(4, 32)]
+    pub prefixes: Vec<String>,       // Role-name prefixes this admin may manage
+    pub bump: u8,
+}
+impl OrgAdmin {
+    /// Whether `role_name` falls under one of this admin's prefixes.
+    pub fn in_scope(&self, role_name: &str) -> bool {
+        self.prefixes.iter().any(|prefix| role_name.starts_with(prefix.as_str()))
+    }
+}
+
+/// Key allowed to assign exactly one (non-admin) role
+#[account]
+#[derive(InitSpace)]
+pub struct RoleManager {
+    pub manager: Pubkey,
+    #[max_len(32)]
+    pub role: String,                // The only role this manager may assign
+    pub bump: u8,
+    pub depth: u8,                   // grant_assigner hops from the root admin (admin-granted = 1)
+    pub granted_by: Pubkey,          // Root admin or parent manager that granted this one
+}
+
+/// ============ ERROR CODES ============
+
+#[error_code]
+pub enum RbacError {
+    #[msg("Role name exceeds maximum length of 32 bytes")]
+    RoleNameTooLong,
+    #[msg("Invalid permissions bitmask value")]
+    InvalidPermissions,
+    #[msg("Role not found from the provided PDA")]
+    RoleNotFound,
+    #[msg("User role PDA does not match expected Role")]
+    UserRoleMismatch,
+    #[msg("User does not have required permissions or role expired")]
+    PermissionDenied,
+    #[msg("Only the admin can perform this configuration action")]
+    NotAuthorized,
+    #[msg("Assignment count underflow")]
+    CountUnderflow,
+    #[msg("Remaining accounts are malformed or not owned by this program")]
+    InvalidRemainingAccounts,
+    #[msg("Expiry timestamp must be in the future")]
+    InvalidExpiry,
+    #[msg("Break-glass duration must be positive and at most 24 hours")]
+    InvalidBreakglassDuration,
+    #[msg("A signer from the assignment's allowlist must co-sign")]
+    SignerNotAllowed,
+    #[msg("Signer allowlist exceeds maximum of 4 keys")]
+    SignerAllowlistTooLong,
+    #[msg("Renewal interval must be positive")]
+    InvalidRenewInterval,
+    #[msg("Only time-bound assignments can auto-renew")]
+    AutoRenewRequiresExpiry,
+    #[msg("Auto-renewal is not enabled for this assignment")]
+    AutoRenewDisabled,
+    #[msg("Assignment has already expired")]
+    AssignmentExpired,
+    #[msg("Assignment is not yet within its renewal window")]
+    RenewalNotDue,
+    #[msg("Referenced role still exists; assignment is not orphaned")]
+    RoleStillExists,
+    #[msg("User does not hold the required base role")]
+    MissingBaseRole,
+    #[msg("Stored bump already matches the canonical bump")]
+    BumpAlreadyCanonical,
+    #[msg("A composite role may reference at most 4 roles")]
+    TooManyComposedRoles,
+    #[msg("A role cannot be composed of itself")]
+    InvalidComposition,
+    #[msg("Assigning a privileged role requires a justification note")]
+    NoteRequired,
+    #[msg("Note exceeds maximum length of 64 bytes")]
+    NoteTooLong,
+    #[msg("Counter overflow")]
+    CounterOverflow,
+    #[msg("User's identity attribute does not meet the role requirement")]
+    AttributeRequirementNotMet,
+    #[msg("Configured hook program must be passed in remaining accounts")]
+    HookProgramMissing,
+    #[msg("Role is outside this org admin's scope")]
+    OutsideAdminScope,
+    #[msg("An org admin may be scoped to at most 4 prefixes")]
+    TooManyAdminPrefixes,
+    #[msg("A UserRole and Role must be passed unless checking the system admin")]
+    MissingAssignment,
+    #[msg("Role configuration is frozen")]
+    ConfigFrozen,
+    #[msg("Delegated permissions must be held by the delegator's role")]
+    DelegationExceedsRole,
+    #[msg("Assignment is suspended")]
+    AssignmentSuspended,
+    #[msg("A bulk instruction may touch at most 10 assignments")]
+    TooManyAssignments,
+    #[msg("Merkle proof does not match the role claim root")]
+    InvalidProof,
+    #[msg("User has not acknowledged this role assignment")]
+    NotAcknowledged,
+    #[msg("Cannot grant a permission the caller does not hold")]
+    CannotGrantUnheldPermission,
+    #[msg("The staker must be passed writable to receive the returned stake")]
+    StakeRecipientMissing,
+    #[msg("Assignment holds no assigner stake")]
+    NoStake,
+    #[msg("Permission is not assignable to non-admin roles")]
+    PermissionNotAssignable,
+    #[msg("No expiry warning window is configured")]
+    ExpiryWarningsDisabled,
+    #[msg("Jurisdiction exceeds maximum length of 8 bytes")]
+    JurisdictionTooLong,
+    #[msg("Assignment is not tagged with the requested jurisdiction")]
+    JurisdictionMismatch,
+    #[msg("Key is already an admin")]
+    AlreadyAdmin,
+    #[msg("Admin set is full (max 8)")]
+    TooManyAdmins,
+    #[msg("Key is not an admin")]
+    NotAnAdmin,
+    #[msg("Cannot remove the last remaining admin")]
+    CannotRemoveLastAdmin,
+    #[msg("No admin transfer is pending")]
+    NoPendingAdmin,
+    #[msg("Assignment has not expired")]
+    AssignmentNotExpired,
+    #[msg("Role still has active assignments")]
+    RoleInUse,
+    #[msg("Permission check did not execute in the pinned slot")]
+    StaleSlot,
+    #[msg("Assignment has been revoked")]
+    AssignmentRevoked,
+    #[msg("Assignment has not been revoked")]
+    AssignmentNotRevoked,
+    #[msg("Revocation grace period has not elapsed")]
+    GracePeriodActive,
+    #[msg("Revocation grace period cannot be negative")]
+    InvalidGracePeriod,
+    #[msg("Role hierarchy exceeds the maximum depth of 5")]
+    HierarchyTooDeep,
+    #[msg("Parent assignment would create a cycle in the role hierarchy")]
+    CyclicHierarchy,
+    #[msg("Ancestor role account missing from remaining accounts")]
+    ParentRoleMissing,
+    #[msg("User does not hold the role's required co-role")]
+    MissingCoRole,
+    #[msg("A role cannot require itself as its co-role")]
+    InvalidCoRole,
+    #[msg("Cannot reassign a role to itself")]
+    InvalidReassignment,
+    #[msg("Staked assignments must be revoked and reassigned separately")]
+    StakedReassignment,
+    #[msg("System is paused")]
+    SystemPaused,
+    #[msg("Quorum must list at most 8 distinct roles with a threshold between 1 and their count")]
+    InvalidQuorum,
+    #[msg("User does not hold enough of the required roles")]
+    QuorumNotMet,
+    #[msg("Maximum number of roles reached")]
+    TooManyRoles,
+    #[msg("Authority to assign the admin role cannot be delegated")]
+    AdminRoleNotDelegable,
+    #[msg("Role graph exceeds the maximum number of resolvable roles")]
+    ResolutionLimitExceeded,
+    #[msg("Role name cannot be empty")]
+    EmptyRoleName,
+    #[msg("Role name must be lowercase without surrounding whitespace")]
+    NonCanonicalRoleName,
+    #[msg("Assignment was changed too recently; wait for the cooldown")]
+    ChangeCooldownActive,
+    #[msg("Change cooldown cannot be negative")]
+    InvalidCooldown,
+    #[msg("A role may carry at most 4 conditional grants")]
+    TooManyConditionalGrants,
+    #[msg("Scoped assignments need a resource other than the global sentinel")]
+    InvalidResource,
+    #[msg("Configuration is permanently sealed")]
+    ConfigSealed,
+    #[msg("Role description exceeds maximum length of 128 bytes")]
+    DescriptionTooLong,
+    #[msg("Role is disabled")]
+    RoleDisabled,
+    #[msg("A batch check may evaluate at most 16 permission sets")]
+    TooManyPermissions,
+    #[msg("User does not hold enough of the role's required token")]
+    TokenGateNotMet,
+    #[msg("This action needs an approved proposal while an admin threshold is set")]
+    ProposalRequired,
+    #[msg("Admin threshold cannot exceed the number of admins")]
+    InvalidThreshold,
+    #[msg("Admin has already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Proposal does not have enough admin approvals yet")]
+    ApprovalsBelowThreshold,
+    #[msg("Role already exists")]
+    RoleAlreadyExists,
+    #[msg("RBAC system is already initialized")]
+    AlreadyInitialized,
+    #[msg("Account does not match any known layout version")]
+    UnknownAccountLayout,
+    #[msg("Account is already at the current layout version")]
+    AccountUpToDate,
+    #[msg("User is frozen")]
+    UserFrozen,
+    #[msg("User status account does not belong to the checked user")]
+    UserStatusMismatch,
+    #[msg("Target program is not an allowed CPI target")]
+    CpiTargetNotAllowed,
+    #[msg("Too many CPI targets")]
+    TooManyCpiTargets,
+    #[msg("Batch exceeds the maximum number of assignments")]
+    BatchTooLarge,
+    #[msg("User role index account missing from remaining accounts")]
+    UserIndexMissing,
+    #[msg("Role has already been renamed")]
+    RoleAlreadyRenamed,
+    #[msg("Role was not renamed to the given name")]
+    RoleNotRenamed,
+    #[msg("The admin role must include the Admin permission")]
+    InvalidAdminRole,
+    #[msg("Cannot disable a role the system admin currently holds")]
+    AdminRoleInUse,
+    #[msg("Role has reached its maximum number of assignments")]
+    RoleAssignmentCapReached,
+    #[msg("Delegation chain would exceed the maximum depth")]
+    DelegationTooDeep,
+    #[msg("Roles or assignments still exist")]
+    SystemNotEmpty,
+    #[msg("Role validity window must start before it ends")]
+    InvalidRoleWindow,
+    #[msg("Role is not valid yet")]
+    RoleNotYetValid,
+    #[msg("Role's validity window has ended")]
+    RoleExpired,
+    #[msg("Role is not emergency-disabled")]
+    RoleNotDisabled,
+    #[msg("Role was already claimed under the current claim root")]
+    RoleAlreadyClaimed,
+}
+
+/// ============ EVENTS ============
+
+#[event]
+pub struct RbacInitialized {
+    pub admin: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+#[event]
+pub struct RoleCreated {
+    pub name: String,
+    pub permissions: u32,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+#[event]
+pub struct RoleAssigned {
+    pub user: Pubkey,
+    pub role: String,
+    pub assigned_by: Pubkey,
+    pub expires_at: Option<i64>,
+    pub note: Option<String>,
+    pub jurisdiction: Option<String>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+    pub permissions: u32,
+    pub delegation_depth: u8,
+}
+#[event]
+pub struct RoleRevoked {
+    pub user: Pubkey,
+    pub role_revoked: String,
+    pub revoked_by: Pubkey,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+#[event]
+pub struct AdminTransferred {
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PermissionChecked {
+    pub user: Pubkey,
+    pub permission_checked: u32,
+    pub result: bool,
+    pub reason: String,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct GlobalGrantSet {
+    pub permissions: u32,
+    pub expires_at: Option<i64>,
+    pub set_by: Pubkey,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct BreakGlassRegistered {
+    pub key: Option<Pubkey>,
+    pub registered_by: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct BreakGlassActivated {
+    pub key: Pubkey,
+    pub admin: Pubkey,
+    pub expires_at: i64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct SignerAllowlistSet {
+    pub user: Pubkey,
+    pub role: String,
+    pub signer_allowlist: Vec<Pubkey>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AutoRenewChanged {
+    pub user: Pubkey,
+    pub role: String,
+    pub auto_renew: bool,
+    pub renew_interval: i64,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleRenewed {
+    pub user: Pubkey,
+    pub role: String,
+    pub previous_expiry: i64,
+    pub expires_at: i64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct OrphanClosed {
+    pub user: Pubkey,
+    pub role: String,
+    pub refunded_to: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RequiredBaseRoleSet {
+    pub base_role: Option<String>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PermissionsSnapshotted {
+    pub role: String,
+    pub permissions: u32,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StateBumpRepaired {
+    pub old_bump: u8,
+    pub new_bump: u8,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleCompositionSet {
+    pub role: String,
+    pub composed_of: Vec<String>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct NotePolicySet {
+    pub require_note_for_privileged: bool,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ReplicationModeSet {
+    pub enabled: bool,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+/// Canonical change record emitted instead of specific events in replication mode.
+/// `data` is the wrapped event's discriminator followed by its borsh encoding.
+#[event]
+pub struct StateDelta {
+    pub seq: u64,
+    pub data: Vec<u8>,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleAttributeRequirementSet {
+    pub role: String,
+    pub attribute_program: Option<Pubkey>,
+    pub min_attribute_value: u64,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ExpiringAssignments {
+    pub users: Vec<Pubkey>,
+    pub roles: Vec<String>,
+    pub expires_at: Vec<i64>,
+    pub window_end: i64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct HookProgramSet {
+    pub hook_program: Option<Pubkey>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct OrgAdminSet {
+    pub admin: Pubkey,
+    pub prefixes: Vec<String>,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ConfigFrozenSet {
+    pub frozen: bool,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PermissionDelegated {
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub role: String,
+    pub permissions: u32,
+    pub expires_at: i64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DelegationRevoked {
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub role: String,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct BulkSuspended {
+    pub role: String,
+    pub users: Vec<Pubkey>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct BulkUnsuspended {
+    pub role: String,
+    pub users: Vec<Pubkey>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleClaimCreated {
+    pub role: String,
+    pub merkle_root: [u8; 32],
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleAcknowledgmentSet {
+    pub role: String,
+    pub require_acknowledgment: bool,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleAcknowledged {
+    pub user: Pubkey,
+    pub role: String,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AccessDescription {
+    pub user: Pubkey,
+    pub roles: Vec<String>,
+    pub role_permissions: Vec<u32>,
+    pub expires_at: Vec<Option<i64>>,
+    pub active: Vec<bool>,
+    pub effective_permissions: u32,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleUpdateStaged {
+    pub role: String,
+    pub old_permissions: u32,
+    pub new_permissions: u32,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleUpdateCommitted {
+    pub role: String,
+    pub old_permissions: u32,
+    pub new_permissions: u32,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleUpdateDiscarded {
+    pub role: String,
+    pub discarded_permissions: u32,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct BaseRoleSet {
+    pub base_role: Option<String>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AssignerStakeSet {
+    pub role: String,
+    pub assigner_stake: u64,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct StakeReturned {
+    pub user: Pubkey,
+    pub role: String,
+    pub staked_by: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AssignerSlashed {
+    pub user: Pubkey,
+    pub role: String,
+    pub staked_by: Pubkey,
+    pub amount: u64,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleGraph {
+    pub roles: Vec<String>,
+    pub composed_of: Vec<Vec<String>>,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RolePermissionsRevoked {
+    pub role: String,
+    pub removed: u32,
+    pub remaining: u32,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AssignablePermissionsSet {
+    pub assignable_permissions: u32,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ExpiryWarningWindowSet {
+    pub window: i64,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ExpiryWarning {
+    pub user: Pubkey,
+    pub role: String,
+    pub expires_at: i64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RolePermissionsUpdated {
+    pub role: String,
+    pub old_permissions: u32,
+    pub new_permissions: u32,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AdminAdded {
+    pub admin: Pubkey,
+    pub added_by: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AdminRemoved {
+    pub admin: Pubkey,
+    pub removed_by: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AdminTransferProposed {
+    pub current_admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AdminTransferAccepted {
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AdminTransferCancelled {
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleUpdatePending {
+    pub role: String,
+    pub current_permissions: u32,
+    pub new_permissions: u32,
+    pub staged_by: Pubkey,
+    pub staged_at: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ExpiredRoleCleaned {
+    pub user: Pubkey,
+    pub role: String,
+    pub refunded_to: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleDeleted {
+    pub name: String,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct MaxDisbursementSet {
+    pub role: String,
+    pub max_disbursement: u64,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RevocationGraceSet {
+    pub revocation_grace_secs: i64,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RevocationFinalized {
+    pub user: Pubkey,
+    pub role: String,
+    pub refunded_to: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AssignmentsByAssigner {
+    pub assigner: Pubkey,
+    pub users: Vec<Pubkey>,
+    pub roles: Vec<String>,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleParentSet {
+    pub role: String,
+    pub parent: Option<String>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RequiredCoRoleSet {
+    pub role: String,
+    pub requires_role: Option<String>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleReassigned {
+    pub user: Pubkey,
+    pub old_role: String,
+    pub new_role: String,
+    pub reassigned_by: Pubkey,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct GuardianSet {
+    pub guardian: Option<Pubkey>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct SystemPaused {
+    pub paused_by: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
-#[derive(Accounts)]
-pub struct CheckPermission<'info> {
-    #[account(
-        seeds = [b"role", user_role.role.as_bytes()],
-        bump = role.bump,
-    )]
-    pub role: Account<'info, Role>,
-    
-    #[account(
-        seeds = [b"user_role", user_role.user.as_ref(), user_role.role.as_bytes()],
-        bump = user_role.bump,
-    )]
-    pub user_role: Account<'info, UserRole>,
+#[event]
+pub struct SystemUnpaused {
+    pub unpaused_by: Pubkey,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
 
-    pub caller: Signer<'info>,
+#[event]
+pub struct TamperDetected {
+    pub user: Pubkey,
+    pub role: String,
+    pub stored: [u8; 32],
+    pub computed: [u8; 32],
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
-#[derive(Accounts)]
-#[instruction(role_name: String)]
-pub struct RevokeRole<'info> {
-    #[account(
-        mut,
-        seeds = [b"rbac_state"],
-        bump = rbac_state.bump,
-    )]
-    pub rbac_state: Account<'info, RbacState>,
+#[event]
+pub struct MaxRolesSet {
+    pub max_roles: u32,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
 
-    #[account(
-        mut,
-        seeds = [b"user_role", user_role.user.as_ref(), role_name.as_bytes()],
-        bump = user_role.bump,
-        close = authority,
-    )]
-    pub user_role: Account<'info, UserRole>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
+#[event]
+pub struct AssignerGranted {
+    pub manager: Pubkey,
+    pub role: String,
+    pub granted_by: Pubkey,
+    pub depth: u8,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
-#[derive(Accounts)]
-pub struct TransferAdmin<'info> {
-    #[account(
-        mut,
-        seeds = [b"rbac_state"],
-        bump = rbac_state.bump,
-    )]
-    pub rbac_state: Account<'info, RbacState>,
-    
-    pub admin: Signer<'info>,
+#[event]
+pub struct AssignerRevoked {
+    pub manager: Pubkey,
+    pub role: String,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
+#[event]
+pub struct TextLogsSet {
+    pub enabled: bool,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
 
-/// ============ STATE ACCOUNTS ============
+#[event]
+pub struct ChangeCooldownSet {
+    pub change_cooldown_secs: i64,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
 
-/// Global RBAC Configuration
-#[account]
-#[derive(InitSpace)]
-pub struct RbacState {
-    pub admin: Pubkey,              // Master admin
-    pub role_count: u32,            // Total distinct roles created
-    pub assignment_count: u32,      // Total role assignments (each user+role PDA = 1)
-    pub bump: u8,               
+#[event]
+pub struct ConditionalGrantsSet {
+    pub role: String,
+    pub conditional_grants: Vec<ConditionalGrant>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
 }
-// impl RbacState {
-//     pub const SIZE: usize = 32 + 4 + 4 + 1; 
-// }
 
-/// On-chain Role Data
-#[account]
-#[derive(InitSpace)]
-pub struct Role {
-    #[max_len(32)]
-    pub name: String,                // Limited to 32 chars
-    pub permissions: u32,            // Bitmask. E.g: 0b0001 = read, 0b0010 = create
-    pub created_at: i64,             
-    pub bump: u8,                    
+#[event]
+pub struct ScopedRoleAssigned {
+    pub user: Pubkey,
+    pub role: String,
+    pub resource: Pubkey,
+    pub assigned_by: Pubkey,
+    pub expires_at: Option<i64>,
+    pub note: Option<String>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
 }
-// impl Role {
-//     // 4 for Prefix + 32 String + 4 (u32) + 8 (i64) + 1 (u8)
-//     pub const SIZE: usize = 4 + 32 + 4 + 8 + 1; 
-// }
 
-/// Maps User to Role 
-#[account]
-#[derive(InitSpace)]
-pub struct UserRole {
+#[event]
+pub struct ScopedRoleRevoked {
     pub user: Pubkey,
-    #[max_len(32)]
-    pub role: String,          
-    pub assigned_at: i64,      
-    pub expires_at: Option<i64>, // Time bound constraint. Nullable.
-    pub assigned_by: Pubkey,   
-    pub bump: u8,              
+    pub role: String,
+    pub resource: Pubkey,
+    pub revoked_by: Pubkey,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
 }
-// impl UserRole {
-//     // 8 (discriminator) + 32 + (4+32 string) + 8 + 9 (Option<i64>) + 32 + 1 
-//     pub const SIZE: usize = 8 + 32 + 36 + 8 + 9 + 32 + 1; 
-// }
 
-/// ============ ERROR CODES ============
+#[event]
+pub struct ConfigSealedForever {
+    pub sealed_by: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
 
-#[error_code]
-pub enum RbacError {
-    #[msg("Role name exceeds maximum length of 32 characters")]
-    RoleNameTooLong,
-    #[msg("Invalid permissions bitmask value")]
-    InvalidPermissions,
-    #[msg("Role not found from the provided PDA")]
-    RoleNotFound,
-    #[msg("User role PDA does not match expected Role")]
-    UserRoleMismatch,
-    #[msg("User does not have required permissions or role expired")]
-    PermissionDenied,
-    #[msg("Only the admin can perform this configuration action")]
-    NotAuthorized,
-    #[msg("Assignment count underflow")]
-    CountUnderflow,
+#[event]
+pub struct RoleActiveChanged {
+    pub role: String,
+    pub active: bool,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
-/// ============ EVENTS ============
+#[event]
+pub struct PermissionsBatchChecked {
+    pub user: Pubkey,
+    pub permissions: Vec<u32>,
+    pub results: Vec<bool>,
+    pub timestamp: i64,
+    pub slot: u64,
+}
 
 #[event]
-pub struct RbacInitialized {
-    pub admin: Pubkey,
+pub struct AdminThresholdSet {
+    pub threshold: u8,
+    pub set_by: Pubkey,
     pub timestamp: i64,
+    pub slot: u64,
 }
+
 #[event]
-pub struct RoleCreated {
+pub struct ProposalCreated {
+    pub id: u64,
+    pub action: ProposalAction,
+    pub role: String,
+    pub permissions: u32,
+    pub proposer: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub id: u64,
+    pub approver: Pubkey,
+    pub approvals: u8,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub id: u64,
+    pub action: ProposalAction,
+    pub executed_by: Pubkey,
+    pub approvals: u8,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct DeniedPermissionsChanged {
+    pub user: Pubkey,
+    pub role: String,
+    pub denied_permissions: u32,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub migrated_by: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PermissionGroupCreated {
     pub name: String,
     pub permissions: u32,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PermissionGroupUpdated {
+    pub name: String,
+    pub old_permissions: u32,
+    pub new_permissions: u32,
+    pub breakglass: bool,
     pub timestamp: i64,
+    pub slot: u64,
 }
+
 #[event]
-pub struct RoleAssigned {
+pub struct UserFrozen {
+    pub user: Pubkey,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct UserUnfrozen {
+    pub user: Pubkey,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PermissionElevated {
     pub user: Pubkey,
     pub role: String,
-    pub assigned_by: Pubkey,
-    pub expires_at: Option<i64>,
+    pub permission: u32,
+    pub expires_at: i64,
+    pub breakglass: bool,
     pub timestamp: i64,
+    pub slot: u64,
 }
+
 #[event]
-pub struct RoleRevoked {
+pub struct CpiTargetsSet {
+    pub cpi_targets: Vec<Pubkey>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct ActionForwarded {
     pub user: Pubkey,
-    pub role_revoked: String,
-    pub revoked_by: Pubkey,
+    pub permission: u32,
+    pub target_program: Pubkey,
     pub timestamp: i64,
+    pub slot: u64,
 }
+
 #[event]
-pub struct AdminTransferred {
-    pub old_admin: Pubkey,
-    pub new_admin: Pubkey,
+pub struct RoleRenamed {
+    pub old_name: String,
+    pub new_name: String,
+    pub pending_assignments: u32,
+    pub breakglass: bool,
     pub timestamp: i64,
+    pub slot: u64,
 }
 
 #[event]
-pub struct PermissionChecked {
+pub struct UserRoleRemapped {
     pub user: Pubkey,
-    pub permission_checked: u32,
-    pub result: bool,
+    pub old_role: String,
+    pub new_role: String,
+    pub old_role_closed: bool,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleCapSet {
+    pub role: String,
+    pub max_assignments: Option<u32>,
+    pub assignment_count: u32,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct MaxDelegationDepthSet {
+    pub max_delegation_depth: u8,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RbacClosed {
+    pub admin: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleValiditySet {
+    pub role: String,
+    pub valid_from: Option<i64>,
+    pub valid_until: Option<i64>,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct PermissionDenied {
+    pub user: Pubkey,
+    pub role: Option<String>,
+    pub required_permission: u32,
+    pub role_permissions: u32,
     pub reason: String,
     pub timestamp: i64,
-}
\ No newline at end of file
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleEmergencyDisabled {
+    pub role: String,
+    pub disabled_by: Pubkey,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RoleReenabled {
+    pub role: String,
+    pub disabled_at: i64,
+    pub breakglass: bool,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct RolesSwept {
+    pub processed: u8,
+    pub closed: u8,
+    pub users: Vec<Pubkey>,
+    pub roles: Vec<String>,
+    pub timestamp: i64,
+    pub slot: u64,
+}
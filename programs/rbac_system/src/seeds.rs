@@ -0,0 +1,82 @@
+//! PDA seed namespaces and the derivations built from them. Every account
+//! address is one of these tags followed by its key fields, and every
+//! `seeds = [...]` constraint and runtime derivation goes through this module.
+//!
+//! Seeds are hashed back to back with no separators, so a tag that is a prefix
+//! of another lets a chosen key spell out the longer one: with a bare `b"role"`
+//! tag, a role named `_registry` derived to the `RoleRegistry` address. No tag
+//! may therefore be a prefix of another (see `ALL`, checked in the tests); the
+//! role tag carries a trailing `:` discriminator for that reason.
+
+use anchor_lang::prelude::*;
+
+use crate::{RbacError, MAX_ROLE_NAME_LEN};
+
+pub const RBAC_STATE: &[u8] = b"rbac_state";
+pub const AUDIT_LOG: &[u8] = b"audit_log";
+pub const ADMIN_HISTORY: &[u8] = b"admin_history";
+pub const ROLE: &[u8] = b"role:";
+pub const ROLE_REGISTRY: &[u8] = b"role_registry";
+pub const ROLE_CLAIM: &[u8] = b"role_claim";
+pub const CLAIM_RECEIPT: &[u8] = b"claim_receipt";
+pub const ROLE_MANAGER: &[u8] = b"role_manager";
+pub const PENDING_ROLE_UPDATE: &[u8] = b"pending_role_update";
+pub const PERM_GROUP: &[u8] = b"perm_group";
+pub const PERM_SNAPSHOT: &[u8] = b"perm_snapshot";
+pub const USER_ROLE: &[u8] = b"user_role";
+pub const USER_INDEX: &[u8] = b"user_index";
+pub const USER_STATUS: &[u8] = b"user_status";
+pub const DELEGATION: &[u8] = b"delegation";
+pub const ORG_ADMIN: &[u8] = b"org_admin";
+pub const PROPOSAL: &[u8] = b"proposal";
+pub const GATEWAY: &[u8] = b"gateway";
+
+/// Every namespace tag; a new one must be added here so the prefix check covers it.
+pub const ALL: &[&[u8]] = &[
+    RBAC_STATE,
+    AUDIT_LOG,
+    ADMIN_HISTORY,
+    ROLE,
+    ROLE_REGISTRY,
+    ROLE_CLAIM,
+    CLAIM_RECEIPT,
+    ROLE_MANAGER,
+    PENDING_ROLE_UPDATE,
+    PERM_GROUP,
+    PERM_SNAPSHOT,
+    USER_ROLE,
+    USER_INDEX,
+    USER_STATUS,
+    DELEGATION,
+    ORG_ADMIN,
+    PROPOSAL,
+    GATEWAY,
+];
+
+/// `role_name` as a PDA seed. Anchor derives `init` addresses before the handler
+/// runs, and deriving from a seed over `MAX_ROLE_NAME_LEN` bytes aborts the
+/// program, so the length is checked here first.
+pub fn role_name_seed(role_name: &str) -> Result<&[u8]> {
+    require!(role_name.len() <= MAX_ROLE_NAME_LEN, RbacError::RoleNameTooLong);
+    Ok(role_name.as_bytes())
+}
+
+/// Address of the singleton `RbacState`.
+pub fn rbac_state_address() -> Pubkey {
+    Pubkey::find_program_address(&[RBAC_STATE], &crate::ID).0
+}
+
+/// Address of the `Role` named `role_name`.
+pub fn role_address(role_name: &str) -> Pubkey {
+    Pubkey::find_program_address(&[ROLE, role_name.as_bytes()], &crate::ID).0
+}
+
+/// Address of `user`'s global assignment of `role_name`.
+pub fn user_role_address(user: &Pubkey, role_name: &str) -> Pubkey {
+    Pubkey::find_program_address(&[USER_ROLE, user.as_ref(), role_name.as_bytes()], &crate::ID).0
+}
+
+/// Address of `user`'s `UserStatus`, which every permission check requires.
+pub fn user_status_address(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[USER_STATUS, user.as_ref()], &crate::ID).0
+}
@@ -0,0 +1,266 @@
+//! Account sizing: every account must fit a maximally-filled instance exactly,
+//! so rent is never overpaid and serialization never runs out of room. Also
+//! covers migrating accounts written in an older layout, the byte limit on
+//! role names, the role/assignment counters' bounds, the admin role's
+//! required permission, the separation of PDA seed namespaces and the
+//! empty-system precondition for teardown.
+
+use super::*;
+
+fn name(len: usize) -> String {
+    "r".repeat(len)
+}
+
+fn serialized_len<T: AnchorSerialize>(value: &T) -> usize {
+    value.try_to_vec().unwrap().len()
+}
+
+fn max_rbac_state() -> RbacState {
+    RbacState {
+        admin: Pubkey::new_unique(),
+        role_count: u32::MAX,
+        assignment_count: u32::MAX,
+        bump: u8::MAX,
+        global_grant: Some(GlobalGrant { permissions: 31, expires_at: i64::MAX }),
+        breakglass_key: Some(Pubkey::new_unique()),
+        breakglass_expires_at: i64::MAX,
+        required_base_role: Some(name(32)),
+        require_note_for_privileged: true,
+        replication_mode: true,
+        replication_seq: u64::MAX,
+        hook_program: Some(Pubkey::new_unique()),
+        config_frozen: true,
+        base_role: Some(name(32)),
+        assignable_permissions: 31,
+        expiry_warning_window: i64::MAX,
+        admins: (0..MAX_ADMINS).map(|_| Pubkey::new_unique()).collect(),
+        pending_admin: Some(Pubkey::new_unique()),
+        revocation_grace_secs: i64::MAX,
+        paused: true,
+        guardian: Some(Pubkey::new_unique()),
+        max_roles: u32::MAX,
+        text_logs: true,
+        text_log_seq: u64::MAX,
+        change_cooldown_secs: i64::MAX,
+        sealed: true,
+        admin_threshold: u8::MAX,
+        proposal_count: u64::MAX,
+        version: u8::MAX,
+        cpi_targets: (0..MAX_CPI_TARGETS).map(|_| Pubkey::new_unique()).collect(),
+        max_delegation_depth: u8::MAX,
+    }
+}
+
+#[test]
+fn rbac_state_space_fits_max_instance() {
+    assert_eq!(serialized_len(&max_rbac_state()), RbacState::INIT_SPACE);
+}
+
+#[test]
+fn role_space_fits_max_instance() {
+    let role = Role {
+        name: name(MAX_ROLE_NAME_LEN),
+        permissions: 31,
+        created_at: i64::MAX,
+        bump: u8::MAX,
+        composed_of: (0..MAX_COMPOSED_ROLES).map(|_| name(32)).collect(),
+        attribute_program: Some(Pubkey::new_unique()),
+        min_attribute_value: u64::MAX,
+        require_acknowledgment: true,
+        assigner_stake: u64::MAX,
+        assignment_count: u32::MAX,
+        max_disbursement: u64::MAX,
+        parent: Some(name(32)),
+        requires_role: Some(name(32)),
+        conditional_grants: (0..MAX_CONDITIONAL_GRANTS)
+            .map(|_| ConditionalGrant { permissions: 31, threshold: u32::MAX })
+            .collect(),
+        description: name(MAX_DESCRIPTION_LEN),
+        active: true,
+        required_mint: Some(Pubkey::new_unique()),
+        min_amount: u64::MAX,
+        version: u8::MAX,
+        permission_group: Some(name(32)),
+        renamed_to: Some(name(32)),
+        max_assignments: Some(u32::MAX),
+        valid_from: Some(i64::MAX),
+        valid_until: Some(i64::MAX),
+        disabled_at: i64::MAX,
+    };
+    assert_eq!(serialized_len(&role), Role::INIT_SPACE);
+}
+
+#[test]
+fn user_role_space_fits_max_instance() {
+    let user_role = UserRole {
+        user: Pubkey::new_unique(),
+        role: name(32),
+        assigned_at: i64::MAX,
+        expires_at: Some(i64::MAX),
+        assigned_by: Pubkey::new_unique(),
+        bump: u8::MAX,
+        signer_allowlist: (0..MAX_SIGNER_ALLOWLIST).map(|_| Pubkey::new_unique()).collect(),
+        auto_renew: true,
+        renew_interval: i64::MAX,
+        note: Some(name(MAX_NOTE_LEN)),
+        suspended: true,
+        acknowledged: true,
+        stake: u64::MAX,
+        staked_by: Pubkey::new_unique(),
+        warned: true,
+        jurisdiction: Some(name(MAX_JURISDICTION_LEN)),
+        revoked: true,
+        revoked_at: i64::MAX,
+        fingerprint: [u8::MAX; 32],
+        last_changed_at: i64::MAX,
+        resource: Pubkey::new_unique(),
+        denied_permissions: u32::MAX,
+        version: u8::MAX,
+        temp_permission: Some(u32::MAX),
+        temp_expires_at: i64::MAX,
+        delegation_depth: u8::MAX,
+    };
+    assert_eq!(serialized_len(&user_role), UserRole::INIT_SPACE);
+}
+
+#[test]
+fn role_registry_space_tracks_names() {
+    let mut registry = RoleRegistry { bump: u8::MAX, names: vec![name(32), name(1)] };
+    assert_eq!(RoleRegistry::space(&registry.names), 8 + serialized_len(&registry));
+
+    let grown = registry.space_with(&name(7));
+    registry.names.push(name(7));
+    assert_eq!(grown, 8 + serialized_len(&registry));
+
+    let shrunk = registry.space_without(&name(32));
+    registry.names.retain(|registered| *registered != name(32));
+    assert_eq!(shrunk, 8 + serialized_len(&registry));
+}
+
+#[test]
+fn user_role_index_space_tracks_roles() {
+    let index = UserRoleIndex {
+        user: Pubkey::new_unique(),
+        bump: u8::MAX,
+        roles: vec![name(32), name(4)],
+    };
+    assert_eq!(UserRoleIndex::space(&index.roles), 8 + serialized_len(&index));
+    assert_eq!(UserRoleIndex::space(&[]), 8 + 32 + 1 + 4);
+}
+
+/// Account data for `state` as written before `version` existed: the same
+/// fields minus the trailing version byte, (empty) `cpi_targets` and
+/// `max_delegation_depth`, in an
+/// account sized for that layout.
+fn version_0_rbac_state(state: &RbacState) -> Vec<u8> {
+    assert!(state.cpi_targets.is_empty());
+    let mut data = Vec::new();
+    state.try_serialize(&mut data).unwrap();
+    data.truncate(data.len() - 1 - 4 - 1);
+    data.resize(RbacState::space_of(0), 0);
+    data
+}
+
+#[test]
+fn migrates_version_0_rbac_state() {
+    let mut state = max_rbac_state();
+    state.admins.truncate(2);
+    state.cpi_targets.clear();
+    let data = version_0_rbac_state(&state);
+    assert_eq!(data.len(), 8 + RbacState::INIT_SPACE - RbacState::LAYOUT_DELTAS.iter().sum::<usize>());
+
+    let (from_version, to_version, migrated) = migrated_data::<RbacState>(&data).unwrap();
+    assert_eq!((from_version, to_version), (0, RbacState::current_version()));
+    assert_eq!(migrated.len(), 8 + RbacState::INIT_SPACE);
+
+    let upgraded = RbacState::try_deserialize(&mut &migrated[..]).unwrap();
+    assert_eq!(upgraded.version, RbacState::current_version());
+    assert_eq!(upgraded.admin, state.admin);
+    assert_eq!(upgraded.admins, state.admins);
+    assert_eq!(upgraded.proposal_count, state.proposal_count);
+    assert_eq!(upgraded.max_delegation_depth, DEFAULT_MAX_DELEGATION_DEPTH);
+}
+
+#[test]
+fn refuses_to_migrate_current_or_unknown_layouts() {
+    let mut state = max_rbac_state();
+    state.version = RbacState::current_version();
+    let mut data = Vec::new();
+    state.try_serialize(&mut data).unwrap();
+    data.resize(RbacState::space_of(RbacState::current_version()), 0);
+    let error = migrated_data::<RbacState>(&data).unwrap_err();
+    assert_eq!(error, RbacError::AccountUpToDate.into());
+
+    data.truncate(data.len() - 2);
+    let error = migrated_data::<RbacState>(&data).unwrap_err();
+    assert_eq!(error, RbacError::UnknownAccountLayout.into());
+}
+
+#[test]
+fn role_name_seed_limit_counts_bytes() {
+    let multibyte = "é".repeat(MAX_ROLE_NAME_LEN / 2 + 1);
+    assert!(multibyte.chars().count() <= MAX_ROLE_NAME_LEN);
+    assert!(seeds::role_name_seed(&multibyte).is_err());
+    assert!(validate_role_name(&multibyte).is_err());
+    assert!(seeds::role_name_seed(&name(MAX_ROLE_NAME_LEN)).is_ok());
+}
+
+#[test]
+fn counters_error_instead_of_wrapping() {
+    let mut counter = u32::MAX - 1;
+    increment(&mut counter).unwrap();
+    assert_eq!(counter, u32::MAX);
+    assert_eq!(
+        increment(&mut counter).unwrap_err(),
+        error!(RbacError::CounterOverflow)
+    );
+    assert_eq!(counter, u32::MAX);
+
+    let mut counter = 1;
+    decrement(&mut counter).unwrap();
+    assert_eq!(counter, 0);
+    assert_eq!(
+        decrement(&mut counter).unwrap_err(),
+        error!(RbacError::CountUnderflow)
+    );
+    assert_eq!(counter, 0);
+}
+
+#[test]
+fn admin_role_requires_admin_permission() {
+    assert_eq!(
+        validate_admin_role(ADMIN_ROLE_NAME, PERM_READ | PERM_UPDATE).unwrap_err(),
+        error!(RbacError::InvalidAdminRole)
+    );
+    validate_admin_role(ADMIN_ROLE_NAME, PERM_READ | PERM_ADMIN).unwrap();
+    validate_admin_role("auditor", PERM_READ).unwrap();
+}
+
+#[test]
+fn seed_namespaces_are_prefix_free() {
+    for (i, a) in seeds::ALL.iter().enumerate() {
+        for (j, b) in seeds::ALL.iter().enumerate() {
+            assert!(i == j || !b.starts_with(a), "{:?} prefixes {:?}", a, b);
+        }
+    }
+}
+
+#[test]
+fn role_names_cannot_reach_other_namespaces() {
+    let pda = |seeds: &[&[u8]]| Pubkey::find_program_address(seeds, &crate::ID).0;
+    let registry = pda(&[seeds::ROLE_REGISTRY]);
+    // Without the discriminator the role "_registry" was the registry account
+    assert_eq!(pda(&[b"role", b"_registry"]), registry);
+    assert_ne!(seeds::role_address("_registry"), registry);
+    assert_ne!(seeds::role_address("_claim"), pda(&[seeds::ROLE_CLAIM]));
+}
+
+#[test]
+fn teardown_requires_no_roles_or_assignments() {
+    let mut state = max_rbac_state();
+    assert_eq!(state.require_empty().unwrap_err(), error!(RbacError::SystemNotEmpty));
+    state.role_count = 0;
+    assert_eq!(state.require_empty().unwrap_err(), error!(RbacError::SystemNotEmpty));
+    state.assignment_count = 0;
+    state.require_empty().unwrap();
+}
@@ -0,0 +1,48 @@
+//! Example dependent of `rbac-system`: `gated_action` only succeeds for callers
+//! whose assignment grants `PERM_UPDATE` and who are not frozen, checked by CPI
+//! into the RBAC program.
+
+use anchor_lang::prelude::*;
+use rbac_system::cpi::accounts::CheckPermission;
+use rbac_system::program::RbacSystem;
+use rbac_system::{guard, Role, UserRole, PERM_UPDATE};
+
+declare_id!("2JkGui3vEpcepmh1UMtCbExihTqnFVNoxxLPvRPu3ngW");
+
+#[program]
+pub mod permission_gate {
+    use super::*;
+
+    pub fn gated_action(ctx: Context<GatedAction>) -> Result<()> {
+        let accounts = &ctx.accounts;
+        guard::require_permission(
+            CpiContext::new(
+                accounts.rbac_program.to_account_info(),
+                CheckPermission {
+                    role: Some(accounts.role.to_account_info()),
+                    user_role: Some(accounts.user_role.to_account_info()),
+                    caller: accounts.caller.to_account_info(),
+                    rbac_state: accounts.rbac_state.to_account_info(),
+                    user_status: accounts.user_status.to_account_info(),
+                },
+            ),
+            PERM_UPDATE,
+        )?;
+
+        msg!("gated action performed by {}", accounts.caller.key());
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct GatedAction<'info> {
+    pub role: Account<'info, Role>,
+    #[account(constraint = user_role.user == caller.key())]
+    pub user_role: Account<'info, UserRole>,
+    pub caller: Signer<'info>,
+    /// CHECK: validated by the RBAC program's own seeds constraint
+    pub rbac_state: UncheckedAccount<'info>,
+    /// CHECK: the caller's UserStatus PDA, verified by the RBAC program
+    pub user_status: UncheckedAccount<'info>,
+    pub rbac_program: Program<'info, RbacSystem>,
+}
@@ -1,7 +1,10 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { RbacSystem } from "../target/types/rbac_system";
+import { PermissionGate } from "../target/types/permission_gate";
 import { expect } from "chai";
+import { createHash } from "crypto";
+import { createMint, getOrCreateAssociatedTokenAccount, mintTo } from "@solana/spl-token";
 
 // Permission Bitmasks
 const PERM_READ = 1 << 0;   // 1
@@ -26,6 +29,121 @@ describe("RBAC System - Access Control on Solana", () => {
   let managerRole: anchor.web3.PublicKey;
   let userRole: anchor.web3.PublicKey;
 
+  // PDA helpers for sections that create their own roles and assignments
+  const findRole = (name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("role:"), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const findUserRole = (user: anchor.web3.PublicKey, name: string) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user_role"), user.toBuffer(), Buffer.from(name)],
+      program.programId
+    )[0];
+
+  const findUserStatus = (user: anchor.web3.PublicKey) =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user_status"), user.toBuffer()],
+      program.programId
+    )[0];
+
+  const assignmentAccounts = (user: anchor.web3.PublicKey, name: string) => [
+    { pubkey: findUserRole(user, name), isSigner: false, isWritable: false },
+    { pubkey: findRole(name), isSigner: false, isWritable: false },
+  ];
+
+  const createRole = (
+    name: string,
+    permissions: number,
+    description = "",
+    requiredMint: anchor.web3.PublicKey | null = null,
+    minAmount = 0,
+    permissionGroup: string | null = null,
+    maxAssignments: number | null = null,
+    validFrom: anchor.BN | null = null,
+    validUntil: anchor.BN | null = null
+  ) =>
+    program.methods
+      .createRole(
+        name,
+        permissions,
+        description,
+        requiredMint,
+        new anchor.BN(minAmount),
+        permissionGroup,
+        maxAssignments,
+        validFrom,
+        validUntil
+      )
+      .accounts({
+        rbacState,
+        role: findRole(name),
+        admin: admin.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+  const assignRole = (
+    user: anchor.web3.PublicKey,
+    name: string,
+    expiresAt: anchor.BN | null = null,
+    note: string | null = null,
+    jurisdiction: string | null = null
+  ) =>
+    program.methods
+      .assignRole(user, name, expiresAt, note, jurisdiction)
+      .accounts({
+        rbacState,
+        role: findRole(name),
+        userRole: findUserRole(user, name),
+        authority: admin.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+  const checkPermission = (
+    user: anchor.web3.PublicKey,
+    name: string,
+    permission: number
+  ) =>
+    program.methods
+      .checkPermission(permission)
+      .accounts({
+        role: findRole(name),
+        userRole: findUserRole(user, name),
+        userStatus: findUserStatus(user),
+      })
+      .view();
+
+  const airdrop = async (pubkey: anchor.web3.PublicKey) =>
+    provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(pubkey, 1 * anchor.web3.LAMPORTS_PER_SOL)
+    );
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    let error: any = null;
+    try {
+      await promise;
+    } catch (e) {
+      error = e;
+    }
+    expect(error, `Expected ${code}`).to.not.be.null;
+    const message =
+      error.error?.errorCode?.code ?? [error.toString(), ...(error.logs ?? [])].join("\n");
+    expect(message).to.contain(code);
+  };
+
+  const eventsOf = async (signature: string) => {
+    await provider.connection.confirmTransaction(signature, "confirmed");
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(program.programId, program.coder);
+    return [...parser.parseLogs(tx?.meta?.logMessages ?? [])];
+  };
+
   // Test user
   const testUser = anchor.web3.Keypair.generate();
   let testUserRoleA: anchor.web3.PublicKey;
@@ -39,17 +157,17 @@ describe("RBAC System - Access Control on Solana", () => {
     );
 
     [adminRole] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("role"), Buffer.from("admin")],
+      [Buffer.from("role:"), Buffer.from("admin")],
       program.programId
     );
 
     [managerRole] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("role"), Buffer.from("manager")],
+      [Buffer.from("role:"), Buffer.from("manager")],
       program.programId
     );
 
     [userRole] = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("role"), Buffer.from("user")],
+      [Buffer.from("role:"), Buffer.from("user")],
       program.programId
     );
 
@@ -70,37 +188,72 @@ describe("RBAC System - Access Control on Solana", () => {
   });
 
   describe("1. System Initialization", () => {
-    it("Should initialize RBAC system with admin", async () => {
-      const tx = await program.methods
-        .initialize()
+    const initialGuardian = anchor.web3.Keypair.generate();
+    const initialize = () =>
+      program.methods
+        .initialize({
+          maxRoles: 1000,
+          changeCooldownSecs: new anchor.BN(0),
+          guardian: initialGuardian.publicKey,
+        })
         .accounts({
           rbacState,
           admin: admin.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
         .rpc();
+
+    it("Should initialize RBAC system with admin", async () => {
+      const tx = await initialize();
         
       console.log(`\n✅ Tx 'initialize': https://explorer.solana.com/tx/${tx}?cluster=devnet`);
 
       const state = await program.account.rbacState.fetch(rbacState);
       expect(state.admin.toString()).to.equal(admin.publicKey.toString());
     });
+
+    it("Should store the initial config", async () => {
+      const state = await program.account.rbacState.fetch(rbacState);
+      expect(state.maxRoles).to.equal(1000);
+      expect(state.changeCooldownSecs.toNumber()).to.equal(0);
+      expect(state.guardian.toString()).to.equal(initialGuardian.publicKey.toString());
+    });
+
+    it("Should reject a second initialize cleanly", async () => {
+      await expectError(initialize(), "AlreadyInitialized");
+    });
+
+    it("Should create the built-in admin role", async () => {
+      const role = await program.account.role.fetch(adminRole);
+      expect(role.name).to.equal("admin");
+      expect(role.permissions).to.equal(PERM_ADMIN);
+      expect(role.active).to.be.true;
+
+      const state = await program.account.rbacState.fetch(rbacState);
+      expect(state.roleCount).to.equal(1);
+      const [registry] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("role_registry")],
+        program.programId
+      );
+      expect((await program.account.roleRegistry.fetch(registry)).names).to.deep.equal(["admin"]);
+    });
+
+    it("Should grant permissions to an admin assigned right after initialize", async () => {
+      const firstAdmin = anchor.web3.Keypair.generate();
+      await assignRole(firstAdmin.publicKey, "admin");
+      expect(await checkPermission(firstAdmin.publicKey, "admin", PERM_DELETE)).to.be.true;
+    });
   });
 
   describe("2. Role Creation (Bitmask Permissions)", () => {
-    it("Should create Admin role with full permissions", async () => {
+    it("Should widen the Admin role to full permissions", async () => {
       const allPerms = PERM_READ | PERM_CREATE | PERM_UPDATE | PERM_DELETE | PERM_ADMIN; // 31
       const tx = await program.methods
-        .createRole("admin", allPerms)
-        .accounts({
-          rbacState,
-          role: adminRole,
-          admin: admin.publicKey,
-          systemProgram: anchor.web3.SystemProgram.programId,
-        })
+        .updateRolePermissions("admin", allPerms)
+        .accounts({ rbacState, role: adminRole, admin: admin.publicKey })
         .rpc();
 
-      console.log(`\n✅ Tx 'createRole (admin)': https://explorer.solana.com/tx/${tx}?cluster=devnet`);
+      console.log(`\n✅ Tx 'updateRolePermissions (admin)': https://explorer.solana.com/tx/${tx}?cluster=devnet`);
 
       const role = await program.account.role.fetch(adminRole);
       expect(role.permissions).to.equal(allPerms);
@@ -108,7 +261,7 @@ describe("RBAC System - Access Control on Solana", () => {
 
     it("Should create User role with read-only permission", async () => {
       const tx = await program.methods
-        .createRole("user", PERM_READ) // 1
+        .createRole("user", PERM_READ, "", null, new anchor.BN(0), null, null, null, null) // 1
         .accounts({
           rbacState,
           role: userRole,
@@ -128,7 +281,7 @@ describe("RBAC System - Access Control on Solana", () => {
     it("Should assign multiple roles to the same user", async () => {
       // Assign Admin (No expiry)
       const txAdmin = await program.methods
-        .assignRole(testUser.publicKey, "admin", null)
+        .assignRole(testUser.publicKey, "admin", null, null, null)
         .accounts({
           rbacState,
           role: adminRole,
@@ -145,7 +298,7 @@ describe("RBAC System - Access Control on Solana", () => {
       const expiresAt = new anchor.BN(now + 2);
 
       const txUser = await program.methods
-        .assignRole(testUser.publicKey, "user", expiresAt)
+        .assignRole(testUser.publicKey, "user", expiresAt, null, null)
         .accounts({
           rbacState,
           role: userRole,
@@ -173,6 +326,7 @@ describe("RBAC System - Access Control on Solana", () => {
         .accounts({
           role: adminRole,
           userRole: testUserRoleA,
+          userStatus: findUserStatus(testUser.publicKey),
         })
         .view();
 
@@ -185,6 +339,7 @@ describe("RBAC System - Access Control on Solana", () => {
         .accounts({
           role: userRole,
           userRole: testUserRoleB,
+          userStatus: findUserStatus(testUser.publicKey),
         })
         .view();
 
@@ -200,6 +355,7 @@ describe("RBAC System - Access Control on Solana", () => {
         .accounts({
           role: userRole,
           userRole: testUserRoleB,
+          userStatus: findUserStatus(testUser.publicKey),
         })
         .view();
 
@@ -210,9 +366,10 @@ describe("RBAC System - Access Control on Solana", () => {
   describe("5. Role Revocation", () => {
     it("Should revoke admin role from user", async () => {
       const tx = await program.methods
-        .revokeRole("admin")
+        .revokeRole("admin", false)
         .accounts({
           rbacState,
+          role: adminRole,
           userRole: testUserRoleA,
           authority: admin.publicKey,
         })
@@ -228,4 +385,4904 @@ describe("RBAC System - Access Control on Solana", () => {
       }
     });
   });
-});
\ No newline at end of file
+
+  describe("6. Access Review Comparison", () => {
+    const reviewAdmin = anchor.web3.Keypair.generate();
+    const editorOne = anchor.web3.Keypair.generate();
+    const editorTwo = anchor.web3.Keypair.generate();
+
+    before(async () => {
+      await createRole("editor", PERM_READ | PERM_CREATE | PERM_UPDATE);
+      await assignRole(reviewAdmin.publicKey, "admin");
+      await assignRole(editorOne.publicKey, "editor");
+      await assignRole(editorTwo.publicKey, "editor");
+    });
+
+    it("Should report admin as a superset of editor", async () => {
+      const result = await program.methods
+        .compareUsers(reviewAdmin.publicKey, editorOne.publicKey)
+        .accounts({ rbacState })
+        .remainingAccounts([
+          ...assignmentAccounts(reviewAdmin.publicKey, "admin"),
+          ...assignmentAccounts(editorOne.publicKey, "editor"),
+        ])
+        .view();
+
+      expect(result).to.equal(1);
+    });
+
+    it("Should report two editors as equal", async () => {
+      const result = await program.methods
+        .compareUsers(editorOne.publicKey, editorTwo.publicKey)
+        .accounts({ rbacState })
+        .remainingAccounts([
+          ...assignmentAccounts(editorOne.publicKey, "editor"),
+          ...assignmentAccounts(editorTwo.publicKey, "editor"),
+        ])
+        .view();
+
+      expect(result).to.equal(0);
+    });
+  });
+
+  describe("7. Temporary Global Grant", () => {
+    const reader = anchor.web3.Keypair.generate();
+
+    const setGlobalGrant = (grant: { permissions: number; expiresAt: anchor.BN } | null) =>
+      program.methods
+        .setGlobalGrant(grant)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    before(async () => {
+      await assignRole(reader.publicKey, "user");
+    });
+
+    it("Should grant UPDATE to a read-only user while the grant is active", async () => {
+      expect(await checkPermission(reader.publicKey, "user", PERM_UPDATE)).to.be.false;
+
+      const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 3);
+      await setGlobalGrant({ permissions: PERM_UPDATE, expiresAt });
+
+      expect(await checkPermission(reader.publicKey, "user", PERM_UPDATE)).to.be.true;
+    });
+
+    it("Should revert once the global grant expires", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+
+      expect(await checkPermission(reader.publicKey, "user", PERM_UPDATE)).to.be.false;
+      await setGlobalGrant(null);
+    });
+  });
+
+  describe("8. Break-Glass Emergency Admin", () => {
+    const breakglass = anchor.web3.Keypair.generate();
+
+    const createRoleAs = (signer: anchor.web3.Keypair, name: string, permissions: number) =>
+      program.methods
+        .createRole(name, permissions, "", null, new anchor.BN(0), null, null, null, null)
+        .accounts({
+          rbacState,
+          role: findRole(name),
+          admin: signer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      await airdrop(breakglass.publicKey);
+      await program.methods
+        .registerBreakglass(breakglass.publicKey)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+    });
+
+    it("Should reject admin actions from break-glass key before activation", async () => {
+      await expectError(createRoleAs(breakglass, "incident", PERM_READ), "NotAuthorized");
+    });
+
+    it("Should allow admin actions while break-glass is active", async () => {
+      await program.methods
+        .invokeBreakglass(new anchor.BN(2))
+        .accounts({ rbacState, breakglass: breakglass.publicKey })
+        .signers([breakglass])
+        .rpc();
+
+      await createRoleAs(breakglass, "incident", PERM_READ);
+      const role = await program.account.role.fetch(findRole("incident"));
+      expect(role.permissions).to.equal(PERM_READ);
+    });
+
+    it("Should expire break-glass powers automatically", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await expectError(createRoleAs(breakglass, "incident2", PERM_READ), "NotAuthorized");
+
+      await program.methods
+        .registerBreakglass(null)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+    });
+  });
+
+  describe("9. Co-Signer Allowlist", () => {
+    const holder = anchor.web3.Keypair.generate();
+    const hardwareKey = anchor.web3.Keypair.generate();
+
+    const assertPermission = (coSigner?: anchor.web3.Keypair) => {
+      const builder = program.methods
+        .assertHasPermission(PERM_READ)
+        .accounts({
+          role: findRole("editor"),
+          userRole: findUserRole(holder.publicKey, "editor"),
+          userStatus: findUserStatus(holder.publicKey),
+        });
+      return coSigner
+        ? builder
+            .remainingAccounts([{ pubkey: coSigner.publicKey, isSigner: true, isWritable: false }])
+            .signers([coSigner])
+            .rpc()
+        : builder.rpc();
+    };
+
+    before(async () => {
+      await assignRole(holder.publicKey, "editor");
+      await program.methods
+        .setSignerAllowlist(holder.publicKey, "editor", [hardwareKey.publicKey])
+        .accounts({
+          rbacState,
+          userRole: findUserRole(holder.publicKey, "editor"),
+          admin: admin.publicKey,
+        })
+        .rpc();
+    });
+
+    it("Should allow the action when an allowlisted key co-signs", async () => {
+      await assertPermission(hardwareKey);
+    });
+
+    it("Should deny the action without an allowlisted co-signer", async () => {
+      await expectError(assertPermission(), "SignerNotAllowed");
+    });
+  });
+
+  describe("10. Auto-Renewing Assignments", () => {
+    const contractor = anchor.web3.Keypair.generate();
+    const cancelled = anchor.web3.Keypair.generate();
+
+    const assignmentAdminAccounts = (user: anchor.web3.PublicKey) => ({
+      rbacState,
+      userRole: findUserRole(user, "user"),
+      admin: admin.publicKey,
+    });
+
+    const processRenewal = (user: anchor.web3.PublicKey) =>
+      program.methods
+        .processRenewal(user, "user")
+        .accounts({ role: findRole("user"), userRole: findUserRole(user, "user") })
+        .rpc();
+
+    before(async () => {
+      const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 3);
+      for (const user of [contractor.publicKey, cancelled.publicKey]) {
+        await assignRole(user, "user", expiresAt);
+        await program.methods
+          .enableAutoRenew(user, "user", new anchor.BN(60))
+          .accounts(assignmentAdminAccounts(user))
+          .rpc();
+      }
+      await program.methods
+        .cancelAutoRenew(cancelled.publicKey, "user")
+        .accounts(assignmentAdminAccounts(cancelled.publicKey))
+        .rpc();
+    });
+
+    it("Should extend a renewable assignment by its interval", async () => {
+      const before = await program.account.userRole.fetch(findUserRole(contractor.publicKey, "user"));
+      await processRenewal(contractor.publicKey);
+      const after = await program.account.userRole.fetch(findUserRole(contractor.publicKey, "user"));
+
+      expect(after.expiresAt.toNumber()).to.equal(before.expiresAt.toNumber() + 60);
+    });
+
+    it("Should let a cancelled assignment expire", async () => {
+      await expectError(processRenewal(cancelled.publicKey), "AutoRenewDisabled");
+
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+      expect(await checkPermission(cancelled.publicKey, "user", PERM_READ)).to.be.false;
+      expect(await checkPermission(contractor.publicKey, "user", PERM_READ)).to.be.true;
+    });
+  });
+
+  describe("11. Orphaned Assignment Cleanup", () => {
+    it("Should refuse to close an assignment whose role still exists", async () => {
+      await expectError(
+        program.methods
+          .closeOrphan(testUser.publicKey)
+          .accounts({
+            rbacState,
+            role: userRole,
+            userRole: testUserRoleB,
+            assignedBy: admin.publicKey,
+          })
+          .rpc(),
+        "RoleStillExists"
+      );
+    });
+  });
+
+  describe("12. Required Base Role", () => {
+    const member = anchor.web3.Keypair.generate();
+
+    const setBaseRole = (name: string | null) =>
+      program.methods
+        .setRequiredBaseRole(name)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    const assertEditorRead = (user = member.publicKey) =>
+      program.methods
+        .assertHasPermission(PERM_READ)
+        .accounts({
+          role: findRole("editor"),
+          userRole: findUserRole(user, "editor"),
+          userStatus: findUserStatus(user),
+        })
+        .remainingAccounts([
+          { pubkey: findUserRole(user, "user"), isSigner: false, isWritable: false },
+          { pubkey: findRole("user"), isSigner: false, isWritable: false },
+        ])
+        .rpc();
+
+    before(async () => {
+      await assignRole(member.publicKey, "editor");
+      await setBaseRole("user");
+    });
+
+    after(async () => {
+      await setBaseRole(null);
+    });
+
+    it("Should deny a user lacking the base role despite holding the permission", async () => {
+      await expectError(assertEditorRead(), "MissingBaseRole");
+    });
+
+    it("Should allow the user once the base role is granted", async () => {
+      await assignRole(member.publicKey, "user");
+      await assertEditorRead();
+    });
+
+    it("Should not count a suspended base assignment", async () => {
+      const suspended = anchor.web3.Keypair.generate().publicKey;
+      await assignRole(suspended, "editor");
+      await assignRole(suspended, "user");
+      await program.methods
+        .suspendRoleHolders("user")
+        .accounts({ rbacState, role: findRole("user"), admin: admin.publicKey })
+        .remainingAccounts([
+          { pubkey: findUserRole(suspended, "user"), isSigner: false, isWritable: true },
+        ])
+        .rpc();
+      await expectError(assertEditorRead(suspended), "MissingBaseRole");
+    });
+  });
+
+  describe("13. Permission Snapshots", () => {
+    const [snapshot] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("perm_snapshot"), Buffer.from("editor")],
+      program.programId
+    );
+
+    it("Should snapshot a role's permissions for the current slot", async () => {
+      await program.methods
+        .snapshotPermissions("editor")
+        .accounts({
+          role: findRole("editor"),
+          snapshot,
+          payer: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const account = await program.account.permissionSnapshot.fetch(snapshot);
+      expect(account.role).to.equal("editor");
+      expect(account.permissions).to.equal(PERM_READ | PERM_CREATE | PERM_UPDATE);
+      expect(account.slot.toNumber()).to.be.greaterThan(0);
+    });
+
+    it("Should fall back to the live role once the snapshot slot has passed", async () => {
+      const editor = anchor.web3.Keypair.generate();
+      await assignRole(editor.publicKey, "editor");
+
+      const allowed = await program.methods
+        .checkPermission(PERM_UPDATE)
+        .accounts({
+          role: findRole("editor"),
+          userRole: findUserRole(editor.publicKey, "editor"),
+          userStatus: findUserStatus(editor.publicKey),
+        })
+        .remainingAccounts([{ pubkey: snapshot, isSigner: false, isWritable: false }])
+        .view();
+
+      expect(allowed).to.be.true;
+    });
+  });
+
+  describe("14. State Bump Repair", () => {
+    it("Should leave a healthy bump untouched", async () => {
+      const before = await program.account.rbacState.fetch(rbacState);
+
+      await expectError(
+        program.methods
+          .repairStateBump()
+          .accounts({ rbacState, admin: admin.publicKey })
+          .rpc(),
+        "BumpAlreadyCanonical"
+      );
+
+      const after = await program.account.rbacState.fetch(rbacState);
+      expect(after.bump).to.equal(before.bump);
+    });
+  });
+
+  describe("15. Composite Roles", () => {
+    const powerUser = anchor.web3.Keypair.generate();
+
+    const checkComposite = (permission: number) =>
+      program.methods
+        .checkPermission(permission)
+        .accounts({
+          role: findRole("power_user"),
+          userRole: findUserRole(powerUser.publicKey, "power_user"),
+          userStatus: findUserStatus(powerUser.publicKey),
+        })
+        .remainingAccounts([
+          { pubkey: findRole("editor"), isSigner: false, isWritable: false },
+          { pubkey: findRole("publisher"), isSigner: false, isWritable: false },
+        ])
+        .view();
+
+    before(async () => {
+      await createRole("publisher", PERM_DELETE);
+      await createRole("power_user", PERM_READ);
+      await program.methods
+        .setRoleComposition("power_user", ["editor", "publisher"])
+        .accounts({ rbacState, role: findRole("power_user"), admin: admin.publicKey })
+        .rpc();
+      await assignRole(powerUser.publicKey, "power_user");
+    });
+
+    it("Should grant permissions from every member role", async () => {
+      expect(await checkComposite(PERM_UPDATE)).to.be.true; // from editor
+      expect(await checkComposite(PERM_DELETE)).to.be.true; // from publisher
+    });
+
+    it("Should still deny permissions no member grants", async () => {
+      expect(await checkComposite(PERM_ADMIN)).to.be.false;
+    });
+  });
+
+  describe("16. Justification Notes for Privileged Assignments", () => {
+    const setNotePolicy = (required: boolean) =>
+      program.methods
+        .setNotePolicy(required)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    before(async () => {
+      await setNotePolicy(true);
+    });
+
+    after(async () => {
+      await setNotePolicy(false);
+    });
+
+    it("Should reject a privileged assignment without a note", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await expectError(assignRole(user.publicKey, "publisher"), "NoteRequired");
+      await expectError(assignRole(user.publicKey, "publisher", null, ""), "NoteRequired");
+    });
+
+    it("Should accept a privileged assignment with a note", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await assignRole(user.publicKey, "publisher", null, "TICKET-42: content cleanup");
+
+      const assignment = await program.account.userRole.fetch(findUserRole(user.publicKey, "publisher"));
+      expect(assignment.note).to.equal("TICKET-42: content cleanup");
+    });
+
+    it("Should not require a note for non-privileged roles", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await assignRole(user.publicKey, "editor");
+    });
+
+    it("Should apply the same policy to resource-scoped assignments", async () => {
+      const user = anchor.web3.Keypair.generate();
+      const resource = anchor.web3.Keypair.generate().publicKey;
+      const userRole = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("user_role"), user.publicKey.toBuffer(), resource.toBuffer(), Buffer.from("publisher")],
+        program.programId
+      )[0];
+      const assignScoped = (note: string | null) =>
+        program.methods
+          .assignRoleScoped(user.publicKey, resource, "publisher", null, note)
+          .accounts({ rbacState, role: findRole("publisher"), userRole, authority: admin.publicKey })
+          .rpc();
+
+      await expectError(assignScoped(null), "NoteRequired");
+      await assignScoped("TICKET-43: scoped cleanup");
+
+      const assignment = await program.account.userRole.fetch(userRole);
+      expect(assignment.note).to.equal("TICKET-43: scoped cleanup");
+    });
+  });
+
+  describe("17. Rent Accounting", () => {
+    it("Should sum rent locked across the state, roles and assignments", async () => {
+      const accounts = [findRole("editor"), findRole("publisher"), testUserRoleB];
+      let expected = await provider.connection.getBalance(rbacState);
+      for (const account of accounts) {
+        expected += await provider.connection.getBalance(account);
+      }
+
+      const total = await program.methods
+        .totalRentLocked()
+        .accounts({ rbacState })
+        .remainingAccounts(
+          accounts.map((pubkey) => ({ pubkey, isSigner: false, isWritable: false }))
+        )
+        .view();
+
+      expect(total.toNumber()).to.equal(expected);
+    });
+
+    it("Should reject duplicated accounts", async () => {
+      const editor = { pubkey: findRole("editor"), isSigner: false, isWritable: false };
+      await expectError(
+        program.methods
+          .totalRentLocked()
+          .accounts({ rbacState })
+          .remainingAccounts([editor, editor])
+          .view(),
+        "InvalidRemainingAccounts"
+      );
+    });
+  });
+
+  describe("18. Replication Mode", () => {
+    const setReplicationMode = (enabled: boolean) =>
+      program.methods
+        .setReplicationMode(enabled)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    after(async () => {
+      await setReplicationMode(false);
+    });
+
+    it("Should emit exactly one StateDelta for create_role", async () => {
+      await setReplicationMode(true);
+      const before = await program.account.rbacState.fetch(rbacState);
+
+      const events = await eventsOf(await createRole("mirror", PERM_READ));
+
+      expect(events.map((e) => e.name)).to.deep.equal(["StateDelta"]);
+      expect(events[0].data.seq.toNumber()).to.equal(before.replicationSeq.toNumber() + 1);
+    });
+
+    it("Should emit specific events again once disabled", async () => {
+      await setReplicationMode(false);
+      const events = await eventsOf(await createRole("mirror2", PERM_READ));
+      expect(events.map((e) => e.name)).to.deep.equal(["RoleCreated"]);
+    });
+  });
+
+  describe("19. Attribute-Gated Assignment", () => {
+    const identityProgram = anchor.web3.Keypair.generate().publicKey;
+    const attributeAccount = anchor.web3.Keypair.generate();
+    const trader = anchor.web3.Keypair.generate();
+
+    const setRequirement = (program_: anchor.web3.PublicKey | null, min: number) =>
+      program.methods
+        .setRoleAttributeRequirement("kyc_trader", program_, new anchor.BN(min))
+        .accounts({ rbacState, role: findRole("kyc_trader"), admin: admin.publicKey })
+        .rpc();
+
+    const assignTrader = () =>
+      program.methods
+        .assignRole(trader.publicKey, "kyc_trader", null, null, null)
+        .accounts({
+          rbacState,
+          role: findRole("kyc_trader"),
+          userRole: findUserRole(trader.publicKey, "kyc_trader"),
+          authority: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: attributeAccount.publicKey, isSigner: false, isWritable: false },
+        ])
+        .rpc();
+
+    before(async () => {
+      await createRole("kyc_trader", PERM_READ | PERM_CREATE);
+      await setRequirement(identityProgram, 2);
+
+      // Mock identity record owned by the identity program; zeroed, so no user has a level yet
+      const space = 48;
+      const tx = new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.createAccount({
+          fromPubkey: admin.publicKey,
+          newAccountPubkey: attributeAccount.publicKey,
+          lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+          space,
+          programId: identityProgram,
+        })
+      );
+      await provider.sendAndConfirm(tx, [attributeAccount]);
+    });
+
+    it("Should reject assignment when the user's attribute is below the minimum", async () => {
+      await expectError(assignTrader(), "AttributeRequirementNotMet");
+      await expectError(assignRole(trader.publicKey, "kyc_trader"), "AttributeRequirementNotMet");
+    });
+
+    it("Should assign once the requirement is lifted", async () => {
+      await setRequirement(null, 0);
+      await assignTrader();
+      expect(await checkPermission(trader.publicKey, "kyc_trader", PERM_CREATE)).to.be.true;
+    });
+  });
+
+  describe("20. Expiring Assignment Report", () => {
+    const soon = anchor.web3.Keypair.generate();
+    const later = anchor.web3.Keypair.generate();
+    const never = anchor.web3.Keypair.generate();
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      await assignRole(soon.publicKey, "user", new anchor.BN(now + 3 * 86400));
+      await assignRole(later.publicKey, "user", new anchor.BN(now + 30 * 86400));
+      await assignRole(never.publicKey, "user");
+    });
+
+    it("Should only report assignments expiring inside the window", async () => {
+      const signature = await program.methods
+        .findExpiring(new anchor.BN(7 * 86400))
+        .accounts({ rbacState })
+        .remainingAccounts(
+          [soon, later, never].map((kp) => ({
+            pubkey: findUserRole(kp.publicKey, "user"),
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .rpc();
+
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("ExpiringAssignments");
+      expect(event.data.users.map((u: anchor.web3.PublicKey) => u.toBase58())).to.deep.equal([
+        soon.publicKey.toBase58(),
+      ]);
+    });
+  });
+
+  describe("21. Assignment Compliance Hook", () => {
+    const setHookProgram = (hook: anchor.web3.PublicKey | null) =>
+      program.methods
+        .setHookProgram(hook)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    const assignWithHook = (user: anchor.web3.PublicKey, hook: anchor.web3.PublicKey) =>
+      program.methods
+        .assignRole(user, "user", null, null, null)
+        .accounts({
+          rbacState,
+          role: findRole("user"),
+          userRole: findUserRole(user, "user"),
+          authority: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([{ pubkey: hook, isSigner: false, isWritable: false }])
+        .rpc();
+
+    after(async () => {
+      await setHookProgram(null);
+    });
+
+    it("Should fail the assignment when the hook vetoes it", async () => {
+      // The system program rejects the unknown on_role_assigned instruction,
+      // standing in for a compliance program that vetoes the grant.
+      const hook = anchor.web3.SystemProgram.programId;
+      await setHookProgram(hook);
+
+      const user = anchor.web3.Keypair.generate();
+      await expectError(assignWithHook(user.publicKey, hook), "Error");
+
+      const assignment = await provider.connection.getAccountInfo(findUserRole(user.publicKey, "user"));
+      expect(assignment).to.be.null;
+    });
+
+    it("Should require the hook program account when a hook is configured", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await expectError(assignRole(user.publicKey, "user"), "HookProgramMissing");
+    });
+
+    it("Should keep current behavior without a hook", async () => {
+      await setHookProgram(null);
+      const user = anchor.web3.Keypair.generate();
+      await assignRole(user.publicKey, "user");
+    });
+  });
+
+  describe("22. Org Admin Scoping", () => {
+    const orgAdmin = anchor.web3.Keypair.generate();
+    const orgAdminPda = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("org_admin"), orgAdmin.publicKey.toBuffer()],
+      program.programId
+    )[0];
+    // Org admins may only grant bits they hold, here through an editor assignment
+    const scope = [
+      { pubkey: orgAdminPda, isSigner: false, isWritable: false },
+      ...assignmentAccounts(orgAdmin.publicKey, "editor"),
+    ];
+
+    const createRoleAsOrgAdmin = (name: string) =>
+      program.methods
+        .createRole(name, PERM_READ | PERM_UPDATE, "", null, new anchor.BN(0), null, null, null, null)
+        .accounts({
+          rbacState,
+          role: findRole(name),
+          admin: orgAdmin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(scope)
+        .signers([orgAdmin])
+        .rpc();
+
+    before(async () => {
+      await airdrop(orgAdmin.publicKey);
+      await assignRole(orgAdmin.publicKey, "editor");
+      await program.methods
+        .setOrgAdmin(orgAdmin.publicKey, ["eng-"])
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+    });
+
+    it("Should let an org admin manage roles within its prefix", async () => {
+      await createRoleAsOrgAdmin("eng-lead");
+
+      const user = anchor.web3.Keypair.generate();
+      await program.methods
+        .assignRole(user.publicKey, "eng-lead", null, null, null)
+        .accounts({
+          rbacState,
+          role: findRole("eng-lead"),
+          userRole: findUserRole(user.publicKey, "eng-lead"),
+          authority: orgAdmin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(scope)
+        .signers([orgAdmin])
+        .rpc();
+
+      expect(await checkPermission(user.publicKey, "eng-lead", PERM_UPDATE)).to.be.true;
+    });
+
+    it("Should reject roles outside the org admin's prefix", async () => {
+      await expectError(createRoleAsOrgAdmin("fin-lead"), "OutsideAdminScope");
+    });
+
+    it("Should reject an org admin that omits its scope account", async () => {
+      await expectError(
+        program.methods
+          .createRole("eng-ops", PERM_READ, "", null, new anchor.BN(0), null, null, null, null)
+          .accounts({
+            rbacState,
+            role: findRole("eng-ops"),
+            admin: orgAdmin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([orgAdmin])
+          .rpc(),
+        "NotAuthorized"
+      );
+    });
+
+    it("Should keep the root admin unrestricted", async () => {
+      await createRole("fin-lead", PERM_READ);
+      const role = await program.account.role.fetch(findRole("fin-lead"));
+      expect(role.permissions).to.equal(PERM_READ);
+    });
+  });
+
+  describe("23. System Admin Short-Circuit", () => {
+    it("Should grant the admin any permission without a UserRole", async () => {
+      const allowed = await program.methods
+        .checkPermission(PERM_DELETE)
+        .accounts({ role: null, userRole: null, userStatus: findUserStatus(admin.publicKey) })
+        .view();
+      expect(allowed).to.be.true;
+
+      await program.methods
+        .assertHasPermission(PERM_DELETE | PERM_ADMIN)
+        .accounts({ role: null, userRole: null, userStatus: findUserStatus(admin.publicKey) })
+        .rpc();
+    });
+
+    it("Should still require an assignment for anyone else", async () => {
+      const outsider = anchor.web3.Keypair.generate();
+      await expectError(
+        program.methods
+          .assertHasPermission(PERM_READ)
+          .accounts({ role: null, userRole: null, caller: outsider.publicKey, userStatus: findUserStatus(outsider.publicKey) })
+          .signers([outsider])
+          .rpc(),
+        "MissingAssignment"
+      );
+    });
+  });
+
+  describe("24. Config Freeze", () => {
+    const auditee = anchor.web3.Keypair.generate();
+
+    const setConfigFrozen = (frozen: boolean) =>
+      program.methods
+        .setConfigFrozen(frozen)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    before(async () => {
+      await assignRole(auditee.publicKey, "editor");
+      await setConfigFrozen(true);
+    });
+
+    after(async () => {
+      await setConfigFrozen(false);
+    });
+
+    it("Should keep permission checks working while frozen", async () => {
+      expect(await checkPermission(auditee.publicKey, "editor", PERM_UPDATE)).to.be.true;
+    });
+
+    it("Should block role creation and assignment while frozen", async () => {
+      await expectError(createRole("frozen_role", PERM_READ), "ConfigFrozen");
+      await expectError(assignRole(auditee.publicKey, "user"), "ConfigFrozen");
+    });
+
+    it("Should block assignment controls while frozen", async () => {
+      await expectError(
+        program.methods
+          .denyPermission(auditee.publicKey, "editor", PERM_UPDATE)
+          .accounts({ rbacState, userRole: findUserRole(auditee.publicKey, "editor"), admin: admin.publicKey })
+          .rpc(),
+        "ConfigFrozen"
+      );
+    });
+
+    it("Should accept mutations again once unfrozen", async () => {
+      await setConfigFrozen(false);
+      await createRole("frozen_role", PERM_READ);
+    });
+  });
+
+  describe("25. Time Until Expiry", () => {
+    const NO_EXPIRY = new anchor.BN("9223372036854775807");
+
+    const timeUntilExpiry = (user: anchor.web3.PublicKey, name: string) =>
+      program.methods
+        .timeUntilExpiry(user, name)
+        .accounts({ userRole: findUserRole(user, name) })
+        .view();
+
+    it("Should return the seconds remaining for a future expiry", async () => {
+      const user = anchor.web3.Keypair.generate();
+      const now = Math.floor(Date.now() / 1000);
+      await assignRole(user.publicKey, "user", new anchor.BN(now + 3600));
+
+      const remaining = (await timeUntilExpiry(user.publicKey, "user")).toNumber();
+      expect(remaining).to.be.greaterThan(3500);
+      expect(remaining).to.be.at.most(3600);
+    });
+
+    it("Should return a negative value once expired", async () => {
+      const user = anchor.web3.Keypair.generate();
+      const now = Math.floor(Date.now() / 1000);
+      await assignRole(user.publicKey, "user", new anchor.BN(now - 60));
+
+      const remaining = (await timeUntilExpiry(user.publicKey, "user")).toNumber();
+      expect(remaining).to.be.lessThan(0);
+    });
+
+    it("Should return the sentinel for a permanent assignment", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await assignRole(user.publicKey, "user");
+
+      const remaining = await timeUntilExpiry(user.publicKey, "user");
+      expect(remaining.eq(NO_EXPIRY)).to.be.true;
+    });
+  });
+
+  describe("26. Permission Delegation", () => {
+    const delegator = anchor.web3.Keypair.generate();
+    const colleague = anchor.web3.Keypair.generate();
+    const token = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("delegation"),
+        delegator.publicKey.toBuffer(),
+        colleague.publicKey.toBuffer(),
+        Buffer.from("editor"),
+      ],
+      program.programId
+    )[0];
+
+    const assertDelegatedUpdate = () =>
+      program.methods
+        .assertHasPermission(PERM_UPDATE)
+        .accounts({ role: null, userRole: null, caller: colleague.publicKey, userStatus: findUserStatus(colleague.publicKey) })
+        .remainingAccounts([
+          { pubkey: token, isSigner: false, isWritable: false },
+          ...assignmentAccounts(delegator.publicKey, "editor"),
+          { pubkey: findUserStatus(delegator.publicKey), isSigner: false, isWritable: false },
+        ])
+        .signers([colleague])
+        .rpc();
+
+    before(async () => {
+      await airdrop(delegator.publicKey);
+      await assignRole(delegator.publicKey, "editor");
+      const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+      await program.methods
+        .delegatePermission(colleague.publicKey, "editor", PERM_UPDATE, expiresAt)
+        .accounts({
+          rbacState,
+          role: findRole("editor"),
+          delegatorRole: findUserRole(delegator.publicKey, "editor"),
+          token,
+          delegator: delegator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([delegator])
+        .rpc();
+    });
+
+    it("Should let the recipient act on a delegated permission", async () => {
+      await assertDelegatedUpdate();
+    });
+
+    it("Should refuse to delegate bits the delegator's role lacks", async () => {
+      const other = anchor.web3.Keypair.generate();
+      const otherToken = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("delegation"),
+          delegator.publicKey.toBuffer(),
+          other.publicKey.toBuffer(),
+          Buffer.from("editor"),
+        ],
+        program.programId
+      )[0];
+      const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+      await expectError(
+        program.methods
+          .delegatePermission(other.publicKey, "editor", PERM_DELETE, expiresAt)
+          .accounts({
+            rbacState,
+            role: findRole("editor"),
+            delegatorRole: findUserRole(delegator.publicKey, "editor"),
+            token: otherToken,
+            delegator: delegator.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([delegator])
+          .rpc(),
+        "DelegationExceedsRole"
+      );
+    });
+
+    it("Should deny the recipient while the delegator is frozen", async () => {
+      await program.methods.freezeUser(delegator.publicKey).accounts({ admin: admin.publicKey }).rpc();
+      await expectError(assertDelegatedUpdate(), "MissingAssignment");
+
+      await program.methods.unfreezeUser(delegator.publicKey).accounts({ admin: admin.publicKey }).rpc();
+      await assertDelegatedUpdate();
+    });
+
+    it("Should deny the recipient once the delegator loses the permission", async () => {
+      await program.methods
+        .revokeRole("editor", false)
+        .accounts({
+          rbacState,
+          role: findRole("editor"),
+          userRole: findUserRole(delegator.publicKey, "editor"),
+          authority: admin.publicKey,
+        })
+        .rpc();
+
+      await expectError(assertDelegatedUpdate(), "MissingAssignment");
+    });
+
+    it("Should let the delegator revoke the token", async () => {
+      await program.methods
+        .revokeDelegation(colleague.publicKey, "editor")
+        .accounts({ rbacState, token, delegator: delegator.publicKey })
+        .signers([delegator])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(token)).to.be.null;
+    });
+  });
+
+  describe("27. Bulk Suspension by Role", () => {
+    const holders = [1, 2, 3].map(() => anchor.web3.Keypair.generate().publicKey);
+    const holderAccounts = () =>
+      holders.map((holder) => ({
+        pubkey: findUserRole(holder, "oncall"),
+        isSigner: false,
+        isWritable: true,
+      }));
+
+    before(async () => {
+      await createRole("oncall", PERM_READ | PERM_UPDATE);
+      for (const holder of holders) {
+        await assignRole(holder, "oncall");
+      }
+    });
+
+    it("Should suspend every holder of a role in one call", async () => {
+      await program.methods
+        .suspendRoleHolders("oncall")
+        .accounts({ rbacState, role: findRole("oncall"), admin: admin.publicKey })
+        .remainingAccounts(holderAccounts())
+        .rpc();
+
+      for (const holder of holders) {
+        const assignment = await program.account.userRole.fetch(findUserRole(holder, "oncall"));
+        expect(assignment.suspended).to.be.true;
+        expect(await checkPermission(holder, "oncall", PERM_READ)).to.be.false;
+      }
+    });
+
+    it("Should restore access when unsuspended", async () => {
+      await program.methods
+        .unsuspendRoleHolders("oncall")
+        .accounts({ rbacState, role: findRole("oncall"), admin: admin.publicKey })
+        .remainingAccounts(holderAccounts())
+        .rpc();
+
+      for (const holder of holders) {
+        expect(await checkPermission(holder, "oncall", PERM_READ)).to.be.true;
+      }
+    });
+  });
+
+  describe("28. Layered Permission Evaluation", () => {
+    const evaluate = (user: anchor.web3.PublicKey, name: string, permission: number) =>
+      program.methods
+        .evaluatePermission(permission)
+        .accounts({ role: findRole(name), userRole: findUserRole(user, name), userStatus: findUserStatus(user) })
+        .view();
+
+    const suspend = (user: anchor.web3.PublicKey, name: string) =>
+      program.methods
+        .suspendRoleHolders(name)
+        .accounts({ rbacState, role: findRole(name), admin: admin.publicKey })
+        .remainingAccounts([
+          { pubkey: findUserRole(user, name), isSigner: false, isWritable: true },
+        ])
+        .rpc();
+
+    const pastExpiry = () => new anchor.BN(Math.floor(Date.now() / 1000) - 60);
+
+    it("Should report the system admin layer", async () => {
+      const evaluation = await program.methods
+        .evaluatePermission(PERM_ADMIN)
+        .accounts({ role: null, userRole: null, userStatus: findUserStatus(admin.publicKey) })
+        .view();
+      expect(evaluation.allowed).to.be.true;
+      expect(evaluation.reason).to.have.property("systemAdmin");
+    });
+
+    it("Should deny an expired assignment", async () => {
+      const user = anchor.web3.Keypair.generate().publicKey;
+      await assignRole(user, "oncall", pastExpiry());
+      const evaluation = await evaluate(user, "oncall", PERM_READ);
+      expect(evaluation.allowed).to.be.false;
+      expect(evaluation.reason).to.have.property("expired");
+    });
+
+    it("Should deny a suspended assignment", async () => {
+      const user = anchor.web3.Keypair.generate().publicKey;
+      await assignRole(user, "oncall");
+      await suspend(user, "oncall");
+      const evaluation = await evaluate(user, "oncall", PERM_READ);
+      expect(evaluation.allowed).to.be.false;
+      expect(evaluation.reason).to.have.property("suspended");
+    });
+
+    it("Should deny missing permission bits", async () => {
+      const user = anchor.web3.Keypair.generate().publicKey;
+      await assignRole(user, "oncall");
+      const evaluation = await evaluate(user, "oncall", PERM_DELETE);
+      expect(evaluation.allowed).to.be.false;
+      expect(evaluation.reason).to.have.property("insufficientPermissions");
+    });
+
+    it("Should apply expiry before suspension and suspension before bits", async () => {
+      const lapsed = anchor.web3.Keypair.generate().publicKey;
+      await assignRole(lapsed, "oncall", pastExpiry());
+      await suspend(lapsed, "oncall");
+      expect((await evaluate(lapsed, "oncall", PERM_DELETE)).reason).to.have.property("expired");
+
+      const suspended = anchor.web3.Keypair.generate().publicKey;
+      await assignRole(suspended, "oncall");
+      await suspend(suspended, "oncall");
+      expect((await evaluate(suspended, "oncall", PERM_DELETE)).reason).to.have.property("suspended");
+    });
+
+    it("Should apply the base role after the permission bits", async () => {
+      const user = anchor.web3.Keypair.generate().publicKey;
+      await assignRole(user, "oncall");
+      await program.methods
+        .setRequiredBaseRole("user")
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+      try {
+        expect((await evaluate(user, "oncall", PERM_DELETE)).reason).to.have.property(
+          "insufficientPermissions"
+        );
+        expect((await evaluate(user, "oncall", PERM_READ)).reason).to.have.property("missingBaseRole");
+      } finally {
+        await program.methods
+          .setRequiredBaseRole(null)
+          .accounts({ rbacState, admin: admin.publicKey })
+          .rpc();
+      }
+      expect((await evaluate(user, "oncall", PERM_READ)).reason).to.have.property("allowed");
+    });
+  });
+
+  describe("29. Merkle Role Claims", () => {
+    const sha256 = (...parts: Buffer[]) =>
+      createHash("sha256").update(Buffer.concat(parts)).digest();
+    // Matches the program's leaf encoding with a `None` expiry
+    const leafOf = (user: anchor.web3.PublicKey, name: string) =>
+      sha256(Buffer.from([0]), user.toBuffer(), Buffer.from(name), Buffer.from([0]));
+    const parentOf = (a: Buffer, b: Buffer) =>
+      Buffer.compare(a, b) <= 0
+        ? sha256(Buffer.from([1]), a, b)
+        : sha256(Buffer.from([1]), b, a);
+
+    const eligible = [1, 2, 3].map(() => anchor.web3.Keypair.generate());
+    const leaves = eligible.map((kp) => leafOf(kp.publicKey, "oncall"));
+    // Three leaves: (L0, L1) pair up and L2 is promoted to pair with their parent
+    const left = parentOf(leaves[0], leaves[1]);
+    const root = parentOf(left, leaves[2]);
+
+    const claimRoleClaim = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("role_claim"), Buffer.from("oncall")],
+      program.programId
+    )[0];
+
+    const claim = (claimant: anchor.web3.Keypair, proof: Buffer[]) =>
+      program.methods
+        .claimRoleMerkle("oncall", proof.map((node) => [...node]), null)
+        .accounts({
+          rbacState,
+          role: findRole("oncall"),
+          claim: claimRoleClaim,
+          userRole: findUserRole(claimant.publicKey, "oncall"),
+          claimant: claimant.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([claimant])
+        .rpc();
+
+    before(async () => {
+      await program.methods
+        .createRoleClaim("oncall", [...root])
+        .accounts({
+          rbacState,
+          role: findRole("oncall"),
+          claim: claimRoleClaim,
+          admin: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    });
+
+    it("Should let an eligible user claim the role with a valid proof", async () => {
+      const claimant = eligible[1];
+      await airdrop(claimant.publicKey);
+      await claim(claimant, [leaves[0], leaves[2]]);
+
+      const assignment = await program.account.userRole.fetch(
+        findUserRole(claimant.publicKey, "oncall")
+      );
+      expect(assignment.user.toBase58()).to.equal(claimant.publicKey.toBase58());
+      expect(await checkPermission(claimant.publicKey, "oncall", PERM_READ)).to.be.true;
+    });
+
+    it("Should reject a forged proof", async () => {
+      const outsider = anchor.web3.Keypair.generate();
+      await airdrop(outsider.publicKey);
+      await expectError(claim(outsider, [leaves[0], leaves[2]]), "InvalidProof");
+    });
+
+    it("Should not let a revoked claimant claim again with the same proof", async () => {
+      const claimant = eligible[1];
+      await program.methods
+        .revokeRole("oncall", false)
+        .accounts({
+          rbacState,
+          role: findRole("oncall"),
+          userRole: findUserRole(claimant.publicKey, "oncall"),
+          authority: admin.publicKey,
+        })
+        .rpc();
+
+      await expectError(claim(claimant, [leaves[0], leaves[2]]), "RoleAlreadyClaimed");
+      expect(await provider.connection.getAccountInfo(findUserRole(claimant.publicKey, "oncall"))).to.be.null;
+    });
+  });
+
+  describe("30. Assignment Acknowledgment", () => {
+    const approver = anchor.web3.Keypair.generate();
+
+    const assertApproverDelete = () =>
+      program.methods
+        .assertHasPermission(PERM_DELETE)
+        .accounts({
+          role: findRole("approver"),
+          userRole: findUserRole(approver.publicKey, "approver"),
+          userStatus: findUserStatus(approver.publicKey),
+        })
+        .rpc();
+
+    before(async () => {
+      await createRole("approver", PERM_READ | PERM_DELETE);
+      await program.methods
+        .setRoleAcknowledgment("approver", true)
+        .accounts({ rbacState, role: findRole("approver"), admin: admin.publicKey })
+        .rpc();
+      await assignRole(approver.publicKey, "approver", null, "SOX approver rotation");
+    });
+
+    it("Should deny an unacknowledged privileged assignment", async () => {
+      await expectError(assertApproverDelete(), "NotAcknowledged");
+      expect(await checkPermission(approver.publicKey, "approver", PERM_DELETE)).to.be.false;
+    });
+
+    it("Should activate the assignment once the user acknowledges it", async () => {
+      await program.methods
+        .acknowledgeRole("approver")
+        .accounts({
+          rbacState,
+          userRole: findUserRole(approver.publicKey, "approver"),
+          user: approver.publicKey,
+        })
+        .signers([approver])
+        .rpc();
+
+      await assertApproverDelete();
+    });
+  });
+
+  describe("31. Effective Access Description", () => {
+    const reviewee = anchor.web3.Keypair.generate();
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      await assignRole(reviewee.publicKey, "power_user");
+      await assignRole(reviewee.publicKey, "user", new anchor.BN(now + 86400));
+    });
+
+    it("Should describe composite and time-bound roles in one event", async () => {
+      const readOnly = (pubkey: anchor.web3.PublicKey) => ({
+        pubkey,
+        isSigner: false,
+        isWritable: false,
+      });
+      const signature = await program.methods
+        .describeAccess(reviewee.publicKey)
+        .accounts({ rbacState })
+        .remainingAccounts([
+          ...assignmentAccounts(reviewee.publicKey, "power_user"),
+          ...assignmentAccounts(reviewee.publicKey, "user"),
+          readOnly(findRole("editor")),
+          readOnly(findRole("publisher")),
+        ])
+        .rpc();
+
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("AccessDescription");
+      expect(event.data.roles).to.deep.equal(["power_user", "user"]);
+      // power_user inherits editor (R|C|U) and publisher (D)
+      expect(event.data.rolePermissions[0]).to.equal(
+        PERM_READ | PERM_CREATE | PERM_UPDATE | PERM_DELETE
+      );
+      expect(event.data.expiresAt[0]).to.be.null;
+      expect(event.data.expiresAt[1]).to.not.be.null;
+      expect(event.data.active).to.deep.equal([true, true]);
+      expect(event.data.effectivePermissions).to.equal(
+        PERM_READ | PERM_CREATE | PERM_UPDATE | PERM_DELETE
+      );
+    });
+  });
+
+  describe("32. Staged Role Updates", () => {
+    const pending = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pending_role_update"), Buffer.from("treasurer")],
+      program.programId
+    )[0];
+
+    const stage = (permissions: number) =>
+      program.methods
+        .stageRoleUpdate("treasurer", permissions)
+        .accounts({
+          rbacState,
+          role: findRole("treasurer"),
+          pending,
+          admin: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+    const resolveAccounts = () => ({
+      rbacState,
+      role: findRole("treasurer"),
+      pending,
+      stagedBy: admin.publicKey,
+      admin: admin.publicKey,
+    });
+
+    const livePermissions = async () =>
+      (await program.account.role.fetch(findRole("treasurer"))).permissions;
+
+    before(async () => {
+      await createRole("treasurer", PERM_READ | PERM_UPDATE);
+    });
+
+    it("Should preview the diff and leave the role untouched on discard", async () => {
+      await stage(PERM_READ | PERM_DELETE);
+
+      const diff = await program.methods
+        .previewRoleUpdate("treasurer")
+        .accounts({ role: findRole("treasurer"), pending })
+        .view();
+      expect(diff.current).to.equal(PERM_READ | PERM_UPDATE);
+      expect(diff.proposed).to.equal(PERM_READ | PERM_DELETE);
+      expect(diff.added).to.equal(PERM_DELETE);
+      expect(diff.removed).to.equal(PERM_UPDATE);
+
+      await program.methods.discardRoleUpdate("treasurer").accounts(resolveAccounts()).rpc();
+      expect(await livePermissions()).to.equal(PERM_READ | PERM_UPDATE);
+      expect(await provider.connection.getAccountInfo(pending)).to.be.null;
+    });
+
+    it("Should apply the staged permissions on commit", async () => {
+      await stage(PERM_READ | PERM_DELETE);
+      await program.methods.commitRoleUpdate("treasurer").accounts(resolveAccounts()).rpc();
+      expect(await livePermissions()).to.equal(PERM_READ | PERM_DELETE);
+    });
+  });
+
+  describe("33. Inherited Base Role", () => {
+    const deleter = anchor.web3.Keypair.generate();
+
+    const setBaseRole = (name: string | null) =>
+      program.methods
+        .setBaseRole(name)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    const checkRead = () =>
+      program.methods
+        .checkPermission(PERM_READ)
+        .accounts({
+          role: findRole("publisher"),
+          userRole: findUserRole(deleter.publicKey, "publisher"),
+          userStatus: findUserStatus(deleter.publicKey),
+        })
+        .remainingAccounts([
+          { pubkey: findRole("authenticated"), isSigner: false, isWritable: false },
+        ])
+        .view();
+
+    before(async () => {
+      await createRole("authenticated", PERM_READ);
+      await assignRole(deleter.publicKey, "publisher");
+    });
+
+    after(async () => {
+      await setBaseRole(null);
+    });
+
+    it("Should deny Read to a Delete-only role without a base role", async () => {
+      expect(await checkRead()).to.be.false;
+    });
+
+    it("Should let every assigned user inherit the base role's Read", async () => {
+      await setBaseRole("authenticated");
+      expect(await checkRead()).to.be.true;
+    });
+  });
+
+  describe("34. Privilege Escalation Guard", () => {
+    const manager = anchor.web3.Keypair.generate();
+    const managerScope = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("org_admin"), manager.publicKey.toBuffer()],
+      program.programId
+    )[0];
+
+    const createAsManager = (name: string, permissions: number) =>
+      program.methods
+        .createRole(name, permissions, "", null, new anchor.BN(0), null, null, null, null)
+        .accounts({
+          rbacState,
+          role: findRole(name),
+          admin: manager.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: managerScope, isSigner: false, isWritable: false },
+          ...assignmentAccounts(manager.publicKey, "editor"),
+        ])
+        .signers([manager])
+        .rpc();
+
+    before(async () => {
+      await airdrop(manager.publicKey);
+      await assignRole(manager.publicKey, "editor");
+      await program.methods
+        .setOrgAdmin(manager.publicKey, ["ops-"])
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+    });
+
+    it("Should let a manager grant permissions they hold", async () => {
+      await createAsManager("ops-writer", PERM_READ | PERM_UPDATE);
+    });
+
+    it("Should block a manager from granting Admin, which they lack", async () => {
+      await expectError(
+        createAsManager("ops-root", PERM_READ | PERM_ADMIN),
+        "CannotGrantUnheldPermission"
+      );
+    });
+
+    it("Should not count bits denied on the manager's own assignment", async () => {
+      const updateDenied = (method: "denyPermission" | "clearDeniedPermission") =>
+        program.methods[method](manager.publicKey, "editor", PERM_UPDATE)
+          .accounts({ rbacState, userRole: findUserRole(manager.publicKey, "editor"), admin: admin.publicKey })
+          .rpc();
+      await updateDenied("denyPermission");
+      try {
+        await expectError(createAsManager("ops-denied", PERM_READ | PERM_UPDATE), "CannotGrantUnheldPermission");
+      } finally {
+        await updateDenied("clearDeniedPermission");
+      }
+    });
+
+    it("Should not count a resource-scoped assignment towards global grants", async () => {
+      const scopedManager = anchor.web3.Keypair.generate();
+      const resource = anchor.web3.Keypair.generate().publicKey;
+      const scopedAssignment = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("user_role"), scopedManager.publicKey.toBuffer(), resource.toBuffer(), Buffer.from("editor")],
+        program.programId
+      )[0];
+      await airdrop(scopedManager.publicKey);
+      await program.methods
+        .setOrgAdmin(scopedManager.publicKey, ["ops-"])
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+      await program.methods
+        .assignRoleScoped(scopedManager.publicKey, resource, "editor", null, null)
+        .accounts({ rbacState, role: findRole("editor"), userRole: scopedAssignment, authority: admin.publicKey })
+        .rpc();
+
+      await expectError(
+        program.methods
+          .createRole("ops-scoped", PERM_READ, "", null, new anchor.BN(0), null, null, null, null)
+          .accounts({ rbacState, role: findRole("ops-scoped"), admin: scopedManager.publicKey })
+          .remainingAccounts([
+            {
+              pubkey: anchor.web3.PublicKey.findProgramAddressSync(
+                [Buffer.from("org_admin"), scopedManager.publicKey.toBuffer()],
+                program.programId
+              )[0],
+              isSigner: false,
+              isWritable: false,
+            },
+            { pubkey: scopedAssignment, isSigner: false, isWritable: false },
+            { pubkey: findRole("editor"), isSigner: false, isWritable: false },
+          ])
+          .signers([scopedManager])
+          .rpc(),
+        "CannotGrantUnheldPermission"
+      );
+    });
+
+    it("Should exempt the root admin", async () => {
+      await createRole("ops-root", PERM_READ | PERM_ADMIN);
+    });
+  });
+
+  describe("35. Assigner Stake", () => {
+    const STAKE = 0.1 * anchor.web3.LAMPORTS_PER_SOL;
+    const desk = anchor.web3.Keypair.generate();
+    const deskScope = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("org_admin"), desk.publicKey.toBuffer()],
+      program.programId
+    )[0];
+
+    const assignAsDesk = (user: anchor.web3.PublicKey) =>
+      program.methods
+        .assignRole(user, "bond-desk", null, null, null)
+        .accounts({
+          rbacState,
+          role: findRole("bond-desk"),
+          userRole: findUserRole(user, "bond-desk"),
+          authority: desk.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([{ pubkey: deskScope, isSigner: false, isWritable: false }])
+        .signers([desk])
+        .rpc();
+
+    const escrowed = async (user: anchor.web3.PublicKey) => {
+      const info = await provider.connection.getAccountInfo(findUserRole(user, "bond-desk"));
+      const rent = await provider.connection.getMinimumBalanceForRentExemption(info!.data.length);
+      return info!.lamports - rent;
+    };
+
+    before(async () => {
+      await airdrop(desk.publicKey);
+      await createRole("bond-desk", PERM_READ);
+      await program.methods
+        .setAssignerStake("bond-desk", new anchor.BN(STAKE))
+        .accounts({ rbacState, role: findRole("bond-desk"), admin: admin.publicKey })
+        .rpc();
+      await program.methods
+        .setOrgAdmin(desk.publicKey, ["bond-"])
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+    });
+
+    it("Should collect the stake from the assigner on assignment", async () => {
+      const user = anchor.web3.Keypair.generate().publicKey;
+      await assignAsDesk(user);
+
+      const assignment = await program.account.userRole.fetch(findUserRole(user, "bond-desk"));
+      expect(assignment.stake.toNumber()).to.equal(STAKE);
+      expect(assignment.stakedBy.toBase58()).to.equal(desk.publicKey.toBase58());
+      expect(await escrowed(user)).to.equal(STAKE);
+
+      const revoke = () =>
+        program.methods
+          .revokeRole("bond-desk", false)
+          .accounts({
+            rbacState,
+            role: findRole("bond-desk"),
+            userRole: findUserRole(user, "bond-desk"),
+            authority: admin.publicKey,
+          });
+      await expectError(revoke().rpc(), "StakeRecipientMissing");
+
+      const before = await provider.connection.getBalance(desk.publicKey);
+      await revoke()
+        .remainingAccounts([{ pubkey: desk.publicKey, isSigner: false, isWritable: true }])
+        .rpc();
+      const after = await provider.connection.getBalance(desk.publicKey);
+      expect(after - before).to.equal(STAKE);
+    });
+
+    it("Should let the admin slash an improper assignment's stake", async () => {
+      const user = anchor.web3.Keypair.generate().publicKey;
+      await assignAsDesk(user);
+
+      await program.methods
+        .slashAssigner(user, "bond-desk")
+        .accounts({
+          rbacState,
+          userRole: findUserRole(user, "bond-desk"),
+          admin: admin.publicKey,
+        })
+        .rpc();
+
+      const assignment = await program.account.userRole.fetch(findUserRole(user, "bond-desk"));
+      expect(assignment.stake.toNumber()).to.equal(0);
+      expect(await escrowed(user)).to.equal(0);
+    });
+  });
+
+  describe("36. Role Graph Export", () => {
+    it("Should emit each role with its composition edges", async () => {
+      const names = ["editor", "publisher", "power_user"];
+      const signature = await program.methods
+        .exportRoleGraph()
+        .accounts({ rbacState })
+        .remainingAccounts(
+          names.map((name) => ({ pubkey: findRole(name), isSigner: false, isWritable: false }))
+        )
+        .rpc();
+
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("RoleGraph");
+      expect(event.data.roles).to.deep.equal(names);
+      expect(event.data.composedOf).to.deep.equal([[], [], ["editor", "publisher"]]);
+    });
+  });
+
+  describe("37. Batch Permission Revocation", () => {
+    before(async () => {
+      await createRole("full_access", PERM_READ | PERM_CREATE | PERM_UPDATE | PERM_DELETE);
+    });
+
+    it("Should remove several permissions and skip ones not held", async () => {
+      const signature = await program.methods
+        .revokePermissions("full_access", PERM_CREATE | PERM_DELETE | PERM_ADMIN)
+        .accounts({ rbacState, role: findRole("full_access"), admin: admin.publicKey })
+        .rpc();
+
+      const role = await program.account.role.fetch(findRole("full_access"));
+      expect(role.permissions).to.equal(PERM_READ | PERM_UPDATE);
+
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("RolePermissionsRevoked");
+      expect(event.data.removed).to.equal(PERM_CREATE | PERM_DELETE);
+    });
+  });
+
+  describe("38. Assignable Permission Allowlist", () => {
+    const setAssignable = (mask: number) =>
+      program.methods
+        .setAssignablePermissions(mask)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    before(async () => {
+      await setAssignable(PERM_READ | PERM_CREATE | PERM_UPDATE | PERM_DELETE);
+    });
+
+    after(async () => {
+      await setAssignable(0);
+    });
+
+    it("Should refuse Admin on a normal role", async () => {
+      await expectError(createRole("wannabe_admin", PERM_READ | PERM_ADMIN), "PermissionNotAssignable");
+    });
+
+    it("Should still allow assignable permissions", async () => {
+      await createRole("reader_only", PERM_READ);
+      const role = await program.account.role.fetch(findRole("reader_only"));
+      expect(role.permissions).to.equal(PERM_READ);
+    });
+  });
+
+  describe("39. Check by Role Name", () => {
+    const checkByName = (name: string, permission: number, includeComposition = false) =>
+      program.methods
+        .checkByRoleName(name, permission, includeComposition)
+        .accounts({ role: findRole(name) })
+        .remainingAccounts(
+          includeComposition
+            ? ["editor", "publisher"].map((member) => ({
+                pubkey: findRole(member),
+                isSigner: false,
+                isWritable: false,
+              }))
+            : []
+        )
+        .view();
+
+    it("Should answer from the role alone", async () => {
+      expect(await checkByName("editor", PERM_UPDATE)).to.be.true;
+      expect(await checkByName("editor", PERM_ADMIN)).to.be.false;
+    });
+
+    it("Should include member roles when requested", async () => {
+      expect(await checkByName("power_user", PERM_DELETE)).to.be.false;
+      expect(await checkByName("power_user", PERM_DELETE, true)).to.be.true;
+    });
+  });
+
+  describe("40. Expiry Warnings", () => {
+    const expiring = anchor.web3.Keypair.generate();
+
+    const setWindow = (seconds: number) =>
+      program.methods
+        .setExpiryWarningWindow(new anchor.BN(seconds))
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    const emitWarnings = async () => {
+      const signature = await program.methods
+        .emitExpiryWarnings()
+        .accounts({ rbacState })
+        .remainingAccounts([
+          { pubkey: findUserRole(expiring.publicKey, "user"), isSigner: false, isWritable: true },
+        ])
+        .rpc();
+      return (await eventsOf(signature)).filter((event) => event.name === "ExpiryWarning");
+    };
+
+    before(async () => {
+      const now = Math.floor(Date.now() / 1000);
+      await assignRole(expiring.publicKey, "user", new anchor.BN(now + 3600));
+      await setWindow(86400);
+    });
+
+    after(async () => {
+      await setWindow(0);
+    });
+
+    it("Should warn once for an assignment inside the window", async () => {
+      const first = await emitWarnings();
+      expect(first).to.have.lengthOf(1);
+      expect(first[0].data.user.toBase58()).to.equal(expiring.publicKey.toBase58());
+
+      const second = await emitWarnings();
+      expect(second).to.have.lengthOf(0);
+    });
+  });
+
+  describe("41. Role Permission Updates", () => {
+    const holder = anchor.web3.Keypair.generate();
+
+    const updatePermissions = (permissions: number) =>
+      program.methods
+        .updateRolePermissions("retuned", permissions)
+        .accounts({ rbacState, role: findRole("retuned"), admin: admin.publicKey })
+        .rpc();
+
+    before(async () => {
+      await createRole("retuned", PERM_READ);
+      await assignRole(holder.publicKey, "retuned");
+    });
+
+    it("Should replace the permissions of an existing role in place", async () => {
+      expect(await checkPermission(holder.publicKey, "retuned", PERM_UPDATE)).to.be.false;
+
+      const signature = await updatePermissions(PERM_READ | PERM_UPDATE);
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("RolePermissionsUpdated");
+      expect(event.data.oldPermissions).to.equal(PERM_READ);
+      expect(event.data.newPermissions).to.equal(PERM_READ | PERM_UPDATE);
+
+      // The existing assignment sees the new bits without being recreated
+      expect(await checkPermission(holder.publicKey, "retuned", PERM_UPDATE)).to.be.true;
+    });
+
+    it("Should reject a non-admin signer", async () => {
+      const outsider = anchor.web3.Keypair.generate();
+      await expectError(
+        program.methods
+          .updateRolePermissions("retuned", PERM_ADMIN)
+          .accounts({ rbacState, role: findRole("retuned"), admin: outsider.publicKey })
+          .signers([outsider])
+          .rpc(),
+        "NotAuthorized"
+      );
+    });
+  });
+
+  describe("42. Jurisdiction-Tagged Assignments", () => {
+    const euTrader = anchor.web3.Keypair.generate();
+
+    const checkIn = (jurisdiction: string) =>
+      program.methods
+        .checkPermissionInJurisdiction(PERM_READ, jurisdiction)
+        .accounts({
+          role: findRole("user"),
+          userRole: findUserRole(euTrader.publicKey, "user"),
+          userStatus: findUserStatus(euTrader.publicKey),
+        })
+        .view();
+
+    before(async () => {
+      await assignRole(euTrader.publicKey, "user", null, null, "EU");
+    });
+
+    it("Should record the jurisdiction on the assignment", async () => {
+      const assignment = await program.account.userRole.fetch(findUserRole(euTrader.publicKey, "user"));
+      expect(assignment.jurisdiction).to.equal("EU");
+    });
+
+    it("Should pass a check in the assignment's jurisdiction", async () => {
+      expect(await checkIn("EU")).to.be.true;
+    });
+
+    it("Should fail a check in another jurisdiction", async () => {
+      await expectError(checkIn("US"), "JurisdictionMismatch");
+    });
+
+    it("Should reject an oversized jurisdiction tag", async () => {
+      const user = anchor.web3.Keypair.generate();
+      await expectError(
+        assignRole(user.publicKey, "user", null, null, "EUROPEAN_UNION"),
+        "JurisdictionTooLong"
+      );
+    });
+  });
+
+  describe("43. Co-Admins", () => {
+    const coAdmin = anchor.web3.Keypair.generate();
+
+    const addAdmin = (newAdmin: anchor.web3.PublicKey) =>
+      program.methods
+        .addAdmin(newAdmin)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    const removeAdmin = (target: anchor.web3.PublicKey, signer?: anchor.web3.Keypair) =>
+      program.methods
+        .removeAdmin(target)
+        .accounts({ rbacState, admin: signer ? signer.publicKey : admin.publicKey })
+        .signers(signer ? [signer] : [])
+        .rpc();
+
+    const createRoleAs = (signer: anchor.web3.Keypair, name: string) =>
+      program.methods
+        .createRole(name, PERM_READ, "", null, new anchor.BN(0), null, null, null, null)
+        .accounts({
+          rbacState,
+          role: findRole(name),
+          admin: signer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      await airdrop(coAdmin.publicKey);
+    });
+
+    it("Should refuse to remove the last remaining admin", async () => {
+      await expectError(removeAdmin(admin.publicKey), "CannotRemoveLastAdmin");
+    });
+
+    it("Should let a co-admin perform admin actions", async () => {
+      await addAdmin(coAdmin.publicKey);
+      const state = await program.account.rbacState.fetch(rbacState);
+      expect(state.admins.map((key) => key.toBase58())).to.include(coAdmin.publicKey.toBase58());
+      await createRoleAs(coAdmin, "co_admin_role");
+    });
+
+    it("Should reject adding an existing admin", async () => {
+      await expectError(addAdmin(coAdmin.publicKey), "AlreadyAdmin");
+    });
+
+    it("Should revoke admin powers once removed", async () => {
+      await removeAdmin(coAdmin.publicKey, coAdmin);
+      await expectError(createRoleAs(coAdmin, "co_admin_role2"), "NotAuthorized");
+      await expectError(removeAdmin(admin.publicKey), "CannotRemoveLastAdmin");
+    });
+  });
+
+  describe("44. Two-Step Admin Transfer", () => {
+    const successor = anchor.web3.Keypair.generate();
+    const impostor = anchor.web3.Keypair.generate();
+
+    const proposeAdmin = (newAdmin: anchor.web3.PublicKey, signer?: anchor.web3.Keypair) =>
+      program.methods
+        .proposeAdmin(newAdmin)
+        .accounts({ rbacState, admin: signer ? signer.publicKey : admin.publicKey })
+        .signers(signer ? [signer] : [])
+        .rpc();
+
+    const acceptAdmin = (signer?: anchor.web3.Keypair) =>
+      program.methods
+        .acceptAdmin()
+        .accounts({ rbacState, newAdmin: signer ? signer.publicKey : admin.publicKey })
+        .signers(signer ? [signer] : [])
+        .rpc();
+
+    before(async () => {
+      await airdrop(successor.publicKey);
+      await airdrop(impostor.publicKey);
+    });
+
+    it("Should let the master admin cancel a pending transfer", async () => {
+      await proposeAdmin(successor.publicKey);
+      await program.methods
+        .cancelAdminTransfer()
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+      const state = await program.account.rbacState.fetch(rbacState);
+      expect(state.pendingAdmin).to.be.null;
+      await expectError(acceptAdmin(successor), "NotAuthorized");
+    });
+
+    it("Should reject acceptance by a signer other than the pending admin", async () => {
+      await proposeAdmin(successor.publicKey);
+      await expectError(acceptAdmin(impostor), "NotAuthorized");
+    });
+
+    it("Should hand over the master admin role once accepted", async () => {
+      await acceptAdmin(successor);
+      const state = await program.account.rbacState.fetch(rbacState);
+      expect(state.admin.toBase58()).to.equal(successor.publicKey.toBase58());
+      expect(state.pendingAdmin).to.be.null;
+      expect(state.admins.map((key) => key.toBase58())).to.not.include(admin.publicKey.toBase58());
+    });
+
+    after(async () => {
+      // Hand the system back so later sections keep the provider wallet as admin
+      await proposeAdmin(admin.publicKey, successor);
+      await acceptAdmin();
+    });
+  });
+
+  describe("45. Pending Update Query", () => {
+    const pending = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("pending_role_update"), Buffer.from("treasurer")],
+      program.programId
+    )[0];
+
+    const hasPendingUpdate = () =>
+      program.methods
+        .hasPendingUpdate("treasurer")
+        .accounts({ role: findRole("treasurer"), pending })
+        .view();
+
+    it("Should report a staged update until it is committed", async () => {
+      expect(await hasPendingUpdate()).to.be.false;
+
+      await program.methods
+        .stageRoleUpdate("treasurer", PERM_READ)
+        .accounts({
+          rbacState,
+          role: findRole("treasurer"),
+          pending,
+          admin: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      expect(await hasPendingUpdate()).to.be.true;
+
+      await program.methods
+        .commitRoleUpdate("treasurer")
+        .accounts({
+          rbacState,
+          role: findRole("treasurer"),
+          pending,
+          stagedBy: admin.publicKey,
+          admin: admin.publicKey,
+        })
+        .rpc();
+      expect(await hasPendingUpdate()).to.be.false;
+    });
+  });
+
+  describe("46. Assignment Return Data", () => {
+    it("Should return the assignment's accounts and expiry as return data", async () => {
+      const user = anchor.web3.Keypair.generate();
+      const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 3600);
+      const signature = await assignRole(user.publicKey, "user", expiresAt);
+
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const returnData = tx.meta.returnData;
+      expect(returnData.programId).to.equal(program.programId.toBase58());
+
+      // AssignmentResult: user, role PDA, user_role PDA, Option<i64> expires_at
+      const data = Buffer.from(returnData.data[0], "base64");
+      expect(new anchor.web3.PublicKey(data.subarray(0, 32)).toBase58()).to.equal(user.publicKey.toBase58());
+      expect(new anchor.web3.PublicKey(data.subarray(32, 64)).toBase58()).to.equal(findRole("user").toBase58());
+      expect(new anchor.web3.PublicKey(data.subarray(64, 96)).toBase58()).to.equal(
+        findUserRole(user.publicKey, "user").toBase58()
+      );
+      expect(data[96]).to.equal(1);
+      expect(new anchor.BN(data.subarray(97, 105), "le").eq(expiresAt)).to.be.true;
+    });
+  });
+
+  describe("47. Assignment Authorization", () => {
+    it("Should reject assignments signed by a random wallet", async () => {
+      const outsider = anchor.web3.Keypair.generate();
+      await airdrop(outsider.publicKey);
+
+      await expectError(
+        program.methods
+          .assignRole(outsider.publicKey, "admin", null, null, null)
+          .accounts({
+            rbacState,
+            role: findRole("admin"),
+            userRole: findUserRole(outsider.publicKey, "admin"),
+            authority: outsider.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([outsider])
+          .rpc(),
+        "NotAuthorized"
+      );
+      expect(await provider.connection.getAccountInfo(findUserRole(outsider.publicKey, "admin"))).to.be.null;
+    });
+  });
+
+  describe("48. Expired Assignment Cleanup", () => {
+    const contractor = anchor.web3.Keypair.generate();
+
+    const cleanup = () =>
+      program.methods
+        .cleanupExpiredRole(contractor.publicKey, "user")
+        .accounts({
+          rbacState,
+          role: findRole("user"),
+          userRole: findUserRole(contractor.publicKey, "user"),
+          assignedBy: admin.publicKey,
+        })
+        .rpc();
+
+    before(async () => {
+      const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 2);
+      await assignRole(contractor.publicKey, "user", expiresAt);
+    });
+
+    it("Should keep the assignment valid and uncleanable before expiry", async () => {
+      expect(await checkPermission(contractor.publicKey, "user", PERM_READ)).to.be.true;
+      await expectError(cleanup(), "AssignmentNotExpired");
+    });
+
+    it("Should deny and allow closing the assignment after expiry", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      expect(await checkPermission(contractor.publicKey, "user", PERM_READ)).to.be.false;
+
+      const before = await provider.connection.getBalance(admin.publicKey);
+      await cleanup();
+      expect(await provider.connection.getAccountInfo(findUserRole(contractor.publicKey, "user"))).to.be.null;
+      // The refunded rent outweighs the fee of the cleanup transaction
+      expect(await provider.connection.getBalance(admin.publicKey)).to.be.greaterThan(before);
+    });
+  });
+
+  describe("49. Role Deletion", () => {
+    const holder = anchor.web3.Keypair.generate();
+
+    const deleteRole = () =>
+      program.methods
+        .deleteRole("short_lived")
+        .accounts({ rbacState, role: findRole("short_lived"), admin: admin.publicKey })
+        .rpc();
+
+    before(async () => {
+      await createRole("short_lived", PERM_READ);
+      await assignRole(holder.publicKey, "short_lived");
+    });
+
+    it("Should track live assignments on the role", async () => {
+      const role = await program.account.role.fetch(findRole("short_lived"));
+      expect(role.assignmentCount).to.equal(1);
+    });
+
+    it("Should refuse to delete a role that is still assigned", async () => {
+      await expectError(deleteRole(), "RoleInUse");
+    });
+
+    it("Should delete the role once its last assignment is revoked", async () => {
+      await program.methods
+        .revokeRole("short_lived", false)
+        .accounts({
+          rbacState,
+          role: findRole("short_lived"),
+          userRole: findUserRole(holder.publicKey, "short_lived"),
+          authority: admin.publicKey,
+        })
+        .rpc();
+
+      const before = await program.account.rbacState.fetch(rbacState);
+      await deleteRole();
+      const after = await program.account.rbacState.fetch(rbacState);
+
+      expect(after.roleCount).to.equal(before.roleCount - 1);
+      expect(await provider.connection.getAccountInfo(findRole("short_lived"))).to.be.null;
+    });
+  });
+
+  describe("50. Slot-Pinned Checks", () => {
+    const checkAtSlot = (asOfSlot: anchor.BN | null) =>
+      program.methods
+        .checkPermissionAtSlot(PERM_READ, asOfSlot)
+        .accounts({
+          role: findRole("editor"),
+          userRole: findUserRole(testUser.publicKey, "editor"),
+          userStatus: findUserStatus(testUser.publicKey),
+        })
+        .view();
+
+    before(async () => {
+      await assignRole(testUser.publicKey, "editor");
+    });
+
+    it("Should return the slot the check executed in", async () => {
+      const before = await provider.connection.getSlot("processed");
+      const result = await checkAtSlot(null);
+      const after = await provider.connection.getSlot("processed");
+
+      expect(result.allowed).to.be.true;
+      expect(result.slot.toNumber()).to.be.at.least(before);
+      expect(result.slot.toNumber()).to.be.at.most(after);
+    });
+
+    it("Should reject a check pinned to a past slot", async () => {
+      await expectError(checkAtSlot(new anchor.BN(0)), "StaleSlot");
+    });
+  });
+
+  describe("51. Disbursement Limits", () => {
+    const spender = anchor.web3.Keypair.generate();
+
+    const checkDisbursement = (amount: number) =>
+      program.methods
+        .checkDisbursement(PERM_UPDATE, new anchor.BN(amount))
+        .accounts({
+          role: findRole("payments"),
+          userRole: findUserRole(spender.publicKey, "payments"),
+          userStatus: findUserStatus(spender.publicKey),
+        })
+        .view();
+
+    before(async () => {
+      await createRole("payments", PERM_READ | PERM_UPDATE);
+      await program.methods
+        .setMaxDisbursement("payments", new anchor.BN(1000))
+        .accounts({ rbacState, role: findRole("payments"), admin: admin.publicKey })
+        .rpc();
+      await assignRole(spender.publicKey, "payments");
+    });
+
+    it("Should approve an amount within the role's cap", async () => {
+      expect(await checkDisbursement(500)).to.be.true;
+    });
+
+    it("Should reject an amount above the role's cap", async () => {
+      expect(await checkDisbursement(1500)).to.be.false;
+    });
+  });
+
+  describe("52. Assignment Counter", () => {
+    it("Should return the global assignment count to its prior value after a revoke", async () => {
+      const user = anchor.web3.Keypair.generate();
+      const before = (await program.account.rbacState.fetch(rbacState)).assignmentCount;
+
+      await assignRole(user.publicKey, "user");
+      expect((await program.account.rbacState.fetch(rbacState)).assignmentCount).to.equal(before + 1);
+
+      await program.methods
+        .revokeRole("user", false)
+        .accounts({
+          rbacState,
+          role: findRole("user"),
+          userRole: findUserRole(user.publicKey, "user"),
+          authority: admin.publicKey,
+        })
+        .rpc();
+      expect((await program.account.rbacState.fetch(rbacState)).assignmentCount).to.equal(before);
+    });
+  });
+
+  describe("53. Revocation Grace Period", () => {
+    const leaver = anchor.web3.Keypair.generate();
+
+    const setRevocationGrace = (seconds: number) =>
+      program.methods
+        .setRevocationGrace(new anchor.BN(seconds))
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    const finalize = () =>
+      program.methods
+        .finalizeRevocation(leaver.publicKey, "user")
+        .accounts({
+          rbacState,
+          userRole: findUserRole(leaver.publicKey, "user"),
+          assignedBy: admin.publicKey,
+        })
+        .rpc();
+
+    before(async () => {
+      await setRevocationGrace(2);
+      await assignRole(leaver.publicKey, "user");
+      await program.methods
+        .revokeRole("user", false)
+        .accounts({
+          rbacState,
+          role: findRole("user"),
+          userRole: findUserRole(leaver.publicKey, "user"),
+          authority: admin.publicKey,
+        })
+        .rpc();
+    });
+
+    after(async () => {
+      await setRevocationGrace(0);
+    });
+
+    it("Should deny a revoked assignment while keeping it inspectable", async () => {
+      expect(await checkPermission(leaver.publicKey, "user", PERM_READ)).to.be.false;
+      const assignment = await program.account.userRole.fetch(findUserRole(leaver.publicKey, "user"));
+      expect(assignment.revoked).to.be.true;
+      expect(assignment.role).to.equal("user");
+    });
+
+    it("Should refuse to finalize before the grace period elapses", async () => {
+      await expectError(finalize(), "GracePeriodActive");
+    });
+
+    it("Should close the assignment once the grace period has elapsed", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await finalize();
+      expect(await provider.connection.getAccountInfo(findUserRole(leaver.publicKey, "user"))).to.be.null;
+    });
+
+    it("Should route a revoked assignment of a deleted role through finalization, not close_orphan", async () => {
+      const orphan = anchor.web3.Keypair.generate();
+      await createRole("grace_orphan", PERM_READ);
+      await assignRole(orphan.publicKey, "grace_orphan");
+      await program.methods
+        .revokeRole("grace_orphan", false)
+        .accounts({
+          rbacState,
+          role: findRole("grace_orphan"),
+          userRole: findUserRole(orphan.publicKey, "grace_orphan"),
+          authority: admin.publicKey,
+        })
+        .rpc();
+      await program.methods
+        .deleteRole("grace_orphan")
+        .accounts({ rbacState, role: findRole("grace_orphan"), admin: admin.publicKey })
+        .rpc();
+
+      const { assignmentCount } = await program.account.rbacState.fetch(rbacState);
+      await expectError(
+        program.methods
+          .closeOrphan(orphan.publicKey)
+          .accounts({
+            rbacState,
+            role: findRole("grace_orphan"),
+            userRole: findUserRole(orphan.publicKey, "grace_orphan"),
+            assignedBy: admin.publicKey,
+          })
+          .rpc(),
+        "AssignmentRevoked"
+      );
+      expect((await program.account.rbacState.fetch(rbacState)).assignmentCount).to.equal(assignmentCount);
+
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await program.methods
+        .finalizeRevocation(orphan.publicKey, "grace_orphan")
+        .accounts({
+          rbacState,
+          userRole: findUserRole(orphan.publicKey, "grace_orphan"),
+          assignedBy: admin.publicKey,
+        })
+        .rpc();
+      expect(await provider.connection.getAccountInfo(findUserRole(orphan.publicKey, "grace_orphan"))).to.be.null;
+    });
+  });
+
+  describe("54. Lightweight Permission View", () => {
+    const hasPermission = (user: anchor.web3.PublicKey, permission: number) =>
+      program.methods
+        .hasPermission(user, permission)
+        .accounts({
+          role: findRole("editor"),
+          userRole: findUserRole(testUser.publicKey, "editor"),
+        })
+        .view();
+
+    it("Should report permissions held through the assignment", async () => {
+      expect(await hasPermission(testUser.publicKey, PERM_UPDATE)).to.be.true;
+      expect(await hasPermission(testUser.publicKey, PERM_DELETE)).to.be.false;
+    });
+
+    it("Should return false instead of failing for a mismatched user", async () => {
+      const stranger = anchor.web3.Keypair.generate();
+      expect(await hasPermission(stranger.publicKey, PERM_READ)).to.be.false;
+    });
+
+    it("Should not treat a resource-scoped assignment as a global grant", async () => {
+      const scopedHolder = anchor.web3.Keypair.generate();
+      const resource = anchor.web3.Keypair.generate().publicKey;
+      const scopedUserRole = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("user_role"), scopedHolder.publicKey.toBuffer(), resource.toBuffer(), Buffer.from("editor")],
+        program.programId
+      )[0];
+      await program.methods
+        .assignRoleScoped(scopedHolder.publicKey, resource, "editor", null, null)
+        .accounts({ rbacState, role: findRole("editor"), userRole: scopedUserRole, authority: admin.publicKey })
+        .rpc();
+
+      const allowed = await program.methods
+        .hasPermission(scopedHolder.publicKey, PERM_READ)
+        .accounts({ role: findRole("editor"), userRole: scopedUserRole })
+        .view();
+      expect(allowed).to.be.false;
+    });
+  });
+
+  describe("55. Assignments by Assigner", () => {
+    const manager = anchor.web3.Keypair.generate();
+    const users = [0, 1, 2].map(() => anchor.web3.Keypair.generate());
+
+    const assignAs = (signer: anchor.web3.Keypair, user: anchor.web3.PublicKey) =>
+      program.methods
+        .assignRole(user, "user", null, null, null)
+        .accounts({
+          rbacState,
+          role: findRole("user"),
+          userRole: findUserRole(user, "user"),
+          authority: signer.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    before(async () => {
+      await airdrop(manager.publicKey);
+      await program.methods.addAdmin(manager.publicKey).accounts({ rbacState, admin: admin.publicKey }).rpc();
+      await assignAs(manager, users[0].publicKey);
+      await assignAs(manager, users[1].publicKey);
+      await assignRole(users[2].publicKey, "user");
+      await program.methods.removeAdmin(manager.publicKey).accounts({ rbacState, admin: admin.publicKey }).rpc();
+    });
+
+    it("Should only report assignments made by the target assigner", async () => {
+      const signature = await program.methods
+        .findByAssigner(manager.publicKey)
+        .accounts({ rbacState })
+        .remainingAccounts(
+          users.map((kp) => ({
+            pubkey: findUserRole(kp.publicKey, "user"),
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .rpc();
+
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("AssignmentsByAssigner");
+      expect(event.data.users.map((u: anchor.web3.PublicKey) => u.toBase58())).to.deep.equal([
+        users[0].publicKey.toBase58(),
+        users[1].publicKey.toBase58(),
+      ]);
+    });
+  });
+
+  describe("56. Role Hierarchy", () => {
+    const manager = anchor.web3.Keypair.generate();
+    const roleAccount = (name: string) => ({ pubkey: findRole(name), isSigner: false, isWritable: false });
+
+    const setRoleParent = (name: string, parent: string | null, ancestors: string[]) =>
+      program.methods
+        .setRoleParent(name, parent)
+        .accounts({ rbacState, role: findRole(name), admin: admin.publicKey })
+        .remainingAccounts(ancestors.map(roleAccount))
+        .rpc();
+
+    before(async () => {
+      await createRole("dept_manager", PERM_DELETE);
+      await setRoleParent("dept_manager", "editor", ["editor"]);
+      await assignRole(manager.publicKey, "dept_manager");
+    });
+
+    it("Should inherit the parent role's permissions at check time", async () => {
+      const allowed = await program.methods
+        .checkPermission(PERM_UPDATE | PERM_DELETE)
+        .accounts({
+          role: findRole("dept_manager"),
+          userRole: findUserRole(manager.publicKey, "dept_manager"),
+          userStatus: findUserStatus(manager.publicKey),
+        })
+        .remainingAccounts([roleAccount("editor")])
+        .view();
+      expect(allowed).to.be.true;
+    });
+
+    it("Should reject a parent that would create a cycle", async () => {
+      await expectError(setRoleParent("editor", "dept_manager", ["dept_manager", "editor"]), "CyclicHierarchy");
+    });
+  });
+
+  describe("57. Late-Bound Permission Bundles", () => {
+    const member = anchor.web3.Keypair.generate();
+
+    const checkBundled = (permission: number) =>
+      program.methods
+        .checkPermission(permission)
+        .accounts({
+          role: findRole("bundled_role"),
+          userRole: findUserRole(member.publicKey, "bundled_role"),
+          userStatus: findUserStatus(member.publicKey),
+        })
+        .remainingAccounts([{ pubkey: findRole("bundle"), isSigner: false, isWritable: false }])
+        .view();
+
+    before(async () => {
+      await createRole("bundle", PERM_READ);
+      await createRole("bundled_role", 0);
+      await program.methods
+        .setRoleComposition("bundled_role", ["bundle"])
+        .accounts({ rbacState, role: findRole("bundled_role"), admin: admin.publicKey })
+        .rpc();
+      await assignRole(member.publicKey, "bundled_role");
+    });
+
+    it("Should apply edits to a referenced bundle immediately", async () => {
+      expect(await checkBundled(PERM_UPDATE)).to.be.false;
+
+      await program.methods
+        .updateRolePermissions("bundle", PERM_READ | PERM_UPDATE)
+        .accounts({ rbacState, role: findRole("bundle"), admin: admin.publicKey })
+        .rpc();
+
+      expect(await checkBundled(PERM_UPDATE)).to.be.true;
+    });
+  });
+
+  describe("58. Admin Implies All Permissions", () => {
+    const operator = anchor.web3.Keypair.generate();
+    const reader = anchor.web3.Keypair.generate();
+    const allBits = [PERM_READ, PERM_CREATE, PERM_UPDATE, PERM_DELETE, PERM_ADMIN];
+
+    before(async () => {
+      await createRole("admin_only", PERM_ADMIN);
+      await assignRole(operator.publicKey, "admin_only");
+      await assignRole(reader.publicKey, "user");
+    });
+
+    it("Should let an Admin-only role pass every permission check", async () => {
+      for (const bit of allBits) {
+        expect(await checkPermission(operator.publicKey, "admin_only", bit)).to.be.true;
+      }
+    });
+
+    it("Should keep a Read-only role limited to reading", async () => {
+      expect(await checkPermission(reader.publicKey, "user", PERM_READ)).to.be.true;
+      for (const bit of allBits.slice(1)) {
+        expect(await checkPermission(reader.publicKey, "user", bit)).to.be.false;
+      }
+    });
+  });
+
+  describe("59. Required Co-Roles", () => {
+    const staff = anchor.web3.Keypair.generate();
+    const contractor = anchor.web3.Keypair.generate();
+
+    const assignApprover = (user: anchor.web3.PublicKey) =>
+      program.methods
+        .assignRole(user, "invoice_approver", null, null, null)
+        .accounts({
+          rbacState,
+          role: findRole("invoice_approver"),
+          userRole: findUserRole(user, "invoice_approver"),
+          authority: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: findUserRole(user, "employee"), isSigner: false, isWritable: false },
+        ])
+        .rpc();
+
+    before(async () => {
+      await createRole("employee", PERM_READ);
+      await createRole("invoice_approver", PERM_UPDATE);
+      await program.methods
+        .setRequiredCoRole("invoice_approver", "employee")
+        .accounts({ rbacState, role: findRole("invoice_approver"), admin: admin.publicKey })
+        .rpc();
+      await assignRole(staff.publicKey, "employee");
+    });
+
+    it("Should reject a user who lacks the required co-role", async () => {
+      await expectError(assignApprover(contractor.publicKey), "MissingCoRole");
+    });
+
+    it("Should assign a user who holds the required co-role", async () => {
+      await assignApprover(staff.publicKey);
+      expect(await checkPermission(staff.publicKey, "invoice_approver", PERM_UPDATE)).to.be.true;
+    });
+  });
+
+  describe("60. In-Place Reassignment", () => {
+    const mover = anchor.web3.Keypair.generate();
+
+    before(async () => {
+      await assignRole(mover.publicKey, "editor");
+    });
+
+    it("Should move the user to the new role in one transaction", async () => {
+      const signature = await program.methods
+        .reassignRole(mover.publicKey, "editor", "user", false)
+        .accounts({
+          rbacState,
+          oldRole: findRole("editor"),
+          newRole: findRole("user"),
+          oldUserRole: findUserRole(mover.publicKey, "editor"),
+          newUserRole: findUserRole(mover.publicKey, "user"),
+          authority: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("RoleReassigned");
+      expect(event.data.oldRole).to.equal("editor");
+      expect(event.data.newRole).to.equal("user");
+
+      expect(await provider.connection.getAccountInfo(findUserRole(mover.publicKey, "editor"))).to.be.null;
+      expect(await checkPermission(mover.publicKey, "user", PERM_READ)).to.be.true;
+      expect(await checkPermission(mover.publicKey, "user", PERM_UPDATE)).to.be.false;
+    });
+  });
+
+  describe("61. Projected Effective Permissions", () => {
+    const temp = anchor.web3.Keypair.generate();
+    const now = Math.floor(Date.now() / 1000);
+
+    const permissionsAt = (timestamp: number) =>
+      program.methods
+        .effectivePermissionsAt(new anchor.BN(timestamp))
+        .accounts({
+          role: findRole("publisher"),
+          userRole: findUserRole(temp.publicKey, "publisher"),
+          userStatus: findUserStatus(temp.publicKey),
+        })
+        .view();
+
+    before(async () => {
+      await assignRole(temp.publicKey, "publisher", new anchor.BN(now + 3600));
+    });
+
+    it("Should include permissions held until the assignment expires", async () => {
+      expect((await permissionsAt(now + 60)) & PERM_DELETE).to.equal(PERM_DELETE);
+    });
+
+    it("Should project no permissions past the assignment's expiry", async () => {
+      expect(await permissionsAt(now + 2 * 3600)).to.equal(0);
+    });
+  });
+
+  describe("62. Emergency Pause", () => {
+    const guardian = anchor.web3.Keypair.generate();
+    const bystander = anchor.web3.Keypair.generate();
+    const compromised = anchor.web3.Keypair.generate();
+
+    before(async () => {
+      await airdrop(guardian.publicKey);
+      await program.methods
+        .setGuardian(guardian.publicKey)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+      await assignRole(bystander.publicKey, "user");
+      await createRole("paused_incident", PERM_READ);
+      await assignRole(compromised.publicKey, "paused_incident");
+      await assignRole(compromised.publicKey, "user");
+    });
+
+    it("Should let the guardian pause privileged instructions", async () => {
+      await program.methods
+        .pause()
+        .accounts({ rbacState, admin: guardian.publicKey })
+        .signers([guardian])
+        .rpc();
+
+      await expectError(createRole("paused_role", PERM_READ), "SystemPaused");
+      await expectError(assignRole(anchor.web3.Keypair.generate().publicKey, "user"), "SystemPaused");
+      await expectError(
+        program.methods
+          .revokeRole("user", false)
+          .accounts({
+            rbacState,
+            role: findRole("user"),
+            userRole: findUserRole(bystander.publicKey, "user"),
+            authority: admin.publicKey,
+          })
+          .rpc(),
+        "SystemPaused"
+      );
+    });
+
+    it("Should still let the guardian disable a role and the admin freeze a user", async () => {
+      await program.methods
+        .emergencyDisableRole("paused_incident")
+        .accounts({ rbacState, role: findRole("paused_incident"), admin: guardian.publicKey })
+        .signers([guardian])
+        .rpc();
+      expect(await checkPermission(compromised.publicKey, "paused_incident", PERM_READ)).to.be.false;
+
+      await program.methods.freezeUser(compromised.publicKey).accounts({ admin: admin.publicKey }).rpc();
+      expect(await checkPermission(compromised.publicKey, "user", PERM_READ)).to.be.false;
+    });
+
+    it("Should keep permission checks working while paused", async () => {
+      expect(await checkPermission(bystander.publicKey, "user", PERM_READ)).to.be.true;
+    });
+
+    it("Should not let the guardian unpause", async () => {
+      await expectError(
+        program.methods
+          .unpause()
+          .accounts({ rbacState, admin: guardian.publicKey })
+          .signers([guardian])
+          .rpc(),
+        "NotAuthorized"
+      );
+    });
+
+    it("Should accept privileged instructions again after the admin unpauses", async () => {
+      await program.methods.unpause().accounts({ rbacState, admin: admin.publicKey }).rpc();
+      await createRole("paused_role", PERM_READ);
+    });
+  });
+
+  describe("63. Role Quorum", () => {
+    const quorumRoles = ["quorum_a", "quorum_b", "quorum_c"];
+    const twoRoles = anchor.web3.Keypair.generate();
+    const oneRole = anchor.web3.Keypair.generate();
+
+    const assertQuorum = (user: anchor.web3.PublicKey) =>
+      program.methods
+        .assertRoleQuorum(user, PERM_UPDATE, quorumRoles, 2)
+        .accounts({ rbacState })
+        .remainingAccounts(quorumRoles.flatMap((name) => assignmentAccounts(user, name)))
+        .rpc();
+
+    before(async () => {
+      for (const name of quorumRoles) {
+        await createRole(name, PERM_UPDATE);
+      }
+      await assignRole(twoRoles.publicKey, "quorum_a");
+      await assignRole(twoRoles.publicKey, "quorum_b");
+      await assignRole(oneRole.publicKey, "quorum_a");
+    });
+
+    it("Should pass when the user holds the threshold of required roles", async () => {
+      await assertQuorum(twoRoles.publicKey);
+    });
+
+    it("Should fail when the user holds fewer roles than the threshold", async () => {
+      await expectError(assertQuorum(oneRole.publicKey), "QuorumNotMet");
+    });
+  });
+
+  describe("64. Assignment Fingerprints", () => {
+    const holder = anchor.web3.Keypair.generate();
+    const holderRole = () => findUserRole(holder.publicKey, "user");
+
+    // Mirrors `UserRole::compute_fingerprint`
+    const fingerprintOf = (assignment: any) => {
+      const noExpiry = new anchor.BN("9223372036854775807");
+      const u32 = (value: number) => {
+        const buffer = Buffer.alloc(4);
+        buffer.writeUInt32LE(value);
+        return buffer;
+      };
+      return createHash("sha256")
+        .update(Buffer.concat([
+          assignment.user.toBuffer(),
+          Buffer.from(assignment.role),
+          assignment.assignedAt.toArrayLike(Buffer, "le", 8),
+          (assignment.expiresAt ?? noExpiry).toArrayLike(Buffer, "le", 8),
+          assignment.assignedBy.toBuffer(),
+          assignment.resource.toBuffer(),
+          u32(assignment.deniedPermissions),
+          u32(assignment.tempPermission ?? 0),
+          assignment.tempExpiresAt.toArrayLike(Buffer, "le", 8),
+          Buffer.from([assignment.suspended ? 1 : 0]),
+          ...assignment.signerAllowlist.map((signer: anchor.web3.PublicKey) => signer.toBuffer()),
+        ]))
+        .digest();
+    };
+
+    const verify = () =>
+      program.methods
+        .verifyAssignmentIntegrity(holder.publicKey, "user")
+        .accounts({ userRole: holderRole() })
+        .view();
+
+    before(async () => {
+      await assignRole(holder.publicKey, "user");
+    });
+
+    it("Should store a fingerprint of the assignment's checked fields", async () => {
+      const assignment = await program.account.userRole.fetch(holderRole());
+      expect(Buffer.from(assignment.fingerprint).equals(fingerprintOf(assignment))).to.be.true;
+    });
+
+    it("Should verify an untampered assignment", async () => {
+      expect(await verify()).to.be.true;
+    });
+
+    it("Should cover the restrictions and grants layered on the assignment", async () => {
+      const before = await program.account.userRole.fetch(holderRole());
+      await program.methods
+        .denyPermission(holder.publicKey, "user", PERM_DELETE)
+        .accounts({ rbacState, userRole: holderRole(), admin: admin.publicKey })
+        .rpc();
+      await program.methods
+        .setSignerAllowlist(holder.publicKey, "user", [admin.publicKey])
+        .accounts({ rbacState, userRole: holderRole(), admin: admin.publicKey })
+        .rpc();
+
+      const after = await program.account.userRole.fetch(holderRole());
+      expect(Buffer.from(after.fingerprint).equals(Buffer.from(before.fingerprint))).to.be.false;
+      expect(Buffer.from(after.fingerprint).equals(fingerprintOf(after))).to.be.true;
+      expect(await verify()).to.be.true;
+    });
+  });
+
+  describe("65. Role Registry", () => {
+    const roleRegistry = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("role_registry")],
+      program.programId
+    )[0];
+    const names = ["registry_a", "registry_b", "registry_c"];
+
+    const getRoles = (start: number, limit: number) =>
+      program.methods.getRoles(start, limit).accounts({ roleRegistry }).view();
+
+    const setMaxRoles = (maxRoles: number) =>
+      program.methods
+        .setMaxRoles(maxRoles)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    before(async () => {
+      for (const name of names) {
+        await createRole(name, PERM_READ);
+      }
+    });
+
+    it("Should register every created role and drop deleted ones", async () => {
+      const registry = await program.account.roleRegistry.fetch(roleRegistry);
+      expect(registry.names).to.include.members(["admin", "user", ...names]);
+      expect(registry.names).to.not.include("short_lived");
+    });
+
+    it("Should read the registry in pages", async () => {
+      const registry = await program.account.roleRegistry.fetch(roleRegistry);
+      const start = registry.names.indexOf(names[0]);
+
+      expect(await getRoles(start, 2)).to.deep.equal(names.slice(0, 2));
+      expect(await getRoles(start + 2, 2)).to.deep.equal(names.slice(2));
+    });
+
+    it("Should refuse new roles beyond max_roles", async () => {
+      const state = await program.account.rbacState.fetch(rbacState);
+      await setMaxRoles(state.roleCount);
+      try {
+        await expectError(createRole("registry_over_cap", PERM_READ), "TooManyRoles");
+      } finally {
+        await setMaxRoles(0);
+      }
+    });
+  });
+
+  describe("66. Admin Transfer History", () => {
+    const successor = anchor.web3.Keypair.generate();
+    const adminHistory = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("admin_history")],
+      program.programId
+    )[0];
+
+    // A missing keypair stands for the provider wallet, which signs implicitly
+    const handOver = async (from: anchor.web3.Keypair | null, to: anchor.web3.Keypair | null) => {
+      await program.methods
+        .proposeAdmin(to ? to.publicKey : admin.publicKey)
+        .accounts({ rbacState, admin: from ? from.publicKey : admin.publicKey })
+        .signers(from ? [from] : [])
+        .rpc();
+      await program.methods
+        .acceptAdmin()
+        .accounts({ rbacState, adminHistory, newAdmin: to ? to.publicKey : admin.publicKey })
+        .signers(to ? [to] : [])
+        .rpc();
+    };
+
+    before(async () => {
+      await airdrop(successor.publicKey);
+    });
+
+    it("Should record each accepted transfer in order", async () => {
+      await handOver(null, successor);
+      await handOver(successor, null);
+
+      const history = await program.methods
+        .getAdminHistory()
+        .accounts({ adminHistory })
+        .view();
+      const lastTwo = history.slice(-2).map((entry: any) => [
+        entry.oldAdmin.toBase58(),
+        entry.newAdmin.toBase58(),
+      ]);
+      expect(lastTwo).to.deep.equal([
+        [admin.publicKey.toBase58(), successor.publicKey.toBase58()],
+        [successor.publicKey.toBase58(), admin.publicKey.toBase58()],
+      ]);
+      expect(history.length).to.be.at.most(8);
+    });
+  });
+
+  describe("67. Role Managers", () => {
+    const manager = anchor.web3.Keypair.generate();
+    const employee = anchor.web3.Keypair.generate();
+    const findRoleManager = (key: anchor.web3.PublicKey, name: string) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("role_manager"), key.toBuffer(), Buffer.from(name)],
+        program.programId
+      )[0];
+
+    const assignAsManager = (name: string) =>
+      program.methods
+        .assignRole(employee.publicKey, name, null, null, null)
+        .accounts({
+          rbacState,
+          role: findRole(name),
+          userRole: findUserRole(employee.publicKey, name),
+          authority: manager.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([
+          { pubkey: findRoleManager(manager.publicKey, "hr_employee"), isSigner: false, isWritable: false },
+        ])
+        .signers([manager])
+        .rpc();
+
+    before(async () => {
+      await airdrop(manager.publicKey);
+      await createRole("hr_employee", PERM_READ);
+      await createRole("hr_contractor", PERM_READ);
+      await program.methods
+        .grantAssigner(manager.publicKey, "hr_employee")
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+    });
+
+    it("Should let a manager assign their delegated role", async () => {
+      await assignAsManager("hr_employee");
+      const assignment = await program.account.userRole.fetch(findUserRole(employee.publicKey, "hr_employee"));
+      expect(assignment.assignedBy.toBase58()).to.equal(manager.publicKey.toBase58());
+    });
+
+    it("Should reject a manager assigning any other role", async () => {
+      await expectError(assignAsManager("hr_contractor"), "NotAuthorized");
+    });
+
+    it("Should refuse to delegate the admin role", async () => {
+      await expectError(
+        program.methods
+          .grantAssigner(manager.publicKey, "admin")
+          .accounts({ rbacState, admin: admin.publicKey })
+          .rpc(),
+        "AdminRoleNotDelegable"
+      );
+    });
+
+    it("Should stop a manager once their authority is revoked", async () => {
+      await program.methods
+        .revokeAssigner(manager.publicKey, "hr_employee")
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+      await expectError(assignAsManager("hr_employee"), "NotAuthorized");
+    });
+  });
+
+  describe("68. Unified Role Graph Resolution", () => {
+    const roleAccount = (name: string) => ({ pubkey: findRole(name), isSigner: false, isWritable: false });
+
+    before(async () => {
+      await createRole("graph_root", PERM_CREATE);
+      await createRole("graph_mid", PERM_UPDATE);
+      await createRole("graph_leaf", PERM_READ);
+      await createRole("graph_bundle", PERM_DELETE);
+      await program.methods
+        .setRoleParent("graph_mid", "graph_root")
+        .accounts({ rbacState, role: findRole("graph_mid"), admin: admin.publicKey })
+        .remainingAccounts([roleAccount("graph_root")])
+        .rpc();
+      await program.methods
+        .setRoleParent("graph_leaf", "graph_mid")
+        .accounts({ rbacState, role: findRole("graph_leaf"), admin: admin.publicKey })
+        .remainingAccounts(["graph_mid", "graph_root"].map(roleAccount))
+        .rpc();
+      // graph_mid is reachable both as a parent and as a member
+      await program.methods
+        .setRoleComposition("graph_leaf", ["graph_bundle", "graph_mid"])
+        .accounts({ rbacState, role: findRole("graph_leaf"), admin: admin.publicKey })
+        .rpc();
+    });
+
+    it("Should merge two-level inheritance with composed members", async () => {
+      const permissions = await program.methods
+        .resolveEffectivePermissions("graph_leaf")
+        .accounts({ role: findRole("graph_leaf") })
+        .remainingAccounts(["graph_mid", "graph_root", "graph_bundle"].map(roleAccount))
+        .view();
+      expect(permissions).to.equal(PERM_READ | PERM_UPDATE | PERM_CREATE | PERM_DELETE);
+    });
+
+    it("Should skip roles that were not supplied", async () => {
+      const permissions = await program.methods
+        .resolveEffectivePermissions("graph_leaf")
+        .accounts({ role: findRole("graph_leaf") })
+        .remainingAccounts(["graph_mid"].map(roleAccount))
+        .view();
+      expect(permissions).to.equal(PERM_READ | PERM_UPDATE);
+    });
+  });
+
+  describe("69. Role Name Validation", () => {
+    it("Should reject an empty role name", async () => {
+      await expectError(createRole("", PERM_READ), "EmptyRoleName");
+    });
+
+    it("Should reject a whitespace-padded role name", async () => {
+      await expectError(createRole(" padded ", PERM_READ), "NonCanonicalRoleName");
+    });
+
+    it("Should reject a case variant of an existing role", async () => {
+      await expectError(createRole("Admin", PERM_READ), "NonCanonicalRoleName");
+      expect(await provider.connection.getAccountInfo(findRole("Admin"))).to.be.null;
+    });
+
+    it("Should reject a multibyte name within 32 characters but over 32 bytes", async () => {
+      const name = "é".repeat(20); // 20 characters, 40 bytes
+      // No valid PDA exists for a 40-byte seed, so pass any address for the role
+      await expectError(
+        program.methods
+          .createRole(name, PERM_READ, "", null, new anchor.BN(0), null, null, null, null)
+          .accounts({
+            rbacState,
+            role: anchor.web3.Keypair.generate().publicKey,
+            admin: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc(),
+        "RoleNameTooLong"
+      );
+    });
+  });
+
+  describe("70. Text Logs", () => {
+    const holder = anchor.web3.Keypair.generate();
+
+    const setTextLogs = (enabled: boolean) =>
+      program.methods
+        .setTextLogs(enabled)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    it("Should log a key=value line for assign_role when enabled", async () => {
+      await setTextLogs(true);
+      try {
+        const signature = await assignRole(holder.publicKey, "user");
+        const tx = await provider.connection.getTransaction(signature, {
+          commitment: "confirmed",
+          maxSupportedTransactionVersion: 0,
+        });
+        const line = tx.meta.logMessages.find((log) => log.startsWith("Program log: evt=role_assigned"));
+        expect(line).to.match(
+          new RegExp(`^Program log: evt=role_assigned user=${holder.publicKey.toBase58()} role=user by=${admin.publicKey.toBase58()} seq=\\d+$`)
+        );
+      } finally {
+        await setTextLogs(false);
+      }
+    });
+  });
+
+  describe("71. On-Chain Audit Log", () => {
+    const capacity = 64;
+    const pageSize = 12;
+    const users = Array.from({ length: capacity + 2 }, () => anchor.web3.Keypair.generate().publicKey);
+    const auditLog = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("audit_log")],
+      program.programId
+    )[0];
+
+    const readAll = async () => {
+      const entries = [];
+      for (let start = 0; start < capacity; start += pageSize) {
+        entries.push(...(await program.methods.readAuditLog(start, pageSize).accounts({ auditLog }).view()));
+      }
+      return entries;
+    };
+
+    before(async () => {
+      for (const user of users) {
+        await assignRole(user, "user");
+      }
+    });
+
+    it("Should keep only the newest entries once the buffer wraps", async () => {
+      const entries = await readAll();
+      expect(entries.length).to.equal(capacity);
+      expect(entries.map((entry) => entry.target.toBase58())).to.deep.equal(
+        users.slice(2).map((user) => user.toBase58())
+      );
+      expect(entries.every((entry) => entry.actionKind === 0)).to.be.true;
+    });
+
+    it("Should append revocations after the assignments they follow", async () => {
+      await program.methods
+        .revokeRole("user", false)
+        .accounts({
+          rbacState,
+          role: findRole("user"),
+          userRole: findUserRole(users[capacity + 1], "user"),
+          authority: admin.publicKey,
+        })
+        .rpc();
+
+      const entries = await readAll();
+      const newest = entries[entries.length - 1];
+      expect(newest.actionKind).to.equal(1);
+      expect(newest.target.toBase58()).to.equal(users[capacity + 1].toBase58());
+      expect(entries[entries.length - 2].target.toBase58()).to.equal(users[capacity + 1].toBase58());
+      expect(entries[0].target.toBase58()).to.equal(users[3].toBase58());
+    });
+  });
+
+  describe("72. Role User Count", () => {
+    const members = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+
+    const userCount = () =>
+      program.methods.roleUserCount("headcount").accounts({ role: findRole("headcount") }).view();
+
+    before(async () => {
+      await createRole("headcount", PERM_READ);
+      for (const member of members) {
+        await assignRole(member.publicKey, "headcount");
+      }
+    });
+
+    it("Should count every assigned user", async () => {
+      expect(await userCount()).to.equal(2);
+    });
+
+    it("Should drop the count on revoke", async () => {
+      await program.methods
+        .revokeRole("headcount", false)
+        .accounts({
+          rbacState,
+          role: findRole("headcount"),
+          userRole: findUserRole(members[0].publicKey, "headcount"),
+          authority: admin.publicKey,
+        })
+        .rpc();
+      expect(await userCount()).to.equal(1);
+    });
+  });
+
+  describe("73. Assignment Change Cooldown", () => {
+    const patient = anchor.web3.Keypair.generate();
+    const urgent = anchor.web3.Keypair.generate();
+
+    const setChangeCooldown = (seconds: number) =>
+      program.methods
+        .setChangeCooldown(new anchor.BN(seconds))
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    const revoke = (user: anchor.web3.PublicKey, bypassCooldown: boolean) =>
+      program.methods
+        .revokeRole("user", bypassCooldown)
+        .accounts({
+          rbacState,
+          role: findRole("user"),
+          userRole: findUserRole(user, "user"),
+          authority: admin.publicKey,
+        })
+        .rpc();
+
+    before(async () => {
+      await setChangeCooldown(2);
+      await assignRole(patient.publicKey, "user");
+      await assignRole(urgent.publicKey, "user");
+    });
+
+    after(async () => {
+      await setChangeCooldown(0);
+    });
+
+    it("Should reject a revoke within the cooldown window", async () => {
+      await expectError(revoke(patient.publicKey, false), "ChangeCooldownActive");
+    });
+
+    it("Should allow the revoke once the cooldown has passed", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      await revoke(patient.publicKey, false);
+      expect(await provider.connection.getAccountInfo(findUserRole(patient.publicKey, "user"))).to.be.null;
+    });
+
+    it("Should let an admin bypass the cooldown", async () => {
+      await assignRole(urgent.publicKey, "user");
+      await revoke(urgent.publicKey, true);
+      expect(await provider.connection.getAccountInfo(findUserRole(urgent.publicKey, "user"))).to.be.null;
+    });
+  });
+
+  describe("74. Conditional Permission Grants", () => {
+    const auditor = anchor.web3.Keypair.generate();
+    const newcomer = anchor.web3.Keypair.generate();
+
+    before(async () => {
+      await createRole("cond_auditor", PERM_READ);
+      const state = await program.account.rbacState.fetch(rbacState);
+      // Our own assignment below adds one; the newcomer's crosses the threshold
+      await program.methods
+        .setConditionalGrants("cond_auditor", [{ permissions: PERM_DELETE, threshold: state.assignmentCount + 2 }])
+        .accounts({ rbacState, role: findRole("cond_auditor"), admin: admin.publicKey })
+        .rpc();
+      await assignRole(auditor.publicKey, "cond_auditor");
+    });
+
+    it("Should deny the conditional permission below the threshold", async () => {
+      expect(await checkPermission(auditor.publicKey, "cond_auditor", PERM_READ)).to.be.true;
+      expect(await checkPermission(auditor.publicKey, "cond_auditor", PERM_DELETE)).to.be.false;
+    });
+
+    it("Should grant it once the assignment count crosses the threshold", async () => {
+      await assignRole(newcomer.publicKey, "user");
+      expect(await checkPermission(auditor.publicKey, "cond_auditor", PERM_DELETE)).to.be.true;
+    });
+  });
+
+  describe("75. Resource-Scoped Assignments", () => {
+    const scopedUser = anchor.web3.Keypair.generate();
+    const globalUser = anchor.web3.Keypair.generate();
+    const resourceA = anchor.web3.Keypair.generate().publicKey;
+    const resourceB = anchor.web3.Keypair.generate().publicKey;
+    const scopedUserRole = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("user_role"), scopedUser.publicKey.toBuffer(), resourceA.toBuffer(), Buffer.from("user")],
+      program.programId
+    )[0];
+
+    const checkScoped = (resource: anchor.web3.PublicKey) =>
+      program.methods
+        .checkPermissionScoped(resource, PERM_READ)
+        .accounts({ role: findRole("user"), userRole: scopedUserRole, userStatus: findUserStatus(scopedUser.publicKey) })
+        .view();
+
+    before(async () => {
+      await program.methods
+        .assignRoleScoped(scopedUser.publicKey, resourceA, "user", null, null)
+        .accounts({
+          rbacState,
+          role: findRole("user"),
+          userRole: scopedUserRole,
+          authority: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      await assignRole(globalUser.publicKey, "user");
+    });
+
+    it("Should grant on the resource the assignment is scoped to", async () => {
+      expect(await checkScoped(resourceA)).to.be.true;
+    });
+
+    it("Should deny on any other resource", async () => {
+      expect(await checkScoped(resourceB)).to.be.false;
+    });
+
+    it("Should not act as a global assignment", async () => {
+      const allowed = await program.methods
+        .checkPermission(PERM_READ)
+        .accounts({ role: findRole("user"), userRole: scopedUserRole, userStatus: findUserStatus(scopedUser.publicKey) })
+        .view();
+      expect(allowed).to.be.false;
+    });
+
+    it("Should let global assignments grant on every resource", async () => {
+      const allowed = await program.methods
+        .checkPermissionScoped(resourceB, PERM_READ)
+        .accounts({ role: findRole("user"), userRole: findUserRole(globalUser.publicKey, "user"), userStatus: findUserStatus(globalUser.publicKey) })
+        .view();
+      expect(allowed).to.be.true;
+    });
+
+    it("Should close the scoped assignment on revoke and audit it", async () => {
+      await program.methods
+        .revokeRoleScoped(resourceA, "user", false)
+        .accounts({ rbacState, role: findRole("user"), userRole: scopedUserRole, authority: admin.publicKey })
+        .rpc();
+      expect(await provider.connection.getAccountInfo(scopedUserRole)).to.be.null;
+
+      // The log is full by now, so the newest entry is the last of 64
+      const auditLog = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("audit_log")],
+        program.programId
+      )[0];
+      const [newest] = await program.methods.readAuditLog(63, 1).accounts({ auditLog }).view();
+      expect(newest.actionKind).to.equal(1);
+      expect(newest.target.toBase58()).to.equal(scopedUser.publicKey.toBase58());
+    });
+  });
+
+  describe("76. Self-Sufficient Events", () => {
+    const holder = anchor.web3.Keypair.generate();
+
+    it("Should carry the role's permissions and the slot in RoleAssigned", async () => {
+      const signature = await assignRole(holder.publicKey, "user");
+      const events = await eventsOf(signature);
+      const assigned = events.find((event) => event.name === "RoleAssigned");
+      const { slot } = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+
+      expect(assigned.data.permissions).to.equal(PERM_READ);
+      expect(assigned.data.slot.toNumber()).to.equal(slot);
+    });
+  });
+
+  describe("77. Role Activation", () => {
+    const holder = anchor.web3.Keypair.generate();
+
+    const setActive = (active: boolean) =>
+      program.methods
+        .setRoleActive("toggled", active)
+        .accounts({ rbacState, role: findRole("toggled"), admin: admin.publicKey })
+        .rpc();
+
+    before(async () => {
+      await createRole("toggled", PERM_READ, "Temporarily suspendable reader");
+      await assignRole(holder.publicKey, "toggled");
+    });
+
+    it("Should store the description and start active", async () => {
+      const role = await program.account.role.fetch(findRole("toggled"));
+      expect(role.description).to.equal("Temporarily suspendable reader");
+      expect(role.active).to.be.true;
+      expect(await checkPermission(holder.publicKey, "toggled", PERM_READ)).to.be.true;
+    });
+
+    it("Should reject an overlong description", async () => {
+      await expectError(createRole("verbose", PERM_READ, "x".repeat(129)), "DescriptionTooLong");
+    });
+
+    it("Should deny a previously-valid user once the role is disabled", async () => {
+      await setActive(false);
+      expect(await checkPermission(holder.publicKey, "toggled", PERM_READ)).to.be.false;
+      await expectError(
+        program.methods
+          .assertHasPermission(PERM_READ)
+          .accounts({ role: findRole("toggled"), userRole: findUserRole(holder.publicKey, "toggled"), userStatus: findUserStatus(holder.publicKey) })
+          .rpc(),
+        "RoleDisabled"
+      );
+    });
+
+    it("Should grant again once re-enabled", async () => {
+      await setActive(true);
+      expect(await checkPermission(holder.publicKey, "toggled", PERM_READ)).to.be.true;
+    });
+  });
+
+  describe("78. Batch Permission Checks", () => {
+    const holder = anchor.web3.Keypair.generate();
+    const batch = (permissions: number[]) =>
+      program.methods
+        .batchCheckPermissions(permissions)
+        .accounts({ role: findRole("batch_editor"), userRole: findUserRole(holder.publicKey, "batch_editor"), userStatus: findUserStatus(holder.publicKey) })
+        .view();
+
+    before(async () => {
+      await createRole("batch_editor", PERM_READ | PERM_UPDATE);
+      await assignRole(holder.publicKey, "batch_editor");
+    });
+
+    it("Should answer every permission in one call", async () => {
+      const results = await batch([PERM_READ, PERM_CREATE, PERM_UPDATE, PERM_DELETE, PERM_ADMIN]);
+      expect(results).to.deep.equal([true, false, true, false, false]);
+    });
+
+    it("Should reject an oversized batch", async () => {
+      await expectError(batch(new Array(17).fill(PERM_READ)), "TooManyPermissions");
+    });
+  });
+
+  describe("79. Revocation Authority", () => {
+    const holder = anchor.web3.Keypair.generate();
+    const stranger = anchor.web3.Keypair.generate();
+
+    before(async () => {
+      await airdrop(stranger.publicKey);
+      await assignRole(holder.publicKey, "user");
+    });
+
+    it("Should stop an unrelated signer from revoking and claiming the rent", async () => {
+      const userRole = findUserRole(holder.publicKey, "user");
+      await expectError(
+        program.methods
+          .revokeRole("user", false)
+          .accounts({ rbacState, role: findRole("user"), userRole, authority: stranger.publicKey })
+          .signers([stranger])
+          .rpc(),
+        "NotAuthorized"
+      );
+      expect(await provider.connection.getAccountInfo(userRole)).to.not.be.null;
+    });
+  });
+
+  describe("80. Token-Gated Assignment", () => {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const holder = anchor.web3.Keypair.generate();
+    const pauper = anchor.web3.Keypair.generate();
+    let mint: anchor.web3.PublicKey;
+
+    const tokenAccountOf = async (owner: anchor.web3.PublicKey, amount: number) => {
+      const account = await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, owner);
+      if (amount > 0) {
+        await mintTo(provider.connection, payer, mint, account.address, payer, amount);
+      }
+      return account.address;
+    };
+
+    const assignGated = (user: anchor.web3.PublicKey, tokenAccount: anchor.web3.PublicKey) =>
+      program.methods
+        .assignRole(user, "token_holder", null, null, null)
+        .accounts({
+          rbacState,
+          role: findRole("token_holder"),
+          userRole: findUserRole(user, "token_holder"),
+          authority: admin.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([{ pubkey: tokenAccount, isSigner: false, isWritable: false }])
+        .rpc();
+
+    before(async () => {
+      mint = await createMint(provider.connection, payer, admin.publicKey, null, 0);
+      await createRole("token_holder", PERM_READ, "", mint, 10);
+    });
+
+    it("Should assign a user holding enough of the required token", async () => {
+      await assignGated(holder.publicKey, await tokenAccountOf(holder.publicKey, 10));
+      expect(await checkPermission(holder.publicKey, "token_holder", PERM_READ)).to.be.true;
+    });
+
+    it("Should refuse a user below the minimum balance", async () => {
+      const tokenAccount = await tokenAccountOf(pauper.publicKey, 9);
+      await expectError(assignGated(pauper.publicKey, tokenAccount), "TokenGateNotMet");
+    });
+
+    it("Should refuse when the token account is not supplied", async () => {
+      await expectError(assignRole(pauper.publicKey, "token_holder"), "TokenGateNotMet");
+    });
+  });
+
+  describe("81. Admin Quorum Proposals", () => {
+    const coAdmins = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+
+    const findProposal = (id: anchor.BN) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("proposal"), id.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+
+    const setThreshold = (threshold: number) =>
+      program.methods.setAdminThreshold(threshold).accounts({ rbacState, admin: admin.publicKey }).rpc();
+
+    const execute = (id: anchor.BN) =>
+      program.methods
+        .executeProposal(id)
+        .accounts({
+          rbacState,
+          proposal: findProposal(id),
+          proposer: admin.publicKey,
+          role: findRole("quorum_admin"),
+          admin: admin.publicKey,
+        })
+        .rpc();
+
+    before(async () => {
+      for (const coAdmin of coAdmins) {
+        await program.methods.addAdmin(coAdmin.publicKey).accounts({ rbacState, admin: admin.publicKey }).rpc();
+      }
+      await setThreshold(2);
+    });
+
+    after(async () => {
+      await setThreshold(0);
+      for (const coAdmin of coAdmins) {
+        await program.methods.removeAdmin(coAdmin.publicKey).accounts({ rbacState, admin: admin.publicKey }).rpc();
+      }
+    });
+
+    it("Should refuse to create an admin-level role without a proposal", async () => {
+      await expectError(createRole("quorum_admin", PERM_ADMIN), "ProposalRequired");
+    });
+
+    describe("bypass paths", () => {
+      const plainRole = findRole("quorum_plain");
+      const pending = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("pending_role_update"), Buffer.from("quorum_plain")],
+        program.programId
+      )[0];
+
+      before(async () => {
+        await createRole("quorum_plain", PERM_READ);
+      });
+
+      it("Should refuse to add the Admin bit to an existing role", async () => {
+        await expectError(
+          program.methods
+            .updateRolePermissions("quorum_plain", PERM_READ | PERM_ADMIN)
+            .accounts({ rbacState, role: plainRole, admin: admin.publicKey })
+            .rpc(),
+          "ProposalRequired"
+        );
+      });
+
+      it("Should refuse to commit a staged update that adds the Admin bit", async () => {
+        await program.methods
+          .stageRoleUpdate("quorum_plain", PERM_READ | PERM_ADMIN)
+          .accounts({
+            rbacState,
+            role: plainRole,
+            pending,
+            admin: admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        await expectError(
+          program.methods
+            .commitRoleUpdate("quorum_plain")
+            .accounts({ rbacState, role: plainRole, pending, stagedBy: admin.publicKey, admin: admin.publicKey })
+            .rpc(),
+          "ProposalRequired"
+        );
+        await program.methods
+          .discardRoleUpdate("quorum_plain")
+          .accounts({ rbacState, role: plainRole, pending, stagedBy: admin.publicKey, admin: admin.publicKey })
+          .rpc();
+      });
+
+      it("Should refuse a conditional grant of the Admin bit", async () => {
+        await expectError(
+          program.methods
+            .setConditionalGrants("quorum_plain", [{ permissions: PERM_ADMIN, threshold: 0 }])
+            .accounts({ rbacState, role: plainRole, admin: admin.publicKey })
+            .rpc(),
+          "ProposalRequired"
+        );
+      });
+
+      it("Should refuse to rename a role", async () => {
+        await expectError(
+          program.methods
+            .renameRole("quorum_plain", "quorum_plain_2")
+            .accountsPartial({ oldRole: plainRole, newRole: findRole("quorum_plain_2"), admin: admin.publicKey })
+            .rpc(),
+          "ProposalRequired"
+        );
+      });
+    });
+
+    it("Should execute only once two of three admins have approved", async () => {
+      const { proposalCount: id } = await program.account.rbacState.fetch(rbacState);
+      await program.methods
+        .proposeAction({ createRole: {} }, "quorum_admin", PERM_ADMIN)
+        .accounts({ rbacState, proposal: findProposal(id), admin: admin.publicKey })
+        .rpc();
+      await expectError(execute(id), "ApprovalsBelowThreshold");
+
+      await program.methods
+        .approveAction(id)
+        .accounts({ rbacState, proposal: findProposal(id), admin: coAdmins[0].publicKey })
+        .signers([coAdmins[0]])
+        .rpc();
+      await execute(id);
+
+      const role = await program.account.role.fetch(findRole("quorum_admin"));
+      expect(role.permissions).to.equal(PERM_ADMIN);
+      expect(await provider.connection.getAccountInfo(findProposal(id))).to.be.null;
+    });
+
+    it("Should reject a duplicate approval from the same admin", async () => {
+      const { proposalCount: id } = await program.account.rbacState.fetch(rbacState);
+      await program.methods
+        .proposeAction({ deleteRole: {} }, "quorum_admin", 0)
+        .accounts({ rbacState, proposal: findProposal(id), admin: admin.publicKey })
+        .rpc();
+      await expectError(
+        program.methods
+          .approveAction(id)
+          .accounts({ rbacState, proposal: findProposal(id), admin: admin.publicKey })
+          .rpc(),
+        "AlreadyApproved"
+      );
+    });
+  });
+
+  describe("82. User Role Index", () => {
+    const holder = anchor.web3.Keypair.generate();
+    const findUserIndex = (user: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("user_index"), user.toBuffer()],
+        program.programId
+      )[0];
+    const getUserRoles = () =>
+      program.methods
+        .getUserRoles(holder.publicKey)
+        .accounts({ userIndex: findUserIndex(holder.publicKey) })
+        .view();
+
+    before(async () => {
+      await createRole("index_reader", PERM_READ);
+      await assignRole(holder.publicKey, "user");
+      await assignRole(holder.publicKey, "index_reader");
+    });
+
+    it("Should list every role the user holds", async () => {
+      expect(await getUserRoles()).to.deep.equal(["user", "index_reader"]);
+    });
+
+    it("Should drop a revoked role and shrink the index", async () => {
+      const before = (await provider.connection.getAccountInfo(findUserIndex(holder.publicKey))).data.length;
+      await program.methods
+        .revokeRole("user", false)
+        .accounts({
+          rbacState,
+          role: findRole("user"),
+          userRole: findUserRole(holder.publicKey, "user"),
+          authority: admin.publicKey,
+        })
+        .rpc();
+
+      expect(await getUserRoles()).to.deep.equal(["index_reader"]);
+      const after = (await provider.connection.getAccountInfo(findUserIndex(holder.publicKey))).data.length;
+      expect(after).to.equal(before - 4 - "user".length);
+    });
+  });
+
+  describe("83. Denied Permissions", () => {
+    const holder = anchor.web3.Keypair.generate();
+    const updateDenied = (method: "denyPermission" | "clearDeniedPermission", permission: number) =>
+      program.methods[method](holder.publicKey, "deny_editor", permission)
+        .accounts({ rbacState, userRole: findUserRole(holder.publicKey, "deny_editor"), admin: admin.publicKey })
+        .rpc();
+
+    before(async () => {
+      await createRole("deny_editor", PERM_READ | PERM_UPDATE | PERM_DELETE | PERM_ADMIN);
+      await assignRole(holder.publicKey, "deny_editor");
+    });
+
+    it("Should deny a carved-out permission the role otherwise grants", async () => {
+      expect(await checkPermission(holder.publicKey, "deny_editor", PERM_DELETE)).to.be.true;
+      await updateDenied("denyPermission", PERM_DELETE);
+      expect(await checkPermission(holder.publicKey, "deny_editor", PERM_DELETE)).to.be.false;
+      expect(await checkPermission(holder.publicKey, "deny_editor", PERM_UPDATE)).to.be.true;
+    });
+
+    it("Should take precedence over the Admin bit's implication", async () => {
+      // PERM_CREATE is only granted through the Admin bit here
+      expect(await checkPermission(holder.publicKey, "deny_editor", PERM_CREATE)).to.be.true;
+      await updateDenied("denyPermission", PERM_ADMIN);
+      expect(await checkPermission(holder.publicKey, "deny_editor", PERM_CREATE)).to.be.false;
+      expect(await checkPermission(holder.publicKey, "deny_editor", PERM_READ)).to.be.true;
+    });
+
+    it("Should restore the permission once the deny is cleared", async () => {
+      await updateDenied("clearDeniedPermission", PERM_DELETE | PERM_ADMIN);
+      expect(await checkPermission(holder.publicKey, "deny_editor", PERM_DELETE)).to.be.true;
+      const userRole = await program.account.userRole.fetch(findUserRole(holder.publicKey, "deny_editor"));
+      expect(userRole.deniedPermissions).to.equal(0);
+    });
+  });
+
+  describe("84. Account Versioning", () => {
+    const stranger = anchor.web3.Keypair.generate();
+    const migrate = (target: anchor.web3.PublicKey, signer?: anchor.web3.Keypair) =>
+      program.methods
+        .migrateAccount()
+        .accounts({ target, admin: signer ? signer.publicKey : admin.publicKey })
+        .signers(signer ? [signer] : [])
+        .rpc();
+
+    before(async () => {
+      await airdrop(stranger.publicKey);
+    });
+
+    it("Should stamp new accounts with the current layout version", async () => {
+      expect((await program.account.rbacState.fetch(rbacState)).version).to.be.greaterThan(0);
+      expect((await program.account.role.fetch(findRole("user"))).version).to.be.greaterThan(0);
+    });
+
+    it("Should refuse to migrate an account already at the current version", async () => {
+      await expectError(migrate(findRole("user")), "AccountUpToDate");
+    });
+
+    it("Should only let the admin migrate", async () => {
+      await expectError(migrate(rbacState, stranger), "NotAuthorized");
+    });
+  });
+
+  describe("85. Permission Groups", () => {
+    const holder = anchor.web3.Keypair.generate();
+    const findGroup = (name: string) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("perm_group"), Buffer.from(name)],
+        program.programId
+      )[0];
+    const checkThroughGroup = (permission: number) =>
+      program.methods
+        .checkPermission(permission)
+        .accounts({ role: findRole("grouped_editor"), userRole: findUserRole(holder.publicKey, "grouped_editor"), userStatus: findUserStatus(holder.publicKey) })
+        .remainingAccounts([{ pubkey: findGroup("content_ops"), isSigner: false, isWritable: false }])
+        .view();
+
+    before(async () => {
+      await program.methods
+        .createPermissionGroup("content_ops", PERM_CREATE | PERM_UPDATE)
+        .accounts({ rbacState, group: findGroup("content_ops"), admin: admin.publicKey })
+        .rpc();
+      await createRole("grouped_editor", PERM_READ, "", null, 0, "content_ops");
+      await assignRole(holder.publicKey, "grouped_editor");
+    });
+
+    it("Should grant the referenced group's permissions", async () => {
+      expect(await checkThroughGroup(PERM_UPDATE)).to.be.true;
+      expect(await checkThroughGroup(PERM_DELETE)).to.be.false;
+    });
+
+    it("Should propagate group changes to referencing roles", async () => {
+      await program.methods
+        .updatePermissionGroup("content_ops", PERM_DELETE)
+        .accounts({ rbacState, group: findGroup("content_ops"), admin: admin.publicKey })
+        .rpc();
+
+      expect(await checkThroughGroup(PERM_DELETE)).to.be.true;
+      expect(await checkThroughGroup(PERM_UPDATE)).to.be.false;
+      expect(await checkThroughGroup(PERM_READ)).to.be.true;
+    });
+  });
+
+  describe("86. Frozen Users", () => {
+    const holder = anchor.web3.Keypair.generate();
+    const stranger = anchor.web3.Keypair.generate();
+    const checkWithStatus = (statusOf: anchor.web3.PublicKey) =>
+      program.methods
+        .checkPermission(PERM_READ)
+        .accounts({
+          role: findRole("freeze_reader"),
+          userRole: findUserRole(holder.publicKey, "freeze_reader"),
+          userStatus: findUserStatus(statusOf),
+        })
+        .view();
+    const setFrozen = (
+      method: "freezeUser" | "unfreezeUser",
+      user: anchor.web3.PublicKey,
+      signer?: anchor.web3.Keypair
+    ) =>
+      program.methods[method](user)
+        .accounts({ admin: signer ? signer.publicKey : admin.publicKey })
+        .signers(signer ? [signer] : [])
+        .rpc();
+
+    before(async () => {
+      await airdrop(stranger.publicKey);
+      await createRole("freeze_reader", PERM_READ);
+      await assignRole(holder.publicKey, "freeze_reader");
+    });
+
+    it("Should deny a frozen user despite a valid role", async () => {
+      await setFrozen("freezeUser", holder.publicKey);
+      expect(await checkWithStatus(holder.publicKey)).to.be.false;
+      await expectError(
+        program.methods
+          .assertHasPermission(PERM_READ)
+          .accounts({
+            role: findRole("freeze_reader"),
+            userRole: findUserRole(holder.publicKey, "freeze_reader"),
+            userStatus: findUserStatus(holder.publicKey),
+          })
+          .rpc(),
+        "UserFrozen"
+      );
+    });
+
+    it("Should not let a frozen user skip the freeze by swapping out their status", async () => {
+      // A status PDA that was never created stands in for leaving the account out
+      await expectError(checkWithStatus(anchor.web3.Keypair.generate().publicKey), "UserStatusMismatch");
+
+      expect(
+        await program.methods
+          .hasPermission(holder.publicKey, PERM_READ)
+          .accounts({
+            role: findRole("freeze_reader"),
+            userRole: findUserRole(holder.publicKey, "freeze_reader"),
+          })
+          .view()
+      ).to.be.false;
+      const permissions = await program.methods
+        .effectivePermissionsAt(new anchor.BN(Math.floor(Date.now() / 1000)))
+        .accounts({
+          role: findRole("freeze_reader"),
+          userRole: findUserRole(holder.publicKey, "freeze_reader"),
+          userStatus: findUserStatus(holder.publicKey),
+        })
+        .view();
+      expect(permissions).to.equal(0);
+    });
+
+    it("Should allow the user again after unfreezing", async () => {
+      await setFrozen("unfreezeUser", holder.publicKey);
+      expect(await checkWithStatus(holder.publicKey)).to.be.true;
+      const status = await program.account.userStatus.fetch(findUserStatus(holder.publicKey));
+      expect(status.frozen).to.be.false;
+    });
+
+    it("Should reject another user's status account", async () => {
+      await setFrozen("unfreezeUser", stranger.publicKey);
+      await expectError(checkWithStatus(stranger.publicKey), "UserStatusMismatch");
+    });
+
+    it("Should only let the admin freeze users", async () => {
+      await expectError(setFrozen("freezeUser", holder.publicKey, stranger), "NotAuthorized");
+    });
+  });
+
+  describe("87. Temporary Elevation", () => {
+    const holder = anchor.web3.Keypair.generate();
+    const stranger = anchor.web3.Keypair.generate();
+    const elevate = (durationSecs: number, signer?: anchor.web3.Keypair) =>
+      program.methods
+        .elevateUser(holder.publicKey, "elevated_reader", PERM_ADMIN, new anchor.BN(durationSecs))
+        .accounts({
+          rbacState,
+          userRole: findUserRole(holder.publicKey, "elevated_reader"),
+          admin: signer ? signer.publicKey : admin.publicKey,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+
+    before(async () => {
+      await airdrop(stranger.publicKey);
+      await createRole("elevated_reader", PERM_READ);
+      await assignRole(holder.publicKey, "elevated_reader");
+    });
+
+    it("Should grant the elevated permission until it expires", async () => {
+      expect(await checkPermission(holder.publicKey, "elevated_reader", PERM_DELETE)).to.be.false;
+      const signature = await elevate(3);
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("PermissionElevated");
+      expect(event.data.permission).to.equal(PERM_ADMIN);
+      expect(await checkPermission(holder.publicKey, "elevated_reader", PERM_DELETE)).to.be.true;
+
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+      expect(await checkPermission(holder.publicKey, "elevated_reader", PERM_DELETE)).to.be.false;
+      expect(await checkPermission(holder.publicKey, "elevated_reader", PERM_READ)).to.be.true;
+    });
+
+    it("Should only let the admin elevate", async () => {
+      await expectError(elevate(60, stranger), "NotAuthorized");
+    });
+
+    it("Should reject a duration beyond the break-glass limit", async () => {
+      await expectError(elevate(24 * 60 * 60 + 1), "InvalidBreakglassDuration");
+    });
+  });
+
+  describe("88. Cross-Program Permission Gate", () => {
+    // tests/permission_gate depends on this crate's `cpi` feature and calls
+    // assert_has_permission before doing its own work
+    const gate = anchor.workspace.PermissionGate as Program<PermissionGate>;
+    const editor = anchor.web3.Keypair.generate();
+    const reader = anchor.web3.Keypair.generate();
+    const gatedAction = (user: anchor.web3.Keypair, name: string) =>
+      gate.methods
+        .gatedAction()
+        .accounts({
+          role: findRole(name),
+          userRole: findUserRole(user.publicKey, name),
+          caller: user.publicKey,
+          rbacState,
+          userStatus: findUserStatus(user.publicKey),
+          rbacProgram: program.programId,
+        })
+        .signers([user])
+        .rpc();
+
+    before(async () => {
+      await airdrop(editor.publicKey);
+      await airdrop(reader.publicKey);
+      await createRole("gate_editor", PERM_READ | PERM_UPDATE);
+      await assignRole(editor.publicKey, "gate_editor");
+      await assignRole(reader.publicKey, "user");
+    });
+
+    it("Should run the downstream instruction when the permission is held", async () => {
+      await gatedAction(editor, "gate_editor");
+    });
+
+    it("Should abort the downstream instruction when it is not", async () => {
+      await expectError(gatedAction(reader, "user"), "PermissionDenied");
+    });
+
+    it("Should abort the downstream instruction for a frozen caller", async () => {
+      await program.methods.freezeUser(editor.publicKey).accounts({ admin: admin.publicKey }).rpc();
+      await expectError(gatedAction(editor, "gate_editor"), "UserFrozen");
+
+      await program.methods.unfreezeUser(editor.publicKey).accounts({ admin: admin.publicKey }).rpc();
+      await gatedAction(editor, "gate_editor");
+    });
+  });
+
+  describe("89. CPI Gateway", () => {
+    const editor = anchor.web3.Keypair.generate();
+    const reader = anchor.web3.Keypair.generate();
+    const recipient = anchor.web3.Keypair.generate();
+    const setCpiTargets = (targets: anchor.web3.PublicKey[]) =>
+      program.methods.setCpiTargets(targets).accounts({ rbacState, admin: admin.publicKey }).rpc();
+    // Gate a plain SOL transfer from `signer` behind PERM_UPDATE, checked through
+    // `holder`'s assignment (the signer's own by default)
+    const gatedTransfer = (signer: anchor.web3.Keypair, name: string, holder = signer.publicKey) => {
+      const transfer = anchor.web3.SystemProgram.transfer({
+        fromPubkey: signer.publicKey,
+        toPubkey: recipient.publicKey,
+        lamports: 1_000_000,
+      });
+      return program.methods
+        .executeActionCpi(PERM_UPDATE, 0, transfer.data)
+        .accounts({
+          check: {
+            role: findRole(name),
+            userRole: findUserRole(holder, name),
+            caller: signer.publicKey,
+            rbacState,
+            userStatus: findUserStatus(holder),
+          },
+          targetProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts(transfer.keys)
+        .signers([signer])
+        .rpc();
+    };
+
+    before(async () => {
+      await airdrop(editor.publicKey);
+      await airdrop(reader.publicKey);
+      await createRole("gateway_editor", PERM_READ | PERM_UPDATE);
+      await assignRole(editor.publicKey, "gateway_editor");
+      await assignRole(reader.publicKey, "user");
+    });
+
+    after(async () => {
+      await setCpiTargets([]);
+    });
+
+    it("Should refuse to forward to a program off the allowlist", async () => {
+      await expectError(gatedTransfer(editor, "gateway_editor"), "CpiTargetNotAllowed");
+    });
+
+    it("Should never allow the RBAC program itself as a target", async () => {
+      await expectError(setCpiTargets([program.programId]), "CpiTargetNotAllowed");
+    });
+
+    it("Should perform the downstream call when the permission is held", async () => {
+      await setCpiTargets([anchor.web3.SystemProgram.programId]);
+      await gatedTransfer(editor, "gateway_editor");
+      expect(await provider.connection.getBalance(recipient.publicKey)).to.equal(1_000_000);
+    });
+
+    it("Should block the downstream call when the permission is missing", async () => {
+      await expectError(gatedTransfer(reader, "user"), "PermissionDenied");
+      expect(await provider.connection.getBalance(recipient.publicKey)).to.equal(1_000_000);
+    });
+
+    it("Should refuse to forward on another user's assignment", async () => {
+      await expectError(gatedTransfer(reader, "gateway_editor", editor.publicKey), "NotAuthorized");
+      expect(await provider.connection.getBalance(recipient.publicKey)).to.equal(1_000_000);
+    });
+
+    it("Should refuse to forward while paused", async () => {
+      await program.methods.pause().accounts({ rbacState, admin: admin.publicKey }).rpc();
+      try {
+        await expectError(gatedTransfer(editor, "gateway_editor"), "SystemPaused");
+      } finally {
+        await program.methods.unpause().accounts({ rbacState, admin: admin.publicKey }).rpc();
+      }
+      expect(await provider.connection.getBalance(recipient.publicKey)).to.equal(1_000_000);
+    });
+  });
+
+  describe("90. Batch Assignment", () => {
+    const team = [...Array(5)].map(() => anchor.web3.Keypair.generate());
+    const findUserIndex = (user: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("user_index"), user.toBuffer()],
+        program.programId
+      )[0];
+    const writable = (pubkey: anchor.web3.PublicKey) => ({ pubkey, isSigner: false, isWritable: true });
+    const assignBatch = (users: anchor.web3.PublicKey[], name: string) =>
+      program.methods
+        .assignRolesBatch(users.map((user) => ({ user, role: name })))
+        .accounts({ rbacState, authority: admin.publicKey })
+        .remainingAccounts([
+          writable(findRole(name)),
+          ...users.flatMap((user) => [writable(findUserRole(user, name)), writable(findUserIndex(user))]),
+        ])
+        .rpc();
+
+    before(async () => {
+      await createRole("batch_member", PERM_READ);
+    });
+
+    it("Should assign five users in one transaction", async () => {
+      const before = (await program.account.role.fetch(findRole("batch_member"))).assignmentCount;
+      await assignBatch(team.map((member) => member.publicKey), "batch_member");
+
+      for (const member of team) {
+        const assignment = await program.account.userRole.fetch(findUserRole(member.publicKey, "batch_member"));
+        expect(assignment.user.toBase58()).to.equal(member.publicKey.toBase58());
+        expect(await checkPermission(member.publicKey, "batch_member", PERM_READ)).to.be.true;
+        const index = await program.account.userRoleIndex.fetch(findUserIndex(member.publicKey));
+        expect(index.roles).to.include("batch_member");
+      }
+      const after = (await program.account.role.fetch(findRole("batch_member"))).assignmentCount;
+      expect(after).to.equal(before + team.length);
+    });
+
+    it("Should roll back the whole batch when one entry fails", async () => {
+      const fresh = anchor.web3.Keypair.generate();
+      // The second entry is already assigned, so creating it fails
+      await expectError(assignBatch([fresh.publicKey, team[0].publicKey], "batch_member"), "already in use");
+      expect(await provider.connection.getAccountInfo(findUserRole(fresh.publicKey, "batch_member"))).to.be.null;
+    });
+
+    it("Should reject a batch over the size cap", async () => {
+      const users = [...Array(6)].map(() => anchor.web3.Keypair.generate().publicKey);
+      await expectError(
+        program.methods
+          .assignRolesBatch(users.map((user) => ({ user, role: "batch_member" })))
+          .accounts({ rbacState, authority: admin.publicKey })
+          .rpc(),
+        "BatchTooLarge"
+      );
+    });
+  });
+
+  describe("91. Wildcard Role", () => {
+    const service = anchor.web3.Keypair.generate();
+    const shortLived = anchor.web3.Keypair.generate();
+    const manager = anchor.web3.Keypair.generate();
+    const findRoleManager = (key: anchor.web3.PublicKey, name: string) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("role_manager"), key.toBuffer(), Buffer.from(name)],
+        program.programId
+      )[0];
+
+    before(async () => {
+      await airdrop(manager.publicKey);
+      // Stored bits are ignored: the wildcard role grants everything
+      await createRole("*", PERM_READ);
+      await assignRole(service.publicKey, "*", null, "service account");
+    });
+
+    after(async () => {
+      await program.methods
+        .unfreezeUser(service.publicKey)
+        .accounts({ admin: admin.publicKey })
+        .rpc();
+    });
+
+    it("Should pass every permission check, including bits beyond the named ones", async () => {
+      for (const permission of [PERM_READ, PERM_DELETE, PERM_ADMIN, 1 << 10]) {
+        expect(await checkPermission(service.publicKey, "*", permission)).to.be.true;
+      }
+    });
+
+    it("Should still be denied once the assignment expires", async () => {
+      const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 2);
+      await assignRole(shortLived.publicKey, "*", expiresAt, "temporary service account");
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      expect(await checkPermission(shortLived.publicKey, "*", PERM_READ)).to.be.false;
+    });
+
+    it("Should still be denied while the holder is frozen", async () => {
+      await program.methods
+        .freezeUser(service.publicKey)
+        .accounts({ admin: admin.publicKey })
+        .rpc();
+      const allowed = await program.methods
+        .checkPermission(PERM_READ)
+        .accounts({
+          role: findRole("*"),
+          userRole: findUserRole(service.publicKey, "*"),
+          userStatus: findUserStatus(service.publicKey),
+        })
+        .view();
+      expect(allowed).to.be.false;
+    });
+
+    it("Should only let the root admin assign it, not a delegated manager", async () => {
+      await program.methods
+        .grantAssigner(manager.publicKey, "*")
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+      const employee = anchor.web3.Keypair.generate();
+      await expectError(
+        program.methods
+          .assignRole(employee.publicKey, "*", null, "service account", null)
+          .accounts({
+            rbacState,
+            role: findRole("*"),
+            userRole: findUserRole(employee.publicKey, "*"),
+            authority: manager.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .remainingAccounts([
+            { pubkey: findRoleManager(manager.publicKey, "*"), isSigner: false, isWritable: false },
+          ])
+          .signers([manager])
+          .rpc(),
+        "NotAuthorized"
+      );
+    });
+  });
+
+  describe("92. Role Renaming", () => {
+    const holder = anchor.web3.Keypair.generate();
+
+    const renameRole = (oldName: string, newName: string) =>
+      program.methods
+        .renameRole(oldName, newName)
+        .accountsPartial({
+          oldRole: findRole(oldName),
+          newRole: findRole(newName),
+          admin: admin.publicKey,
+        })
+        .rpc();
+
+    before(async () => {
+      await createRole("rename-src", PERM_READ | PERM_WRITE, "before rename");
+      await createRole("rename-empty", PERM_READ);
+      await assignRole(holder.publicKey, "rename-src", null, "rename test");
+    });
+
+    it("Should close a role without assignments straight away", async () => {
+      await renameRole("rename-empty", "rename-empty-2");
+      expect(await provider.connection.getAccountInfo(findRole("rename-empty"))).to.be.null;
+      const renamed = await program.account.role.fetch(findRole("rename-empty-2"));
+      expect(renamed.permissions).to.equal(PERM_READ);
+    });
+
+    it("Should refuse a rename with holders at max_roles, since both roles stay live", async () => {
+      const setMaxRoles = (maxRoles: number) =>
+        program.methods.setMaxRoles(maxRoles).accounts({ rbacState, admin: admin.publicKey }).rpc();
+      const state = await program.account.rbacState.fetch(rbacState);
+      await setMaxRoles(state.roleCount);
+      try {
+        await expectError(renameRole("rename-src", "rename-dst"), "TooManyRoles");
+      } finally {
+        await setMaxRoles(0);
+      }
+    });
+
+    it("Should copy the role and leave the old one disabled until assignments are remapped", async () => {
+      await renameRole("rename-src", "rename-dst");
+      const renamed = await program.account.role.fetch(findRole("rename-dst"));
+      expect(renamed.permissions).to.equal(PERM_READ | PERM_WRITE);
+      expect(renamed.description).to.equal("before rename");
+      expect(renamed.assignmentCount).to.equal(0);
+
+      const old = await program.account.role.fetch(findRole("rename-src"));
+      expect(old.active).to.be.false;
+      expect(old.renamedTo).to.equal("rename-dst");
+      expect(await checkPermission(holder.publicKey, "rename-src", PERM_READ)).to.be.false;
+    });
+
+    it("Should reject remapping to a name the role was not renamed to", async () => {
+      await expectError(
+        program.methods
+          .remapUserRole(holder.publicKey, "rename-src", "rename-empty-2")
+          .accountsPartial({
+            newUserRole: findUserRole(holder.publicKey, "rename-empty-2"),
+            admin: admin.publicKey,
+          })
+          .rpc(),
+        "RoleNotRenamed"
+      );
+    });
+
+    it("Should resolve permissions through the new name after a remap", async () => {
+      await program.methods
+        .remapUserRole(holder.publicKey, "rename-src", "rename-dst")
+        .accountsPartial({
+          newUserRole: findUserRole(holder.publicKey, "rename-dst"),
+          admin: admin.publicKey,
+        })
+        .rpc();
+
+      expect(await checkPermission(holder.publicKey, "rename-dst", PERM_WRITE)).to.be.true;
+      const moved = await program.account.userRole.fetch(findUserRole(holder.publicKey, "rename-dst"));
+      expect(moved.note).to.equal("rename test");
+      expect(await provider.connection.getAccountInfo(findUserRole(holder.publicKey, "rename-src"))).to.be.null;
+      // The last remap closes the old role
+      expect(await provider.connection.getAccountInfo(findRole("rename-src"))).to.be.null;
+    });
+  });
+
+  describe("93. Admin Role Invariants", () => {
+    before(async () => {
+      await createRole("admin-console", PERM_READ | PERM_ADMIN);
+      await assignRole(admin.publicKey, "admin-console", null, "system admin console");
+    });
+
+    it("Should reject an admin role without the Admin permission", async () => {
+      const name = "admin";
+      await expectError(
+        program.methods
+          .updateRolePermissions(name, PERM_READ | PERM_UPDATE)
+          .accounts({ rbacState, role: findRole(name), admin: admin.publicKey })
+          .rpc(),
+        "InvalidAdminRole"
+      );
+      const role = await program.account.role.fetch(findRole(name));
+      expect(role.permissions & PERM_ADMIN).to.equal(PERM_ADMIN);
+    });
+
+    it("Should refuse to disable a role the system admin holds", async () => {
+      await expectError(
+        program.methods
+          .setRoleActive("admin-console", false)
+          .accounts({ rbacState, role: findRole("admin-console"), admin: admin.publicKey })
+          .rpc(),
+        "AdminRoleInUse"
+      );
+      const role = await program.account.role.fetch(findRole("admin-console"));
+      expect(role.active).to.be.true;
+    });
+
+    it("Should reject revoking the Admin permission from the admin role", async () => {
+      await expectError(
+        program.methods
+          .revokePermissions("admin", PERM_ADMIN)
+          .accounts({ rbacState, role: findRole("admin"), admin: admin.publicKey })
+          .rpc(),
+        "InvalidAdminRole"
+      );
+    });
+
+    it("Should refuse to emergency-disable a role the system admin holds", async () => {
+      await expectError(
+        program.methods
+          .emergencyDisableRole("admin-console")
+          .accounts({ rbacState, role: findRole("admin-console"), admin: admin.publicKey })
+          .rpc(),
+        "AdminRoleInUse"
+      );
+      const role = await program.account.role.fetch(findRole("admin-console"));
+      expect(role.disabledAt.toNumber()).to.equal(0);
+    });
+
+    it("Should refuse to rename a role the system admin holds", async () => {
+      await expectError(
+        program.methods
+          .renameRole("admin-console", "admin-console-2")
+          .accountsPartial({
+            oldRole: findRole("admin-console"),
+            newRole: findRole("admin-console-2"),
+            admin: admin.publicKey,
+          })
+          .rpc(),
+        "AdminRoleInUse"
+      );
+    });
+
+    it("Should refuse to delete a role the system admin holds", async () => {
+      await expectError(
+        program.methods
+          .deleteRole("admin-console")
+          .accounts({ rbacState, role: findRole("admin-console"), admin: admin.publicKey })
+          .rpc(),
+        "RoleInUse"
+      );
+    });
+  });
+
+  describe("94. Role Assignment Caps", () => {
+    const holders = [0, 1, 2, 3].map(() => anchor.web3.Keypair.generate());
+
+    const revoke = (user: anchor.web3.PublicKey) =>
+      program.methods
+        .revokeRole("treasurer", false)
+        .accounts({
+          rbacState,
+          role: findRole("treasurer"),
+          userRole: findUserRole(user, "treasurer"),
+          authority: admin.publicKey,
+        })
+        .rpc();
+
+    before(async () => {
+      await createRole("treasurer", PERM_READ | PERM_UPDATE, "Capped role", null, 0, null, 3);
+    });
+
+    it("Should store the cap set at creation", async () => {
+      const role = await program.account.role.fetch(findRole("treasurer"));
+      expect(role.maxAssignments).to.equal(3);
+      expect(role.assignmentCount).to.equal(0);
+    });
+
+    it("Should reject the assignment past the cap", async () => {
+      for (const holder of holders.slice(0, 3)) {
+        await assignRole(holder.publicKey, "treasurer");
+      }
+      await expectError(assignRole(holders[3].publicKey, "treasurer"), "RoleAssignmentCapReached");
+      const role = await program.account.role.fetch(findRole("treasurer"));
+      expect(role.assignmentCount).to.equal(3);
+    });
+
+    it("Should accept an assignment again once one is revoked", async () => {
+      await revoke(holders[0].publicKey);
+      await assignRole(holders[3].publicKey, "treasurer");
+      expect(await checkPermission(holders[3].publicKey, "treasurer", PERM_UPDATE)).to.be.true;
+    });
+
+    it("Should let the admin raise or clear the cap", async () => {
+      await program.methods
+        .setRoleCap("treasurer", 4)
+        .accounts({ rbacState, role: findRole("treasurer"), admin: admin.publicKey })
+        .rpc();
+      await assignRole(holders[0].publicKey, "treasurer");
+      await expectError(
+        assignRole(anchor.web3.Keypair.generate().publicKey, "treasurer"),
+        "RoleAssignmentCapReached"
+      );
+
+      await program.methods
+        .setRoleCap("treasurer", null)
+        .accounts({ rbacState, role: findRole("treasurer"), admin: admin.publicKey })
+        .rpc();
+      await assignRole(anchor.web3.Keypair.generate().publicKey, "treasurer");
+      const role = await program.account.role.fetch(findRole("treasurer"));
+      expect(role.maxAssignments).to.be.null;
+      expect(role.assignmentCount).to.equal(5);
+    });
+  });
+
+  describe("95. Event-Free Preview", () => {
+    const holder = anchor.web3.Keypair.generate();
+    const configurations = ["preview-reader", "preview-editor", "preview-off"];
+    const allBits = [PERM_READ, PERM_CREATE, PERM_UPDATE, PERM_DELETE, PERM_ADMIN];
+
+    const preview = (name: string, permission: number) =>
+      program.methods
+        .evaluatePermission(permission)
+        .accounts({ role: findRole(name), userRole: findUserRole(holder.publicKey, name), userStatus: findUserStatus(holder.publicKey) });
+
+    before(async () => {
+      await createRole("preview-reader", PERM_READ);
+      await createRole("preview-editor", PERM_READ | PERM_CREATE | PERM_UPDATE);
+      await createRole("preview-off", PERM_READ | PERM_DELETE);
+      for (const name of configurations) {
+        await assignRole(holder.publicKey, name);
+      }
+      await program.methods
+        .setRoleActive("preview-off", false)
+        .accounts({ rbacState, role: findRole("preview-off"), admin: admin.publicKey })
+        .rpc();
+    });
+
+    it("Should agree with check_permission for every permission bit", async () => {
+      for (const name of configurations) {
+        for (const permission of allBits) {
+          const evaluation = await preview(name, permission).view();
+          const allowed = await checkPermission(holder.publicKey, name, permission);
+          expect(evaluation.allowed, `${name} / ${permission}`).to.equal(allowed);
+        }
+      }
+    });
+
+    it("Should not emit any event", async () => {
+      const simulation = await preview("preview-editor", PERM_UPDATE).simulate();
+      expect(simulation.events).to.be.empty;
+    });
+  });
+
+  describe("96. Seed Namespaces", () => {
+    const roleRegistry = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("role_registry")],
+      program.programId
+    )[0];
+
+    it("Should keep a role named like another namespace off that namespace's PDA", async () => {
+      expect(findRole("_registry").toString()).to.not.equal(roleRegistry.toString());
+      await createRole("_registry", PERM_READ);
+
+      const role = await program.account.role.fetch(findRole("_registry"));
+      expect(role.name).to.equal("_registry");
+      const registry = await program.account.roleRegistry.fetch(roleRegistry);
+      expect(registry.names).to.include("_registry");
+    });
+  });
+
+  describe("97. Delegation Depth", () => {
+    const [lead, deputy, intern, employee] = [0, 1, 2, 3].map(() => anchor.web3.Keypair.generate());
+    const findRoleManager = (key: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("role_manager"), key.toBuffer(), Buffer.from("ops-shift")],
+        program.programId
+      )[0];
+    const readOnly = (pubkey: anchor.web3.PublicKey) => ({ pubkey, isSigner: false, isWritable: false });
+
+    const setMaxDelegationDepth = (depth: number) =>
+      program.methods
+        .setMaxDelegationDepth(depth)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+
+    const subDelegate = (granter: anchor.web3.Keypair, manager: anchor.web3.PublicKey) =>
+      program.methods
+        .grantAssigner(manager, "ops-shift")
+        .accounts({ rbacState, admin: granter.publicKey })
+        .remainingAccounts([readOnly(findRoleManager(granter.publicKey))])
+        .signers([granter])
+        .rpc();
+
+    before(async () => {
+      for (const key of [lead, deputy]) {
+        await airdrop(key.publicKey);
+      }
+      await createRole("ops-shift", PERM_READ);
+      await setMaxDelegationDepth(2);
+      await program.methods
+        .grantAssigner(lead.publicKey, "ops-shift")
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+    });
+
+    after(async () => {
+      await setMaxDelegationDepth(1);
+    });
+
+    it("Should let a manager sub-delegate one level down", async () => {
+      await subDelegate(lead, deputy.publicKey);
+      const deputyManager = await program.account.roleManager.fetch(findRoleManager(deputy.publicKey));
+      expect(deputyManager.depth).to.equal(2);
+      expect(deputyManager.grantedBy.toBase58()).to.equal(lead.publicKey.toBase58());
+    });
+
+    it("Should record the assigner's depth on the assignment and in RoleAssigned", async () => {
+      const sig = await program.methods
+        .assignRole(employee.publicKey, "ops-shift", null, null, null)
+        .accounts({
+          rbacState,
+          role: findRole("ops-shift"),
+          userRole: findUserRole(employee.publicKey, "ops-shift"),
+          authority: deputy.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .remainingAccounts([readOnly(findRoleManager(deputy.publicKey))])
+        .signers([deputy])
+        .rpc();
+
+      const assignment = await program.account.userRole.fetch(findUserRole(employee.publicKey, "ops-shift"));
+      expect(assignment.delegationDepth).to.equal(2);
+      const assigned = (await eventsOf(sig)).find((event) => event.name === "RoleAssigned");
+      expect(assigned.data.delegationDepth).to.equal(2);
+    });
+
+    it("Should reject a third level beyond the max", async () => {
+      await expectError(subDelegate(deputy, intern.publicKey), "DelegationTooDeep");
+    });
+  });
+
+  describe("98. Teardown", () => {
+    it("Should refuse to close the state while roles and assignments exist", async () => {
+      const state = await program.account.rbacState.fetch(rbacState);
+      expect(state.roleCount).to.be.greaterThan(0);
+      await expectError(
+        program.methods.closeRbacState().accounts({ admin: admin.publicKey }).rpc(),
+        "SystemNotEmpty"
+      );
+      expect(await provider.connection.getAccountInfo(rbacState)).to.not.be.null;
+    });
+
+    it("Should only let the root admin close it", async () => {
+      const outsider = anchor.web3.Keypair.generate();
+      await airdrop(outsider.publicKey);
+      await expectError(
+        program.methods
+          .closeRbacState()
+          .accounts({ admin: outsider.publicKey })
+          .signers([outsider])
+          .rpc(),
+        "NotAuthorized"
+      );
+    });
+  });
+
+  describe("99. Checks For Unassigned Users", () => {
+    const holder = anchor.web3.Keypair.generate();
+    const stranger = anchor.web3.Keypair.generate();
+
+    const checkUser = (user: anchor.web3.PublicKey, name: string, permission: number) =>
+      program.methods
+        .checkUserPermission(user, name, permission)
+        .accounts({ role: findRole(name) })
+        .view();
+
+    before(async () => {
+      await createRole("lookup", PERM_READ | PERM_UPDATE);
+      await assignRole(holder.publicKey, "lookup");
+    });
+
+    it("Should return false for a user without the assignment", async () => {
+      expect(await provider.connection.getAccountInfo(findUserRole(stranger.publicKey, "lookup"))).to.be.null;
+      expect(await checkUser(stranger.publicKey, "lookup", PERM_READ)).to.be.false;
+    });
+
+    it("Should return the assignment's verdict for an assigned user", async () => {
+      expect(await checkUser(holder.publicKey, "lookup", PERM_UPDATE)).to.be.true;
+      expect(await checkUser(holder.publicKey, "lookup", PERM_DELETE)).to.be.false;
+    });
+
+    it("Should still answer for the system admin without an assignment", async () => {
+      expect(await checkUser(admin.publicKey, "lookup", PERM_ADMIN)).to.be.true;
+    });
+
+    it("Should deny rather than fail when check_permission gets no assignment", async () => {
+      const evaluation = await program.methods
+        .evaluatePermission(PERM_READ)
+        .accounts({ role: null, userRole: null, caller: stranger.publicKey, userStatus: findUserStatus(stranger.publicKey) })
+        .signers([stranger])
+        .view();
+      expect(evaluation.allowed).to.be.false;
+      expect(evaluation.reason).to.have.property("noAssignment");
+    });
+  });
+
+  describe("100. Role Validity Windows", () => {
+    const holder = anchor.web3.Keypair.generate();
+    const now = () => Math.floor(Date.now() / 1000);
+
+    const assertRead = () =>
+      program.methods
+        .assertHasPermission(PERM_READ)
+        .accounts({
+          role: findRole("campaign"),
+          userRole: findUserRole(holder.publicKey, "campaign"),
+          caller: holder.publicKey,
+          userStatus: findUserStatus(holder.publicKey),
+        })
+        .signers([holder])
+        .rpc();
+
+    before(async () => {
+      await createRole("campaign", PERM_READ, "Launch campaign", null, 0, null, null, new anchor.BN(now() + 3600), null);
+      await assignRole(holder.publicKey, "campaign");
+    });
+
+    it("Should deny checks before the window opens", async () => {
+      expect(await checkPermission(holder.publicKey, "campaign", PERM_READ)).to.be.false;
+      await expectError(assertRead(), "RoleNotYetValid");
+    });
+
+    it("Should reject a window that ends before it starts", async () => {
+      await expectError(
+        program.methods
+          .setRoleValidity("campaign", new anchor.BN(now() + 10), new anchor.BN(now()))
+          .accounts({ rbacState, role: findRole("campaign"), admin: admin.publicKey })
+          .rpc(),
+        "InvalidRoleWindow"
+      );
+    });
+
+    it("Should allow checks inside the window", async () => {
+      await program.methods
+        .setRoleValidity("campaign", new anchor.BN(now() - 60), new anchor.BN(now() + 3))
+        .accounts({ rbacState, role: findRole("campaign"), admin: admin.publicKey })
+        .rpc();
+      expect(await checkPermission(holder.publicKey, "campaign", PERM_READ)).to.be.true;
+    });
+
+    it("Should deny checks once the window has closed", async () => {
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+      expect(await checkPermission(holder.publicKey, "campaign", PERM_READ)).to.be.false;
+      await expectError(assertRead(), "RoleExpired");
+    });
+  });
+
+  describe("101. Denial Diagnostics", () => {
+    const holder = anchor.web3.Keypair.generate();
+
+    before(async () => {
+      await createRole("diagnosed", PERM_READ | PERM_CREATE);
+      await assignRole(holder.publicKey, "diagnosed");
+    });
+
+    it("Should emit PermissionDenied with the missing permission and the role's bits", async () => {
+      let logs: string[] = [];
+      try {
+        await program.methods
+          .assertHasPermission(PERM_DELETE)
+          .accounts({
+            role: findRole("diagnosed"),
+            userRole: findUserRole(holder.publicKey, "diagnosed"),
+            caller: holder.publicKey,
+            userStatus: findUserStatus(holder.publicKey),
+          })
+          .signers([holder])
+          .rpc();
+        expect.fail("the check should have failed");
+      } catch (err: any) {
+        expect(err.error?.errorCode?.code).to.equal("PermissionDenied");
+        logs = err.logs;
+      }
+
+      const parser = new anchor.EventParser(program.programId, program.coder);
+      const denied = [...parser.parseLogs(logs)].find((event) => event.name === "PermissionDenied");
+      expect(denied, "PermissionDenied event").to.not.be.undefined;
+      expect(denied.data.user.toBase58()).to.equal(holder.publicKey.toBase58());
+      expect(denied.data.role).to.equal("diagnosed");
+      expect(denied.data.requiredPermission).to.equal(PERM_DELETE);
+      expect(denied.data.rolePermissions).to.equal(PERM_READ | PERM_CREATE);
+      expect(denied.data.reason).to.equal("Insufficient Bitmask");
+    });
+  });
+
+  describe("102. Role Introspection", () => {
+    const findGroup = (name: string) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("perm_group"), Buffer.from(name)],
+        program.programId
+      )[0];
+
+    const roleHas = (name: string, permission: number, remaining: anchor.web3.PublicKey[] = []) =>
+      program.methods
+        .roleHasPermission(name, permission)
+        .accounts({ role: findRole(name) })
+        .remainingAccounts(remaining.map((pubkey) => ({ pubkey, isSigner: false, isWritable: false })))
+        .view();
+
+    before(async () => {
+      await program.methods
+        .createPermissionGroup("introspect_ops", PERM_DELETE)
+        .accounts({ rbacState, group: findGroup("introspect_ops"), admin: admin.publicKey })
+        .rpc();
+      await createRole("introspected", PERM_READ | PERM_UPDATE, "", null, 0, "introspect_ops");
+      await createRole("introspected-admin", PERM_ADMIN);
+    });
+
+    it("Should report the role's own bits without any assignment", async () => {
+      expect(await roleHas("introspected", PERM_UPDATE)).to.be.true;
+      expect(await roleHas("introspected", PERM_READ | PERM_UPDATE)).to.be.true;
+      expect(await roleHas("introspected", PERM_CREATE)).to.be.false;
+    });
+
+    it("Should include group bits when the group account is passed", async () => {
+      expect(await roleHas("introspected", PERM_DELETE)).to.be.false;
+      expect(await roleHas("introspected", PERM_DELETE, [findGroup("introspect_ops")])).to.be.true;
+    });
+
+    it("Should treat the Admin bit as implying every permission", async () => {
+      for (const permission of [PERM_READ, PERM_CREATE, PERM_DELETE]) {
+        expect(await roleHas("introspected-admin", permission)).to.be.true;
+      }
+    });
+
+    it("Should not emit any event", async () => {
+      const simulation = await program.methods
+        .roleHasPermission("introspected", PERM_READ)
+        .accounts({ role: findRole("introspected") })
+        .simulate();
+      expect(simulation.events).to.be.empty;
+    });
+  });
+
+  describe("103. Emergency Role Disable", () => {
+    const guardian = anchor.web3.Keypair.generate();
+    const holders = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+
+    const assertUpdate = (holder: anchor.web3.Keypair) =>
+      program.methods
+        .assertHasPermission(PERM_UPDATE)
+        .accounts({
+          role: findRole("compromised"),
+          userRole: findUserRole(holder.publicKey, "compromised"),
+          caller: holder.publicKey,
+          userStatus: findUserStatus(holder.publicKey),
+        })
+        .signers([holder])
+        .rpc();
+
+    before(async () => {
+      await airdrop(guardian.publicKey);
+      await program.methods
+        .setGuardian(guardian.publicKey)
+        .accounts({ rbacState, admin: admin.publicKey })
+        .rpc();
+      await createRole("compromised", PERM_READ | PERM_UPDATE);
+      for (const holder of holders) {
+        await assignRole(holder.publicKey, "compromised");
+      }
+    });
+
+    it("Should let the guardian deny every holder at once", async () => {
+      const signature = await program.methods
+        .emergencyDisableRole("compromised")
+        .accounts({ rbacState, role: findRole("compromised"), admin: guardian.publicKey })
+        .signers([guardian])
+        .rpc();
+      const [event] = await eventsOf(signature);
+      expect(event.name).to.equal("RoleEmergencyDisabled");
+      expect(event.data.disabledBy.toBase58()).to.equal(guardian.publicKey.toBase58());
+
+      const role = await program.account.role.fetch(findRole("compromised"));
+      expect(role.disabledAt.toNumber()).to.be.greaterThan(0);
+      for (const holder of holders) {
+        expect(await checkPermission(holder.publicKey, "compromised", PERM_READ)).to.be.false;
+        await expectError(assertUpdate(holder), "RoleDisabled");
+      }
+    });
+
+    it("Should not let the guardian re-enable the role", async () => {
+      await expectError(
+        program.methods
+          .reenableRole("compromised")
+          .accounts({ rbacState, role: findRole("compromised"), admin: guardian.publicKey })
+          .signers([guardian])
+          .rpc(),
+        "NotAuthorized"
+      );
+    });
+
+    it("Should restore every holder once the admin re-enables it", async () => {
+      await program.methods
+        .reenableRole("compromised")
+        .accounts({ rbacState, role: findRole("compromised"), admin: admin.publicKey })
+        .rpc();
+
+      for (const holder of holders) {
+        expect(await checkPermission(holder.publicKey, "compromised", PERM_UPDATE)).to.be.true;
+        await assertUpdate(holder);
+      }
+      await expectError(
+        program.methods
+          .reenableRole("compromised")
+          .accounts({ rbacState, role: findRole("compromised"), admin: admin.publicKey })
+          .rpc(),
+        "RoleNotDisabled"
+      );
+    });
+
+    it("Should neutralize the role inside composites and for child roles", async () => {
+      const bundleHolder = anchor.web3.Keypair.generate();
+      const childHolder = anchor.web3.Keypair.generate();
+      const viaMember = (user: anchor.web3.PublicKey, name: string) =>
+        program.methods
+          .checkPermission(PERM_UPDATE)
+          .accounts({ role: findRole(name), userRole: findUserRole(user, name), userStatus: findUserStatus(user) })
+          .remainingAccounts([{ pubkey: findRole("compromised"), isSigner: false, isWritable: false }])
+          .view();
+      const setDisabled = (disabled: boolean) =>
+        program.methods[disabled ? "emergencyDisableRole" : "reenableRole"]("compromised")
+          .accounts({ rbacState, role: findRole("compromised"), admin: admin.publicKey })
+          .rpc();
+
+      await createRole("compromised_bundle", PERM_READ);
+      await program.methods
+        .setRoleComposition("compromised_bundle", ["compromised"])
+        .accounts({ rbacState, role: findRole("compromised_bundle"), admin: admin.publicKey })
+        .rpc();
+      await createRole("compromised_child", PERM_READ);
+      await program.methods
+        .setRoleParent("compromised_child", "compromised")
+        .accounts({ rbacState, role: findRole("compromised_child"), admin: admin.publicKey })
+        .remainingAccounts([{ pubkey: findRole("compromised"), isSigner: false, isWritable: false }])
+        .rpc();
+      await assignRole(bundleHolder.publicKey, "compromised_bundle");
+      await assignRole(childHolder.publicKey, "compromised_child");
+      expect(await viaMember(bundleHolder.publicKey, "compromised_bundle")).to.be.true;
+      expect(await viaMember(childHolder.publicKey, "compromised_child")).to.be.true;
+
+      await setDisabled(true);
+      try {
+        expect(await viaMember(bundleHolder.publicKey, "compromised_bundle")).to.be.false;
+        expect(await viaMember(childHolder.publicKey, "compromised_child")).to.be.false;
+      } finally {
+        await setDisabled(false);
+      }
+    });
+  });
+
+  describe("104. Expired Assignment Sweeper", () => {
+    const lapsed = [anchor.web3.Keypair.generate(), anchor.web3.Keypair.generate()];
+    const current = anchor.web3.Keypair.generate();
+    const holders = [...lapsed, current];
+
+    const findUserIndex = (user: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("user_index"), user.toBuffer()],
+        program.programId
+      )[0];
+
+    const writable = (pubkey: anchor.web3.PublicKey) => ({ pubkey, isSigner: false, isWritable: true });
+
+    const sweep = (limit: number) =>
+      program.methods
+        .sweepExpiredRoles(limit)
+        .accounts({ rbacState })
+        .remainingAccounts([
+          ...holders.map((holder) => writable(findUserRole(holder.publicKey, "seasonal"))),
+          writable(findRole("seasonal")),
+          ...holders.map((holder) => writable(findUserIndex(holder.publicKey))),
+          writable(admin.publicKey),
+        ])
+        .rpc();
+
+    before(async () => {
+      await createRole("seasonal", PERM_READ);
+      const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 2);
+      for (const holder of lapsed) {
+        await assignRole(holder.publicKey, "seasonal", expiresAt);
+      }
+      await assignRole(current.publicKey, "seasonal");
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+    });
+
+    it("Should stop after `limit` assignments", async () => {
+      const [event] = await eventsOf(await sweep(1));
+      expect(event.name).to.equal("RolesSwept");
+      expect(event.data.processed).to.equal(1);
+      expect(event.data.closed).to.equal(1);
+      expect(await provider.connection.getAccountInfo(findUserRole(lapsed[0].publicKey, "seasonal"))).to.be.null;
+      expect(await provider.connection.getAccountInfo(findUserRole(lapsed[1].publicKey, "seasonal"))).to.not.be.null;
+    });
+
+    it("Should close only the expired assignments and refund their rent", async () => {
+      const before = await provider.connection.getBalance(admin.publicKey);
+      const [event] = await eventsOf(await sweep(10));
+      expect(event.data.processed).to.equal(2);
+      expect(event.data.closed).to.equal(1);
+      expect(event.data.users.map((user) => user.toBase58())).to.deep.equal([lapsed[1].publicKey.toBase58()]);
+      expect(await provider.connection.getBalance(admin.publicKey)).to.be.greaterThan(before);
+
+      expect(await provider.connection.getAccountInfo(findUserRole(lapsed[1].publicKey, "seasonal"))).to.be.null;
+      expect(await checkPermission(current.publicKey, "seasonal", PERM_READ)).to.be.true;
+      const role = await program.account.role.fetch(findRole("seasonal"));
+      expect(role.assignmentCount).to.equal(1);
+    });
+  });
+
+  describe("105. Disabled Roles Behind Delegation and Quorum", () => {
+    const delegator = anchor.web3.Keypair.generate();
+    const delegate = anchor.web3.Keypair.generate();
+    const member = anchor.web3.Keypair.generate();
+    const quorumRoles = ["gated_quorum_a", "gated_quorum_b"];
+    const now = () => Math.floor(Date.now() / 1000);
+    const token = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("delegation"),
+        delegator.publicKey.toBuffer(),
+        delegate.publicKey.toBuffer(),
+        Buffer.from("delegated_ops"),
+      ],
+      program.programId
+    )[0];
+
+    const assertDelegated = () =>
+      program.methods
+        .assertHasPermission(PERM_UPDATE)
+        .accounts({ role: null, userRole: null, caller: delegate.publicKey, userStatus: findUserStatus(delegate.publicKey) })
+        .remainingAccounts([
+          { pubkey: token, isSigner: false, isWritable: false },
+          ...assignmentAccounts(delegator.publicKey, "delegated_ops"),
+          { pubkey: findUserStatus(delegator.publicKey), isSigner: false, isWritable: false },
+        ])
+        .signers([delegate])
+        .rpc();
+
+    const assertQuorum = () =>
+      program.methods
+        .assertRoleQuorum(member.publicKey, PERM_UPDATE, quorumRoles, 2)
+        .accounts({ rbacState })
+        .remainingAccounts(quorumRoles.flatMap((name) => assignmentAccounts(member.publicKey, name)))
+        .rpc();
+
+    const setValidity = (name: string, validFrom: anchor.BN | null, validUntil: anchor.BN | null) =>
+      program.methods
+        .setRoleValidity(name, validFrom, validUntil)
+        .accounts({ rbacState, role: findRole(name), admin: admin.publicKey })
+        .rpc();
+
+    before(async () => {
+      await airdrop(delegator.publicKey);
+      await airdrop(delegate.publicKey);
+      await createRole("delegated_ops", PERM_READ | PERM_UPDATE);
+      await assignRole(delegator.publicKey, "delegated_ops");
+      await program.methods
+        .delegatePermission(delegate.publicKey, "delegated_ops", PERM_UPDATE, new anchor.BN(now() + 3600))
+        .accounts({
+          rbacState,
+          role: findRole("delegated_ops"),
+          delegatorRole: findUserRole(delegator.publicKey, "delegated_ops"),
+          token,
+          delegator: delegator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([delegator])
+        .rpc();
+
+      for (const name of quorumRoles) {
+        await createRole(name, PERM_UPDATE);
+        await assignRole(member.publicKey, name);
+      }
+    });
+
+    it("Should deny a delegate of an emergency-disabled role", async () => {
+      await assertDelegated();
+      await program.methods
+        .emergencyDisableRole("delegated_ops")
+        .accounts({ rbacState, role: findRole("delegated_ops"), admin: admin.publicKey })
+        .rpc();
+      await expectError(assertDelegated(), "MissingAssignment");
+
+      await program.methods
+        .reenableRole("delegated_ops")
+        .accounts({ rbacState, role: findRole("delegated_ops"), admin: admin.publicKey })
+        .rpc();
+      await assertDelegated();
+    });
+
+    it("Should deny a delegate of a role outside its validity window", async () => {
+      await setValidity("delegated_ops", new anchor.BN(now() + 3600), null);
+      await expectError(assertDelegated(), "MissingAssignment");
+      await setValidity("delegated_ops", null, null);
+      await assertDelegated();
+    });
+
+    it("Should not count an inactive role towards a quorum", async () => {
+      await assertQuorum();
+      await program.methods
+        .setRoleActive(quorumRoles[0], false)
+        .accounts({ rbacState, role: findRole(quorumRoles[0]), admin: admin.publicKey })
+        .rpc();
+      await expectError(assertQuorum(), "QuorumNotMet");
+      await program.methods
+        .setRoleActive(quorumRoles[0], true)
+        .accounts({ rbacState, role: findRole(quorumRoles[0]), admin: admin.publicKey })
+        .rpc();
+    });
+
+    it("Should not count an emergency-disabled role towards a quorum", async () => {
+      await program.methods
+        .emergencyDisableRole(quorumRoles[0])
+        .accounts({ rbacState, role: findRole(quorumRoles[0]), admin: admin.publicKey })
+        .rpc();
+      await expectError(assertQuorum(), "QuorumNotMet");
+      await program.methods
+        .reenableRole(quorumRoles[0])
+        .accounts({ rbacState, role: findRole(quorumRoles[0]), admin: admin.publicKey })
+        .rpc();
+    });
+
+    it("Should not count a role outside its validity window towards a quorum", async () => {
+      await setValidity(quorumRoles[1], new anchor.BN(now() + 3600), null);
+      await expectError(assertQuorum(), "QuorumNotMet");
+      await setValidity(quorumRoles[1], null, null);
+      await assertQuorum();
+    });
+  });
+
+  describe("106. Denied Permissions Through Delegation", () => {
+    const delegator = anchor.web3.Keypair.generate();
+    const delegate = anchor.web3.Keypair.generate();
+    const findToken = (to: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("delegation"), delegator.publicKey.toBuffer(), to.toBuffer(), Buffer.from("deny_delegator")],
+        program.programId
+      )[0];
+
+    const delegateDelete = (to: anchor.web3.PublicKey) =>
+      program.methods
+        .delegatePermission(to, "deny_delegator", PERM_DELETE, new anchor.BN(Math.floor(Date.now() / 1000) + 3600))
+        .accounts({
+          rbacState,
+          role: findRole("deny_delegator"),
+          delegatorRole: findUserRole(delegator.publicKey, "deny_delegator"),
+          token: findToken(to),
+          delegator: delegator.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([delegator])
+        .rpc();
+
+    const assertDelegatedDelete = () =>
+      program.methods
+        .assertHasPermission(PERM_DELETE)
+        .accounts({ role: null, userRole: null, caller: delegate.publicKey, userStatus: findUserStatus(delegate.publicKey) })
+        .remainingAccounts([
+          { pubkey: findToken(delegate.publicKey), isSigner: false, isWritable: false },
+          ...assignmentAccounts(delegator.publicKey, "deny_delegator"),
+          { pubkey: findUserStatus(delegator.publicKey), isSigner: false, isWritable: false },
+        ])
+        .signers([delegate])
+        .rpc();
+
+    before(async () => {
+      await airdrop(delegator.publicKey);
+      await airdrop(delegate.publicKey);
+      await createRole("deny_delegator", PERM_READ | PERM_DELETE);
+      await assignRole(delegator.publicKey, "deny_delegator");
+      await delegateDelete(delegate.publicKey);
+    });
+
+    it("Should stop honoring a delegation once the delegator is denied the bit", async () => {
+      await assertDelegatedDelete();
+      await program.methods
+        .denyPermission(delegator.publicKey, "deny_delegator", PERM_DELETE)
+        .accounts({ rbacState, userRole: findUserRole(delegator.publicKey, "deny_delegator"), admin: admin.publicKey })
+        .rpc();
+      await expectError(assertDelegatedDelete(), "MissingAssignment");
+    });
+
+    it("Should refuse to delegate a denied bit", async () => {
+      await expectError(delegateDelete(anchor.web3.Keypair.generate().publicKey), "DelegationExceedsRole");
+    });
+  });
+
+  // Keep this section last: sealing is irreversible for the rest of the suite
+  describe("107. Sealed Configuration", () => {
+    const stranger = anchor.web3.Keypair.generate();
+    const holder = anchor.web3.Keypair.generate();
+
+    const sealConfig = () =>
+      program.methods.sealConfig().accounts({ rbacState, admin: admin.publicKey }).rpc();
+
+    before(async () => {
+      await assignRole(holder.publicKey, "user");
+      await sealConfig();
+    });
+
+    it("Should mark the state as sealed", async () => {
+      const state = await program.account.rbacState.fetch(rbacState);
+      expect(state.sealed).to.be.true;
+    });
+
+    it("Should reject role and assignment mutations", async () => {
+      await expectError(createRole("after_seal", PERM_READ), "ConfigSealed");
+      await expectError(assignRole(stranger.publicKey, "user"), "ConfigSealed");
+      await expectError(
+        program.methods
+          .revokeRole("user", false)
+          .accounts({
+            rbacState,
+            role: findRole("user"),
+            userRole: findUserRole(holder.publicKey, "user"),
+            authority: admin.publicKey,
+          })
+          .rpc(),
+        "ConfigSealed"
+      );
+    });
+
+    it("Should reject config and admin changes, including unsealing paths", async () => {
+      await expectError(
+        program.methods.pause().accounts({ rbacState, admin: admin.publicKey }).rpc(),
+        "ConfigSealed"
+      );
+      await expectError(
+        program.methods.setConfigFrozen(false).accounts({ rbacState, admin: admin.publicKey }).rpc(),
+        "ConfigSealed"
+      );
+      await expectError(
+        program.methods.addAdmin(stranger.publicKey).accounts({ rbacState, admin: admin.publicKey }).rpc(),
+        "ConfigSealed"
+      );
+      await expectError(sealConfig(), "ConfigSealed");
+    });
+
+    it("Should keep permission checks working", async () => {
+      expect(await checkPermission(holder.publicKey, "user", PERM_READ)).to.be.true;
+    });
+  });
+});